@@ -1,5 +1,5 @@
 VERSION="1.16.3"
-DATA_FILES=("blocks.min.json" "entities.min.json" "block_entities.min.json" "models.min.json")
+DATA_FILES=("blocks.min.json" "entities.min.json" "block_entities.min.json" "models.min.json" "items.min.json" "registries.min.json" "shapes.min.json" "biomes.min.json")
 
 mkdir assets
 for file in ${DATA_FILES[@]}; do
@@ -1,25 +1,112 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
 use mcproto_rs::v1_16_3::PlayServerChatMessageSpec;
+use thiserror::Error;
+
+pub mod chat_log;
+
+/// How many sent messages are remembered to be recalled with the arrow keys
+const SENT_HISTORY_SIZE: usize = 100;
+/// The longest chat message or command the server accepts, in UTF-16 code units like Java counts
+/// them. See `message_length`.
+pub const MAX_MESSAGE_LENGTH: usize = 256;
+/// The longest chat message that can be written, which is split up to send. Commands can't be
+/// split so they're held to `MAX_MESSAGE_LENGTH`.
+const MAX_INPUT_LENGTH: usize = MAX_MESSAGE_LENGTH * 8;
+/// Messages from the same sender within this many seconds of each other are grouped together
+const GROUP_TIME: i64 = 5;
+/// How many received messages are kept unless the settings say otherwise
+pub const DEFAULT_MAX_HISTORY: usize = 500;
+
+/// A message from the server, kept to show in the chat
+pub struct ReceivedMessage {
+    pub message: PlayServerChatMessageSpec,
+    /// The world time it arrived at
+    pub time: i64,
+    /// When it arrived by the clock, for showing
+    pub received: DateTime<Local>,
+    /// If it mentions the player's name or one of their highlight keywords
+    pub mention: bool,
+}
+
+impl ReceivedMessage {
+    /// If this message carries on from `previous`, being from the same sender shortly after it
+    #[must_use]
+    pub fn follows(&self, previous: &ReceivedMessage) -> bool {
+        self.message.sender == previous.message.sender
+            && (self.received - previous.received).num_seconds() < GROUP_TIME
+    }
+}
 
 pub struct Chat {
-    history: Vec<(PlayServerChatMessageSpec, i64)>,
+    /// Received messages, oldest first
+    history: VecDeque<ReceivedMessage>,
+    /// The most messages `history` keeps before dropping the oldest
+    max_history: usize,
 
     input: String,
+
+    /// Messages and commands sent this session, oldest first
+    sent: VecDeque<String>,
+    /// Index into `sent` of the message currently being recalled, if any
+    sent_index: Option<usize>,
+    /// The message that was being written before recalling sent ones
+    draft: Option<String>,
+
+    /// How many messages from ignored players have been dropped
+    suppressed: usize,
 }
 
 impl Chat {
     pub fn new() -> Chat {
         Chat {
-            history: Vec::with_capacity(255),
+            history: VecDeque::with_capacity(DEFAULT_MAX_HISTORY),
+            max_history: DEFAULT_MAX_HISTORY,
             input: String::with_capacity(255),
+
+            sent: VecDeque::new(),
+            sent_index: None,
+            draft: None,
+
+            suppressed: 0,
         }
     }
 
-    pub fn get_history(&self) -> &Vec<(PlayServerChatMessageSpec, i64)> {
+    pub fn get_history(&self) -> &VecDeque<ReceivedMessage> {
         &self.history
     }
 
-    pub fn add_message(&mut self, chat: PlayServerChatMessageSpec, time: i64) {
-        self.history.push((chat, time));
+    /// Keep a received message, dropping the oldest once there are more than `max_history`
+    pub fn add_message(&mut self, message: PlayServerChatMessageSpec, time: i64, mention: bool) {
+        if self.history.len() >= self.max_history {
+            self.history.pop_front();
+        }
+        self.history.push_back(ReceivedMessage {
+            message,
+            time,
+            received: Local::now(),
+            mention,
+        });
+    }
+
+    /// Count a message from an ignored player that was dropped
+    pub fn suppress_message(&mut self) {
+        self.suppressed += 1;
+    }
+
+    #[must_use]
+    pub const fn get_suppressed(&self) -> usize {
+        self.suppressed
+    }
+
+    /// Change how many received messages are kept, dropping the oldest if there are too many.
+    /// At least one is always kept.
+    pub fn set_max_history(&mut self, max: usize) {
+        self.max_history = max.max(1);
+        while self.history.len() > self.max_history {
+            self.history.pop_front();
+        }
     }
 
     pub fn get_current_message(&self) -> &String {
@@ -40,4 +127,416 @@ impl Chat {
     pub fn set_current_message(&mut self, text: String) {
         self.input = text;
     }
+
+    /// Add typed or pasted text to the end of the message being written, leaving out characters
+    /// servers won't accept and stopping at the length limit
+    pub fn type_text(&mut self, text: &str) {
+        let command =
+            self.input.starts_with('/') || (self.input.is_empty() && text.starts_with('/'));
+        let limit = if command {
+            MAX_MESSAGE_LENGTH
+        } else {
+            MAX_INPUT_LENGTH
+        };
+
+        let mut room = limit.saturating_sub(message_length(&self.input));
+        for c in sanitise(text).chars() {
+            let Some(left) = room.checked_sub(c.len_utf16()) else {
+                break;
+            };
+            room = left;
+            self.input.push(c);
+        }
+    }
+
+    /// Remove the last character of the message being written
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    pub fn get_sent_messages(&self) -> &VecDeque<String> {
+        &self.sent
+    }
+
+    /// Remember a message that was sent so it can be recalled later. Empty messages and repeats of
+    /// the last one are ignored, and only the most recent `SENT_HISTORY_SIZE` are kept.
+    pub fn push_history(&mut self, text: String) {
+        self.reset_recall();
+        if text.is_empty() || self.sent.back() == Some(&text) {
+            return;
+        }
+        if self.sent.len() >= SENT_HISTORY_SIZE {
+            self.sent.pop_front();
+        }
+        self.sent.push_back(text);
+    }
+
+    /// Replaces the current message with the previously sent message, stopping at the oldest. The
+    /// message being written is kept to come back to.
+    pub fn history_prev(&mut self) {
+        if self.sent.is_empty() {
+            return;
+        }
+
+        let index = match self.sent_index {
+            Some(i) => i.saturating_sub(1),
+            None => {
+                self.draft = Some(self.input.clone());
+                self.sent.len() - 1
+            }
+        };
+        self.sent_index = Some(index);
+        self.set_current_message(self.sent[index].clone());
+    }
+
+    /// Replaces the current message with the next sent message, or goes back to the message that
+    /// was being written when moving past the most recent one
+    pub fn history_next(&mut self) {
+        match self.sent_index {
+            Some(i) if i + 1 < self.sent.len() => {
+                self.sent_index = Some(i + 1);
+                self.set_current_message(self.sent[i + 1].clone());
+            }
+            Some(_) => {
+                self.sent_index = None;
+                self.input = self.draft.take().unwrap_or_default();
+            }
+            None => {}
+        }
+    }
+
+    /// Stop recalling sent messages, e.g. when the chat is closed
+    pub fn reset_recall(&mut self) {
+        self.sent_index = None;
+        self.draft = None;
+    }
+}
+
+/// Replace line breaks and tabs with spaces and remove the other characters servers kick players
+/// for sending, i.e. control characters and `§`
+#[must_use]
+pub fn sanitise(text: &str) -> String {
+    text.chars()
+        .map(|c| if c == '\n' || c == '\t' { ' ' } else { c })
+        .filter(|c| *c != '§' && !c.is_control())
+        .collect()
+}
+
+/// How long some text is to the server. Java strings are UTF-16, so characters outside the basic
+/// multilingual plane such as emoji count twice.
+#[must_use]
+pub fn message_length(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// A command that can't be sent as it's over `MAX_MESSAGE_LENGTH`, with its length
+#[derive(Debug, Error)]
+#[error("Command is {0} characters long but the server only accepts {MAX_MESSAGE_LENGTH}")]
+pub struct CommandTooLong(pub usize);
+
+/// Break a message up into pieces the server will accept, at spaces where possible. Commands
+/// can't be split, so ones over `MAX_MESSAGE_LENGTH` are an error rather than being cut short.
+pub fn split_message(text: &str) -> Result<Vec<String>, CommandTooLong> {
+    let text = sanitise(text);
+    if text.starts_with('/') {
+        let length = message_length(&text);
+        if length > MAX_MESSAGE_LENGTH {
+            return Err(CommandTooLong(length));
+        }
+        return Ok(vec![text]);
+    }
+
+    let mut parts = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        // Byte index of the end of the longest piece that fits
+        let mut length = 0;
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| {
+                length += c.len_utf16();
+                length > MAX_MESSAGE_LENGTH
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        // Break at the last space if it doesn't end on one already, or mid word if there isn't one
+        let split = if end == rest.len() || rest[end..].starts_with(' ') {
+            end
+        } else {
+            rest[..end].rfind(' ').filter(|i| *i > 0).unwrap_or(end)
+        };
+
+        parts.push(rest[..split].trim_end().to_string());
+        rest = rest[split..].trim_start();
+    }
+    Ok(parts)
+}
+
+/// If some chat text mentions any of `words`, ignoring case, colour codes and matches inside
+/// other words
+#[must_use]
+pub fn mentions<S: AsRef<str>>(text: &str, words: &[S]) -> bool {
+    let text = strip_codes(text).to_lowercase();
+    words
+        .iter()
+        .map(|w| w.as_ref().trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .any(|word| contains_word(&text, &word))
+}
+
+/// Remove legacy `§` colour and formatting codes from some text
+#[must_use]
+pub fn strip_codes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// If `word` appears in `text` without a letter, digit or underscore either side of it
+fn contains_word(text: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    text.match_indices(word).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mcproto_rs::{types::Chat as ChatComponent, uuid::UUID4, v1_16_3::ChatPosition};
+
+    use super::*;
+
+    /// Receive `count` messages, each at the world time of its index
+    fn receive(chat: &mut Chat, count: usize) {
+        let sender = UUID4::random();
+        for i in 0..count {
+            let message = PlayServerChatMessageSpec {
+                message: ChatComponent::from_text("spam"),
+                position: ChatPosition::ChatBox,
+                sender,
+            };
+            chat.add_message(message, i as i64, false);
+        }
+    }
+
+    /// A chat with `sent` already sent and `draft` being written
+    fn chat_with(sent: &[&str], draft: &str) -> Chat {
+        let mut chat = Chat::new();
+        for text in sent {
+            chat.push_history((*text).to_string());
+        }
+        chat.set_current_message(draft.to_string());
+        chat
+    }
+
+    #[test]
+    fn recalling_keeps_the_draft() {
+        let mut chat = chat_with(&["first", "second"], "half writ");
+
+        chat.history_prev();
+        assert_eq!(chat.get_current_message(), "second");
+        chat.history_prev();
+        assert_eq!(chat.get_current_message(), "first");
+        chat.history_prev();
+        assert_eq!(chat.get_current_message(), "first");
+
+        chat.history_next();
+        assert_eq!(chat.get_current_message(), "second");
+        chat.history_next();
+        assert_eq!(chat.get_current_message(), "half writ");
+        chat.history_next();
+        assert_eq!(chat.get_current_message(), "half writ");
+    }
+
+    #[test]
+    fn editing_a_recalled_message_keeps_the_draft() {
+        let mut chat = chat_with(&["first"], "");
+
+        chat.history_prev();
+        chat.type_text(" again");
+        assert_eq!(chat.get_current_message(), "first again");
+        chat.history_next();
+        assert_eq!(chat.get_current_message(), "");
+    }
+
+    #[test]
+    fn recall_without_history_does_nothing() {
+        let mut chat = chat_with(&[], "draft");
+
+        chat.history_prev();
+        assert_eq!(chat.get_current_message(), "draft");
+        chat.history_next();
+        assert_eq!(chat.get_current_message(), "draft");
+    }
+
+    #[test]
+    fn sending_stops_recalling() {
+        let mut chat = chat_with(&["first", "second"], "draft");
+
+        chat.history_prev();
+        chat.history_prev();
+        chat.push_history("third".to_string());
+        chat.set_current_message(String::new());
+
+        chat.history_prev();
+        assert_eq!(chat.get_current_message(), "third");
+        chat.history_next();
+        assert_eq!(chat.get_current_message(), "");
+    }
+
+    #[test]
+    fn repeats_and_empty_messages_are_not_remembered() {
+        let chat = chat_with(&["hi", "hi", "", "/help", "hi"], "");
+
+        assert_eq!(chat.get_sent_messages(), &["hi", "/help", "hi"]);
+    }
+
+    #[test]
+    fn sent_history_is_capped() {
+        let mut chat = Chat::new();
+        for i in 0..SENT_HISTORY_SIZE + 10 {
+            chat.push_history(format!("message {i}"));
+        }
+
+        let sent = chat.get_sent_messages();
+        assert_eq!(sent.len(), SENT_HISTORY_SIZE);
+        assert_eq!(sent.front().map(String::as_str), Some("message 10"));
+        assert_eq!(
+            sent.back(),
+            Some(&format!("message {}", SENT_HISTORY_SIZE + 9))
+        );
+
+        for _ in 0..SENT_HISTORY_SIZE + 5 {
+            chat.history_prev();
+        }
+        assert_eq!(chat.get_current_message(), "message 10");
+    }
+
+    #[test]
+    fn received_history_is_bounded() {
+        let mut chat = Chat::new();
+        receive(&mut chat, 100_000);
+
+        let history = chat.get_history();
+        assert_eq!(history.len(), DEFAULT_MAX_HISTORY);
+        assert!(history.capacity() < DEFAULT_MAX_HISTORY * 2);
+        assert_eq!(history.front().map(|m| m.time), Some(99_500));
+        assert_eq!(history.back().map(|m| m.time), Some(99_999));
+    }
+
+    #[test]
+    fn changing_max_history_drops_the_oldest() {
+        let mut chat = Chat::new();
+        receive(&mut chat, 100);
+
+        chat.set_max_history(10);
+        let history = chat.get_history();
+        assert_eq!(history.len(), 10);
+        assert_eq!(history.front().map(|m| m.time), Some(90));
+
+        chat.set_max_history(0);
+        receive(&mut chat, 100_000);
+        assert_eq!(chat.get_history().len(), 1);
+        assert_eq!(chat.get_history().back().map(|m| m.time), Some(99_999));
+    }
+
+    /// The length of each piece a message is split into
+    fn split_lengths(text: &str) -> Vec<usize> {
+        split_message(text)
+            .unwrap()
+            .iter()
+            .map(|part| message_length(part))
+            .collect()
+    }
+
+    #[test]
+    fn short_messages_are_kept_whole() {
+        assert_eq!(split_message("  hello there ").unwrap(), ["hello there"]);
+        assert!(split_message(" ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn splits_at_spaces() {
+        let text = ["word"; 100].join(" ");
+        let parts = split_message(&text).unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert!(parts
+            .iter()
+            .all(|p| message_length(p) <= MAX_MESSAGE_LENGTH && !p.starts_with(' ')));
+        assert!(parts.iter().all(|p| p.ends_with("word")));
+        assert_eq!(parts.join(" "), text);
+    }
+
+    #[test]
+    fn splits_at_a_space_right_after_the_limit() {
+        let first = "a".repeat(MAX_MESSAGE_LENGTH);
+        let parts = split_message(&format!("{first} b")).unwrap();
+
+        assert_eq!(parts, [first, "b".to_string()]);
+    }
+
+    #[test]
+    fn splits_mid_word_without_spaces() {
+        assert_eq!(split_lengths(&"a".repeat(600)), [256, 256, 88]);
+        assert_eq!(split_lengths(&format!(" {}", "a".repeat(300))), [256, 44]);
+    }
+
+    #[test]
+    fn multi_byte_characters_count_once() {
+        let parts = split_message(&"é".repeat(300)).unwrap();
+
+        assert_eq!(parts, ["é".repeat(256), "é".repeat(44)]);
+    }
+
+    #[test]
+    fn astral_characters_count_twice() {
+        let parts = split_message(&format!("a{}", "😀".repeat(200))).unwrap();
+
+        assert_eq!(parts, [format!("a{}", "😀".repeat(127)), "😀".repeat(73)]);
+        assert_eq!(split_lengths(&"😀".repeat(200)), [256, 144]);
+    }
+
+    #[test]
+    fn sanitises_before_splitting() {
+        assert_eq!(split_message("§ctab\there\n\u{7}").unwrap(), ["ctab here"]);
+    }
+
+    #[test]
+    fn commands_are_never_split() {
+        let command = format!("/say {}", "word ".repeat(50));
+        assert_eq!(message_length(&command), 255);
+        assert_eq!(split_message(&command).unwrap(), [command]);
+
+        let command = format!("/say {}", "x".repeat(252));
+        assert!(matches!(split_message(&command), Err(CommandTooLong(257))));
+
+        let command = format!("/say {}", "😀".repeat(126));
+        assert!(matches!(split_message(&command), Err(CommandTooLong(257))));
+    }
+
+    #[test]
+    fn typing_stops_at_the_limit() {
+        let mut chat = Chat::new();
+        chat.type_text("/");
+        chat.type_text(&"😀".repeat(200));
+        assert_eq!(message_length(chat.get_current_message()), 255);
+
+        chat.type_text("ab");
+        assert_eq!(message_length(chat.get_current_message()), 256);
+        assert!(chat.get_current_message().ends_with("😀a"));
+
+        let mut chat = Chat::new();
+        chat.type_text(&"é".repeat(MAX_INPUT_LENGTH + 1));
+        assert_eq!(chat.get_current_message().chars().count(), MAX_INPUT_LENGTH);
+    }
 }
@@ -0,0 +1,138 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use chrono::{Local, NaiveDate};
+
+use crate::settings::{locate_config_directory, Error};
+
+/// How often the log is written out to disk, in seconds
+const FLUSH_PERIOD: f64 = 5.0;
+
+/// Writes the chat on a server to a log file for each day, under
+/// `logs/<server>/<date>.log` in the config directory. Files are only opened once there's
+/// something to write.
+pub struct ChatLog {
+    server: String,
+    enabled: bool,
+    /// The open log file and the day it's for
+    file: Option<(NaiveDate, BufWriter<File>)>,
+    flush_timer: f64,
+    /// Set after the first error so a broken log only warns once
+    failed: bool,
+}
+
+impl ChatLog {
+    #[must_use]
+    pub fn new(server: &str) -> Self {
+        Self {
+            server: sanitise(server),
+            enabled: false,
+            file: None,
+            flush_timer: 0.0,
+            failed: false,
+        }
+    }
+
+    /// Turn logging on or off, closing the file when it's turned off
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled && !enabled {
+            self.flush();
+            self.file = None;
+        }
+        self.enabled = enabled;
+    }
+
+    /// Log a message the server sent
+    pub fn log_received(&mut self, text: &str) {
+        self.write_line("", text);
+    }
+
+    /// Log a message or command the player sent
+    pub fn log_sent(&mut self, text: &str) {
+        self.write_line("> ", text);
+    }
+
+    /// Writes the log out every `FLUSH_PERIOD` seconds
+    pub fn update(&mut self, delta: f64) {
+        self.flush_timer += delta;
+        if self.flush_timer >= FLUSH_PERIOD {
+            self.flush_timer = 0.0;
+            self.flush();
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let Some((_, file)) = &mut self.file {
+            if let Err(e) = file.flush() {
+                self.fail(&e.into());
+            }
+        }
+    }
+
+    fn write_line(&mut self, marker: &str, text: &str) {
+        if !self.enabled || self.failed {
+            return;
+        }
+
+        let now = Local::now();
+        let result = self.file_for(now.date_naive()).and_then(|file| {
+            writeln!(file, "[{}] {marker}{text}", now.format("%H:%M:%S")).map_err(Error::from)
+        });
+        if let Err(e) = result {
+            self.fail(&e);
+        }
+    }
+
+    /// The file for the given day, moving on to a new one when the day changes
+    fn file_for(&mut self, date: NaiveDate) -> Result<&mut BufWriter<File>, Error> {
+        let file = match self.file.take() {
+            Some((day, file)) if day == date => file,
+            old => {
+                if let Some((_, mut old)) = old {
+                    old.flush()?;
+                }
+                let dir = log_directory()?.join(&self.server);
+                std::fs::create_dir_all(&dir)?;
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(dir.join(format!("{}.log", date.format("%Y-%m-%d"))))?;
+                BufWriter::new(file)
+            }
+        };
+        Ok(&mut self.file.insert((date, file)).1)
+    }
+
+    fn fail(&mut self, e: &Error) {
+        if !self.failed {
+            tracing::warn!("Failed to write chat log, no more chat will be logged: {e}");
+        }
+        self.failed = true;
+        self.file = None;
+    }
+}
+
+/// The directory all the chat logs go in
+///
+/// # Errors
+/// If there's no config directory
+pub fn log_directory() -> Result<PathBuf, Error> {
+    Ok(locate_config_directory()?.join("logs"))
+}
+
+/// Make a server's address safe to use as a directory name
+fn sanitise(server: &str) -> String {
+    server
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -1,14 +1,24 @@
 pub mod components;
+pub mod equipment;
+pub mod metadata;
 use components::*;
 use glam::DVec3;
 use mcproto_rs::uuid::UUID4;
 
+use crate::{
+    player::physics::Aabb,
+    resources::{self, entities},
+};
+
+use self::{equipment::Equipment, metadata::EntityMetadata};
+
 pub struct Entity {
     pub id: i32,
     pub uuid: UUID4,
 
-    // pub entity_type: &'static resources::Entity,
     pub entity_type: u32,
+    /// What we know about the entity's type, None if the id isn't in the resources
+    kind: Option<&'static resources::Entity>,
 
     pub data: i32,
 
@@ -19,6 +29,8 @@ pub struct Entity {
     pub ori_head: Orientation,
 
     pub on_ground: bool,
+    pub metadata: EntityMetadata,
+    pub equipment: Equipment,
 }
 
 impl Entity {
@@ -27,10 +39,8 @@ impl Entity {
             id: 0,
             uuid: UUID4::random(),
 
-            // entity_type: ENTITIES
-            //     .get(&entity_type)
-            //     .expect(&format!("No entity with id {}", &entity_type)),
             entity_type,
+            kind: entity_kind(entity_type),
             data: 0,
 
             pos: DVec3::new(0.0, 0.0, 0.0),
@@ -40,6 +50,8 @@ impl Entity {
             ori_head: Orientation::new(),
 
             on_ground: true,
+            metadata: EntityMetadata::default(),
+            equipment: Equipment::default(),
         }
     }
 
@@ -61,10 +73,8 @@ impl Entity {
         Entity {
             id,
             uuid,
-            // entity_type: ENTITIES
-            //     .get(&entity_type)
-            //     .expect(&format!("Failed to get entity from ID: {}", entity_type)),
             entity_type,
+            kind: entity_kind(entity_type),
             data,
             pos: DVec3::new(px, py, pz),
             last_pos: DVec3::new(px, py, pz),
@@ -72,9 +82,35 @@ impl Entity {
             ori: Orientation::new_with_values(yaw, pitch, 0.0, 0.0),
             ori_head: Orientation::new_with_values(0.0, head_pitch, -90.0, 90.0),
             on_ground: true,
+            metadata: EntityMetadata::default(),
+            equipment: Equipment::default(),
         }
     }
 
+    /// The box the entity takes up, if we know its size
+    pub fn get_bounding_box(&self) -> Option<Aabb> {
+        let kind = self.kind?;
+        Some(Aabb::entity(
+            self.pos,
+            f64::from(kind.width),
+            f64::from(kind.height),
+        ))
+    }
+
+    /// How far above its feet the entity sees from, which like vanilla is most of its height
+    pub fn get_eye_height(&self) -> f64 {
+        self.kind.map_or(0.0, |kind| f64::from(kind.height) * 0.85)
+    }
+
+    /// Which way the entity is looking as a yaw and pitch in degrees. Orientations are kept as
+    /// fractions of a turn straight from the protocol's angles, where pitches above the horizon
+    /// wrap around.
+    pub fn get_look_degrees(&self) -> (f64, f64) {
+        let yaw = self.ori.get_yaw() * 360.0;
+        let pitch = self.ori.get_pitch() * 360.0;
+        (yaw, if pitch > 180.0 { pitch - 360.0 } else { pitch })
+    }
+
     pub fn get_id(&self) -> i32 {
         self.id
     }
@@ -83,9 +119,15 @@ impl Entity {
         self.uuid
     }
 
-    // pub fn get_type(&self) -> &'static resources::Entity {
-    //     self.entity_type
-    // }
+    /// The entity's type, None if it isn't one we know about
+    pub fn get_type(&self) -> Option<&'static resources::Entity> {
+        self.kind
+    }
+
+    /// What kind of entity this is, for showing to the player
+    pub fn get_type_name(&self) -> &'static str {
+        self.kind.map_or("Unknown", |kind| kind.name.as_str())
+    }
 
     pub fn update(&mut self, delta: f64) {
         let mut vel = self.vel;
@@ -99,6 +141,15 @@ impl Entity {
     }
 }
 
+/// Look up an entity type, which servers with extra entities might send ids we don't have for
+fn entity_kind(entity_type: u32) -> Option<&'static resources::Entity> {
+    let kind = entities().get(&entity_type);
+    if kind.is_none() {
+        tracing::debug!("Unknown entity type {entity_type}");
+    }
+    kind
+}
+
 /*
 pub fn hitbox_model() -> Vec<Vertex> {
     vec![
@@ -0,0 +1,116 @@
+use mcproto_rs::{types::Slot, v1_16_3::EquipmentSlot};
+
+use crate::player::inventory::ItemStack;
+
+/// The items an entity is holding and wearing
+#[derive(Debug, Default)]
+pub struct Equipment {
+    pub main_hand: Option<ItemStack>,
+    pub off_hand: Option<ItemStack>,
+    pub boots: Option<ItemStack>,
+    pub leggings: Option<ItemStack>,
+    pub chestplate: Option<ItemStack>,
+    pub helmet: Option<ItemStack>,
+}
+
+impl Equipment {
+    pub fn set(&mut self, slot: &EquipmentSlot, item: &Slot) {
+        let item = ItemStack::from_slot(item);
+        match slot {
+            EquipmentSlot::MainHand => self.main_hand = item,
+            EquipmentSlot::OffHand => self.off_hand = item,
+            EquipmentSlot::ArmorBoots => self.boots = item,
+            EquipmentSlot::ArmorLeggings => self.leggings = item,
+            EquipmentSlot::ArmorChestplate => self.chestplate = item,
+            EquipmentSlot::ArmorHelmet => self.helmet = item,
+        }
+    }
+
+    /// Each slot that has something in it, labelled with the slot's name
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &ItemStack)> {
+        [
+            ("Main hand", &self.main_hand),
+            ("Off hand", &self.off_hand),
+            ("Helmet", &self.helmet),
+            ("Chestplate", &self.chestplate),
+            ("Leggings", &self.leggings),
+            ("Boots", &self.boots),
+        ]
+        .into_iter()
+        .filter_map(|(name, item)| item.as_ref().map(|item| (name, item)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mcproto_rs::protocol::{Id, PacketDirection, RawPacket, State};
+
+    use crate::network::{PacketType, RawPacketType};
+
+    use super::*;
+
+    /// Entity Equipment packet id in the play state
+    const ENTITY_EQUIPMENT_ID: i32 = 0x47;
+    /// Entity 42 holding 64 of item 1 and wearing item 600 as a helmet. Every entry but the last
+    /// has the top bit of its slot set to say another one follows
+    const PAYLOAD: [u8; 12] = [
+        0x2a, // Entity id
+        0x80, 0x01, 0x01, 0x40, 0x00, // Main hand, present, item 1, count 64, no NBT
+        0x05, 0x01, 0xd8, 0x04, 0x01, 0x00, // Helmet, present, item 600, count 1, no NBT
+    ];
+
+    fn equipment_from(payload: &[u8]) -> (i32, Equipment) {
+        let id = Id {
+            id: ENTITY_EQUIPMENT_ID,
+            state: State::Play,
+            direction: PacketDirection::ClientBound,
+        };
+        let packet = RawPacketType::create(id, payload)
+            .and_then(|raw| raw.deserialize())
+            .unwrap();
+        let PacketType::PlayEntityEquipment(pack) = packet else {
+            panic!("Not an entity equipment packet: {packet:?}");
+        };
+
+        let mut equipment = Equipment::default();
+        for entry in pack.equipment.iter() {
+            equipment.set(&entry.slot, &entry.item);
+        }
+        (pack.entity_id.0, equipment)
+    }
+
+    #[test]
+    fn multiple_slots_in_one_packet() {
+        let (entity, equipment) = equipment_from(&PAYLOAD);
+        assert_eq!(entity, 42);
+
+        let main_hand = equipment.main_hand.as_ref().unwrap();
+        assert_eq!((main_hand.item_id, main_hand.count), (1, 64));
+        let helmet = equipment.helmet.as_ref().unwrap();
+        assert_eq!((helmet.item_id, helmet.count), (600, 1));
+        assert!(equipment.off_hand.is_none());
+        assert!(equipment.boots.is_none());
+        assert!(equipment.leggings.is_none());
+        assert!(equipment.chestplate.is_none());
+    }
+
+    #[test]
+    fn empty_slot_clears_item() {
+        let (_, mut equipment) = equipment_from(&PAYLOAD);
+        equipment.set(&EquipmentSlot::MainHand, &None);
+
+        assert!(equipment.main_hand.is_none());
+        assert!(equipment.helmet.is_some());
+    }
+
+    #[test]
+    fn iter_only_filled_slots() {
+        let (_, equipment) = equipment_from(&PAYLOAD);
+        let slots: Vec<_> = equipment
+            .iter()
+            .map(|(name, item)| (name, item.item_id))
+            .collect();
+
+        assert_eq!(slots, [("Main hand", 1), ("Helmet", 600)]);
+    }
+}
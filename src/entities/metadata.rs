@@ -0,0 +1,78 @@
+use mcproto_rs::v1_16_3::{EntityMetadata as PacketMetadata, EntityMetadataFieldData, EntityPose};
+
+use crate::player::inventory::ItemStack;
+
+/// Bits of the flags byte every entity has at index 0
+const ON_FIRE: u8 = 0x01;
+const CROUCHING: u8 = 0x02;
+const SPRINTING: u8 = 0x08;
+const SWIMMING: u8 = 0x10;
+const INVISIBLE: u8 = 0x20;
+const GLOWING: u8 = 0x40;
+
+/// The parts of an entity's metadata we understand, from the base Entity and Living Entity
+/// indices (https://wiki.vg/Entity_metadata). Anything else is left as it was.
+#[derive(Debug, Default)]
+pub struct EntityMetadata {
+    flags: u8,
+    /// In the legacy `§` coded format
+    pub custom_name: Option<String>,
+    pub custom_name_visible: bool,
+    pub silent: bool,
+    pub no_gravity: bool,
+    pub pose: Option<EntityPose>,
+    /// Only living entities have health
+    pub health: Option<f32>,
+    /// What an item frame is holding, or the item a dropped item entity is
+    pub item: Option<ItemStack>,
+}
+
+impl EntityMetadata {
+    /// Apply the fields from a metadata packet. Fields are decoded by mcproto along with their
+    /// types, so indices we don't know about, or that mean something else for this kind of
+    /// entity, are skipped without upsetting the rest.
+    pub fn update(&mut self, metadata: &PacketMetadata) {
+        for field in &metadata.fields {
+            match (field.index, &field.data) {
+                (0, EntityMetadataFieldData::Byte(flags)) => self.flags = *flags as u8,
+                (2, EntityMetadataFieldData::OptChat(name)) => {
+                    self.custom_name = name.as_ref().and_then(|name| name.to_traditional());
+                }
+                (3, EntityMetadataFieldData::Boolean(visible)) => {
+                    self.custom_name_visible = *visible;
+                }
+                (4, EntityMetadataFieldData::Boolean(silent)) => self.silent = *silent,
+                (5, EntityMetadataFieldData::Boolean(no_gravity)) => self.no_gravity = *no_gravity,
+                (6, EntityMetadataFieldData::Pose(pose)) => self.pose = Some(pose.clone()),
+                (7, EntityMetadataFieldData::Slot(slot)) => self.item = ItemStack::from_slot(slot),
+                (8, EntityMetadataFieldData::Float(health)) => self.health = Some(*health),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn is_on_fire(&self) -> bool {
+        self.flags & ON_FIRE != 0
+    }
+
+    pub fn is_crouching(&self) -> bool {
+        self.flags & CROUCHING != 0
+    }
+
+    pub fn is_sprinting(&self) -> bool {
+        self.flags & SPRINTING != 0
+    }
+
+    pub fn is_swimming(&self) -> bool {
+        self.flags & SWIMMING != 0
+    }
+
+    /// Invisible entities shouldn't be drawn
+    pub fn is_invisible(&self) -> bool {
+        self.flags & INVISIBLE != 0
+    }
+
+    pub fn is_glowing(&self) -> bool {
+        self.flags & GLOWING != 0
+    }
+}
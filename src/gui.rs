@@ -1,16 +1,22 @@
 #![allow(dead_code)]
 
-use egui::{Align2, Context, Id, Vec2};
+use egui::{Align2, Color32, Context, Id, LayerId, Order, Stroke, Vec2};
 use wgpu_app::{utils::persistent_window::PersistentWindow, Timer};
 
 use crate::{server::InputState, App};
 
 use self::other_windows::fps_counter;
 
+pub mod boss_bar_windows;
 pub mod chat_windows;
+pub mod clipboard;
+pub mod container_windows;
+pub mod death_windows;
+pub mod hud;
 pub mod info_windows;
 pub mod other_windows;
 pub mod pause_windows;
+pub mod title_windows;
 
 pub mod main_menu;
 
@@ -18,14 +24,53 @@ pub fn render(gui_ctx: &Context, cli: &mut App, t: &Timer) {
     match &mut cli.server {
         Some(s) => {
             if cli.settings.show_fps {
-                fps_counter::render(gui_ctx, t.fps(), t.delta());
+                fps_counter::render(gui_ctx, t.fps(), t.delta(), s.get_own_ping());
             }
 
-            s.render(gui_ctx, &mut cli.window_manager);
+            if cli.settings.show_hud
+                && matches!(
+                    s.get_input_state(),
+                    InputState::Playing | InputState::ShowingInfo
+                )
+            {
+                hud::render(gui_ctx, s.get_player());
+                hud::toggle_indicators(
+                    gui_ctx,
+                    cli.settings.toggle_sneak && s.is_sneak_toggled(),
+                    cli.settings.toggle_sprint && s.is_sprint_toggled(),
+                );
+                if let Some(name) = s.get_spectating_name() {
+                    hud::spectating(gui_ctx, &name);
+                }
+            }
+
+            if matches!(
+                s.get_input_state(),
+                InputState::Playing | InputState::ShowingInfo
+            ) {
+                hud::border_warning(
+                    gui_ctx,
+                    s.get_world().border(),
+                    s.get_player().get_position(),
+                );
+                crosshair(gui_ctx);
+            }
+
+            if matches!(
+                s.get_input_state(),
+                InputState::ShowingInfo | InputState::InteractingInfo
+            ) {
+                info_windows::debug_window::render(gui_ctx, s, cli.renderer.as_ref(), t.fps());
+            }
+
+            s.render(gui_ctx, &mut cli.window_manager, &mut cli.settings);
         }
         None => match main_menu::render(gui_ctx, cli) {
             Some(mut s) => {
                 s.set_input_state(InputState::Playing);
+                if let Some(renderer) = &mut cli.renderer {
+                    renderer.get_camera_mut().fov = cli.settings.fov as f32;
+                }
                 cli.server = Some(s);
             }
             None => {}
@@ -33,6 +78,31 @@ pub fn render(gui_ctx: &Context, cli: &mut App, t: &Timer) {
     }
 }
 
+/// Draws a crosshair in the centre of the screen. The size is in points so it scales with the
+/// window's `pixels_per_point`.
+pub fn crosshair(gui_ctx: &Context) {
+    const SIZE: f32 = 8.0;
+
+    let painter = gui_ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("Crosshair")));
+    let centre = gui_ctx.screen_rect().center();
+
+    // Outline it so it's visible against both light and dark backgrounds
+    for (width, colour) in [
+        (4.0, Color32::from_rgba_unmultiplied(0, 0, 0, 150)),
+        (2.0, Color32::WHITE),
+    ] {
+        let stroke = Stroke::new(width, colour);
+        painter.line_segment(
+            [centre - Vec2::new(SIZE, 0.0), centre + Vec2::new(SIZE, 0.0)],
+            stroke,
+        );
+        painter.line_segment(
+            [centre - Vec2::new(0.0, SIZE), centre + Vec2::new(0.0, SIZE)],
+            stroke,
+        );
+    }
+}
+
 pub fn disconnect_window(reason: Option<String>) -> PersistentWindow<App> {
     PersistentWindow::new(Box::new(move |id, _, gui_ctx, _| {
         let mut open = true;
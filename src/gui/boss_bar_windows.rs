@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use egui::{Align2, Color32, Context, FontId, Frame, Stroke, Vec2};
+use mcproto_rs::uuid::UUID4;
+
+use crate::server::boss_bar::BossBar;
+
+use super::chat_windows::chat_text_job;
+
+const BAR_WIDTH: f32 = 250.0;
+const BAR_HEIGHT: f32 = 6.0;
+
+/// Renders the active boss bars stacked at the top of the screen, with their titles above them
+pub fn render(gui_ctx: &Context, bars: &HashMap<UUID4, BossBar>) {
+    if bars.is_empty() {
+        return;
+    }
+
+    let mut bars: Vec<&BossBar> = bars.values().collect();
+    bars.sort_by_key(|bar| bar.order);
+
+    egui::Window::new("Boss Bars")
+        .title_bar(false)
+        .resizable(false)
+        .interactable(false)
+        .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 10.0))
+        .frame(Frame::none())
+        .show(gui_ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                for bar in bars {
+                    ui.add(
+                        egui::Label::new(chat_text_job(
+                            &bar.title,
+                            &FontId::proportional(14.0),
+                            Color32::WHITE,
+                        ))
+                        .wrap(false),
+                    );
+
+                    let colour = bar.get_colour();
+                    let response = ui.add(
+                        egui::ProgressBar::new(bar.health.clamp(0.0, 1.0))
+                            .desired_width(BAR_WIDTH)
+                            .desired_height(BAR_HEIGHT)
+                            .rounding(0.0)
+                            .fill(colour),
+                    );
+
+                    // Notches dividing the bar into segments
+                    let rect = response.rect;
+                    let segments = bar.get_segments();
+                    for i in 1..segments {
+                        let x = rect.left() + rect.width() * i as f32 / segments as f32;
+                        ui.painter().vline(
+                            x,
+                            rect.y_range(),
+                            Stroke::new(1.0, Color32::from_rgba_unmultiplied(0, 0, 0, 150)),
+                        );
+                    }
+
+                    ui.add_space(4.0);
+                }
+            });
+        });
+}
@@ -1,17 +1,38 @@
 const CHAT_TIME: i64 = 300;
+/// The most recent messages shown while the chat is closed
+const INACTIVE_MESSAGES: usize = 10;
+const MESSAGE_BACKGROUND: Color32 = Color32::from_rgba_premultiplied(0, 0, 0, 175);
+/// Wide enough for `HH:MM` and a gap after it
+const TIMESTAMP_WIDTH: f32 = 45.0;
+const MENTION_BACKGROUND: Color32 = Color32::from_rgba_premultiplied(90, 70, 0, 175);
+const ERROR_COLOUR: Color32 = Color32::from_rgb(255, 85, 85);
+const GHOST_COLOUR: Color32 = Color32::GRAY;
+/// Drawn in place of each letter of obfuscated text, which vanilla scrambles every frame
+const OBFUSCATED_PLACEHOLDER: char = '*';
 
-use egui::{Align, Align2, Color32, Context, Frame, Layout, RichText, Vec2};
-use mcproto_rs::v1_16_3::PlayServerChatMessageSpec;
+use egui::{
+    text::LayoutJob, Align, Align2, Color32, Context, CursorIcon, FontId, Frame, Label, Layout,
+    Margin, Sense, Stroke, TextFormat, TextStyle, Ui, Vec2,
+};
+use mcproto_rs::types::{
+    Chat as ChatComponent, ChatClickEvent as ClickEvent, ChatHoverEvent as HoverEvent, ColorCode,
+};
 
-use crate::server::Server;
+use crate::{
+    chat::{self, ReceivedMessage, MAX_MESSAGE_LENGTH},
+    server::Server,
+};
+
+use super::clipboard;
 
 pub fn render_inactive(server: &Server, gui_ctx: &Context) {
-    let messages: Vec<&(PlayServerChatMessageSpec, i64)> = server
+    let messages: Vec<&ReceivedMessage> = server
         .get_chat()
         .get_history()
         .iter()
         .rev()
-        .filter(|m| server.get_world_time() - m.1 < CHAT_TIME)
+        .take(INACTIVE_MESSAGES)
+        .take_while(|m| server.get_world_time() - m.time < CHAT_TIME)
         .collect();
 
     if !messages.is_empty() {
@@ -24,20 +45,38 @@ pub fn render_inactive(server: &Server, gui_ctx: &Context) {
                 ui.with_layout(Layout::bottom_up(Align::LEFT), |ui| {
                     ui.add_space(ui.text_style_height(&egui::TextStyle::Body) + 9.0);
                     for message in messages {
-                        if let Some(text) = message.0.message.to_traditional() {
-                            ui.label(
-                                RichText::new(text).color(Color32::WHITE).background_color(
-                                    Color32::from_rgba_unmultiplied(0, 0, 0, 175),
-                                ),
-                            );
-                        }
+                        chat_component(
+                            ui,
+                            &message.message.message,
+                            Color32::WHITE,
+                            message_background(message),
+                        );
                     }
                 });
             });
     }
 }
 
-pub fn render_active(server: &mut Server, gui_ctx: &Context) {
+/// Renders the chat with the message being typed, returning what the player clicked on. When
+/// `timestamps` is set, messages show the time they arrived, once for each run of messages from
+/// the same sender.
+pub fn render_active(server: &Server, gui_ctx: &Context, timestamps: bool) -> ChatAction {
+    // The chat input isn't an egui widget, so pasting is picked up here
+    let pasted: String = gui_ctx.input(|i| {
+        i.events
+            .iter()
+            .filter_map(|e| match e {
+                egui::Event::Paste(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    });
+    let mut action = if pasted.is_empty() {
+        ChatAction::Nothing
+    } else {
+        ChatAction::Paste(pasted)
+    };
+
     egui::Window::new("Chat_Active")
         .title_bar(false)
         .resizable(false)
@@ -46,18 +85,33 @@ pub fn render_active(server: &mut Server, gui_ctx: &Context) {
         .frame(Frame::none())
         .show(gui_ctx, |ui| {
             ui.with_layout(Layout::bottom_up(Align::LEFT), |ui| {
-                ui.text_edit_singleline(server.get_chat_mut().get_current_message_mut())
-                    .request_focus();
+                chat_input(ui, server);
                 ui.add_space(5.0);
 
                 egui::ScrollArea::vertical().show(ui, |ui| {
-                    for message in server.get_chat().get_history().iter().rev() {
-                        if let Some(text) = message.0.message.to_traditional() {
-                            ui.label(
-                                RichText::new(text).color(Color32::WHITE).background_color(
-                                    Color32::from_rgba_unmultiplied(0, 0, 0, 175),
-                                ),
-                            );
+                    let history = server.get_chat().get_history();
+                    for (i, message) in history.iter().enumerate().rev() {
+                        let background = message_background(message);
+                        let clicked = ui
+                            .horizontal_top(|ui| {
+                                ui.spacing_mut().item_spacing.x = 0.0;
+                                if timestamps {
+                                    let previous = i.checked_sub(1).and_then(|i| history.get(i));
+                                    let grouped = previous.is_some_and(|p| message.follows(p));
+                                    timestamp(ui, message, !grouped, background);
+                                }
+                                let component = &message.message.message;
+                                show_chat(
+                                    ui,
+                                    component,
+                                    Color32::WHITE,
+                                    background,
+                                    Some(&|ui: &mut Ui| message_menu(ui, component)),
+                                )
+                            })
+                            .inner;
+                        if !matches!(clicked, ChatAction::Nothing) {
+                            action = clicked;
                         }
                     }
                 });
@@ -77,4 +131,650 @@ pub fn render_active(server: &mut Server, gui_ctx: &Context) {
                 //     });
             });
         });
+
+    action
+}
+
+/// The time a message arrived, in a column of its own so messages line up whether or not it's
+/// shown
+fn timestamp(ui: &mut Ui, message: &ReceivedMessage, show: bool, background: Color32) {
+    let font = TextStyle::Body.resolve(ui.style());
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(TIMESTAMP_WIDTH, font.size), Sense::hover());
+    ui.painter().rect_filled(rect, 0.0, background);
+    if show {
+        ui.painter().text(
+            rect.left_center(),
+            Align2::LEFT_CENTER,
+            message.received.format("%H:%M").to_string(),
+            font,
+            GHOST_COLOUR,
+        );
+    }
+}
+
+/// Right click options for a message in the open chat. The whole message is copied however it's
+/// wrapped.
+fn message_menu(ui: &mut Ui, message: &ChatComponent) {
+    let raw = message.to_traditional().unwrap_or_default();
+    if ui.button("Copy text").clicked() {
+        clipboard::copy(ui.ctx(), chat::strip_codes(&raw));
+        ui.close_menu();
+    }
+    if ui.button("Copy raw").clicked() {
+        clipboard::copy(ui.ctx(), raw);
+        ui.close_menu();
+    }
+}
+
+/// Messages that mention the player stand out from the rest
+const fn message_background(message: &ReceivedMessage) -> Color32 {
+    if message.mention {
+        MENTION_BACKGROUND
+    } else {
+        MESSAGE_BACKGROUND
+    }
+}
+
+/// The message being typed, which is taken straight from the keyboard in
+/// `Server::handle_chat_open_state`. Commands are checked against the ones the server sent: the
+/// part that isn't a known command turns red, and what could come next is shown greyed out after
+/// the cursor.
+fn chat_input(ui: &mut Ui, server: &Server) {
+    let input = server.get_chat().get_current_message();
+    let check = server.get_commands().check(input);
+    let font = TextStyle::Body.resolve(ui.style());
+    let text_colour = ui.visuals().widgets.inactive.text_color();
+
+    let split = check.error_at.unwrap_or(input.len());
+    let mut job = LayoutJob::default();
+    job.append(
+        &input[..split],
+        0.0,
+        TextFormat::simple(font.clone(), text_colour),
+    );
+    job.append(
+        &input[split..],
+        0.0,
+        TextFormat::simple(font.clone(), ERROR_COLOUR),
+    );
+    let galley = ui.fonts(|fonts| fonts.layout_job(job));
+
+    Frame::none()
+        .fill(MESSAGE_BACKGROUND)
+        .inner_margin(Margin::same(4.0))
+        .show(ui, |ui| {
+            let size = Vec2::new(ui.available_width(), font.size);
+            let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+            let painter = ui.painter_at(rect);
+
+            let end = rect.min + Vec2::new(galley.rect.width(), 0.0);
+            painter.galley(rect.min, galley, text_colour);
+
+            // The cursor blinks twice a second and always sits at the end
+            if ui.input(|i| i.time).fract() < 0.5 {
+                painter.line_segment(
+                    [end, end + Vec2::new(0.0, font.size)],
+                    Stroke::new(1.0, text_colour),
+                );
+            }
+
+            if let Some(ghost) = check.completion.or(check.expected) {
+                painter.text(end, Align2::LEFT_TOP, ghost, font.clone(), GHOST_COLOUR);
+            }
+
+            // Messages over the limit are split up when sent, commands over it can't be sent
+            let length = chat::message_length(input);
+            let (counter, colour) = if length > MAX_MESSAGE_LENGTH {
+                let note = match chat::split_message(input) {
+                    Ok(parts) => format!("sends as {} messages", parts.len()),
+                    Err(_) => "too long to send".to_string(),
+                };
+                (
+                    format!("{length}/{MAX_MESSAGE_LENGTH}, {note}"),
+                    ERROR_COLOUR,
+                )
+            } else {
+                (format!("{length}/{MAX_MESSAGE_LENGTH}"), GHOST_COLOUR)
+            };
+            painter.text(rect.right_top(), Align2::RIGHT_TOP, counter, font, colour);
+        });
+}
+
+/// The colour of a legacy `§0`-`§f` colour code
+#[must_use]
+pub fn legacy_colour(code: char) -> Option<Color32> {
+    Some(match code.to_ascii_lowercase() {
+        '0' => Color32::from_rgb(0, 0, 0),
+        '1' => Color32::from_rgb(0, 0, 170),
+        '2' => Color32::from_rgb(0, 170, 0),
+        '3' => Color32::from_rgb(0, 170, 170),
+        '4' => Color32::from_rgb(170, 0, 0),
+        '5' => Color32::from_rgb(170, 0, 170),
+        '6' => Color32::from_rgb(255, 170, 0),
+        '7' => Color32::from_rgb(170, 170, 170),
+        '8' => Color32::from_rgb(85, 85, 85),
+        '9' => Color32::from_rgb(85, 85, 255),
+        'a' => Color32::from_rgb(85, 255, 85),
+        'b' => Color32::from_rgb(85, 255, 255),
+        'c' => Color32::from_rgb(255, 85, 85),
+        'd' => Color32::from_rgb(255, 85, 255),
+        'e' => Color32::from_rgb(255, 255, 85),
+        'f' => Color32::from_rgb(255, 255, 255),
+        _ => return None,
+    })
+}
+
+/// Lays out text containing legacy `§` colour and formatting codes. Like vanilla, colour codes
+/// also clear any formatting and `§r` goes back to `default`. Unknown codes are dropped.
+#[must_use]
+pub fn legacy_text_job(text: &str, font: &FontId, default: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    append_legacy_text(
+        &mut job,
+        text,
+        &TextFormat::simple(font.clone(), default),
+        false,
+    );
+    job
+}
+
+/// Add text with legacy codes to `job`, starting from the style of `base`. Scrambled text from
+/// `§k` or `obfuscated` is drawn as a static placeholder.
+fn append_legacy_text(job: &mut LayoutJob, text: &str, base: &TextFormat, obfuscated: bool) {
+    let mut format = base.clone();
+    let mut scrambled = obfuscated;
+
+    for (i, segment) in text.split('§').enumerate() {
+        let mut chars = segment.chars();
+        // Text before the first code doesn't start with one
+        if i > 0 {
+            match chars.next().map(|c| c.to_ascii_lowercase()) {
+                Some('r') => {
+                    format = base.clone();
+                    scrambled = obfuscated;
+                }
+                Some('k') => scrambled = true,
+                // egui's fonts have no bold face, vanilla bold text is a pixel wider per letter
+                Some('l') => format.extra_letter_spacing = 1.0,
+                Some('o') => format.italics = true,
+                Some('n') => format.underline = Stroke::new(1.0, format.color),
+                Some('m') => format.strikethrough = Stroke::new(1.0, format.color),
+                Some(code) => {
+                    if let Some(colour) = legacy_colour(code) {
+                        format = TextFormat {
+                            background: base.background,
+                            ..TextFormat::simple(base.font_id.clone(), colour)
+                        };
+                        scrambled = false;
+                    }
+                }
+                None => {}
+            }
+        }
+
+        let segment = chars.as_str();
+        if segment.is_empty() {
+            continue;
+        }
+        if scrambled {
+            let placeholder: String = segment
+                .chars()
+                .map(|c| {
+                    if c.is_whitespace() {
+                        c
+                    } else {
+                        OBFUSCATED_PLACEHOLDER
+                    }
+                })
+                .collect();
+            job.append(&placeholder, 0.0, format.clone());
+        } else {
+            job.append(segment, 0.0, format.clone());
+        }
+    }
+}
+
+/// What clicking on part of a chat component asks for, or text pasted into the open chat
+pub enum ChatAction {
+    Nothing,
+    Paste(String),
+    /// Open a link, once the player has confirmed it
+    OpenUrl(String),
+    /// Send a chat message or command as if the player typed it
+    RunCommand(String),
+    /// Put some text in the chat input for the player to finish
+    SuggestCommand(String),
+}
+
+/// Shows a chat component with the colours and styles of each part of it, on one wrapped line.
+/// Parts with click events can be clicked to get what they ask for, and parts with hover text
+/// show it in a tooltip.
+pub fn chat_component(
+    ui: &mut Ui,
+    chat: &ChatComponent,
+    default: Color32,
+    background: Color32,
+) -> ChatAction {
+    show_chat(ui, chat, default, background, None)
+}
+
+/// Shows a chat component like `chat_component`, with `menu` as the right click menu of every
+/// part of it
+fn show_chat(
+    ui: &mut Ui,
+    chat: &ChatComponent,
+    default: Color32,
+    background: Color32,
+    menu: Option<&dyn Fn(&mut Ui)>,
+) -> ChatAction {
+    let mut plain = TextFormat::simple(TextStyle::Body.resolve(ui.style()), default);
+    plain.background = background;
+    let style = SpanStyle {
+        format: plain,
+        obfuscated: false,
+        click: None,
+        hover: None,
+    };
+
+    let mut spans = Vec::new();
+    collect_spans(chat, &style, &mut spans);
+
+    let mut action = ChatAction::Nothing;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            let sense = if span.click.is_some() || menu.is_some() {
+                Sense::click()
+            } else {
+                Sense::hover()
+            };
+            let mut response = ui.add(Label::new(span.job).sense(sense));
+            if let Some(menu) = menu {
+                response.context_menu(menu);
+            }
+
+            if let Some(hover) = span.hover {
+                response = response.on_hover_ui(|ui| {
+                    chat_component(ui, hover, Color32::WHITE, Color32::TRANSPARENT);
+                });
+            }
+            if let Some(click) = &span.click {
+                response = response.on_hover_cursor(CursorIcon::PointingHand);
+                if response.clicked() {
+                    action = match click {
+                        ClickEvent::OpenUrl(url) => ChatAction::OpenUrl(url.clone()),
+                        ClickEvent::RunCommand(command) => ChatAction::RunCommand(command.clone()),
+                        ClickEvent::SuggestCommand(command) => {
+                            ChatAction::SuggestCommand(command.clone())
+                        }
+                        // Only books have pages to change
+                        _ => ChatAction::Nothing,
+                    };
+                }
+            }
+        }
+    });
+
+    action
+}
+
+/// Lays out a whole chat component as one piece of text with the colours and styles of each
+/// part, for when it isn't interactive
+#[must_use]
+pub fn chat_text_job(chat: &ChatComponent, font: &FontId, default: Color32) -> LayoutJob {
+    let style = SpanStyle {
+        format: TextFormat::simple(font.clone(), default),
+        obfuscated: false,
+        click: None,
+        hover: None,
+    };
+    let mut spans = Vec::new();
+    collect_spans(chat, &style, &mut spans);
+
+    let mut job = LayoutJob::default();
+    for span in spans {
+        for section in span.job.sections {
+            job.append(&span.job.text[section.byte_range], 0.0, section.format);
+        }
+    }
+    job
+}
+
+/// The style a chat component passes down to its children
+#[derive(Clone)]
+struct SpanStyle<'a> {
+    format: TextFormat,
+    obfuscated: bool,
+    click: Option<&'a ClickEvent>,
+    /// The text to show when hovering
+    hover: Option<&'a ChatComponent>,
+}
+
+/// A run of text that does the same thing when clicked or hovered
+struct Span<'a> {
+    job: LayoutJob,
+    /// Owned as links found in the text don't come from a component
+    click: Option<ClickEvent>,
+    hover: Option<&'a ChatComponent>,
+}
+
+/// Flatten a chat component and its children into runs of text, each with the events they have.
+/// Styles and events are inherited from `parent`, unless a component has its own.
+fn collect_spans<'a>(chat: &'a ChatComponent, parent: &SpanStyle<'a>, spans: &mut Vec<Span<'a>>) {
+    let base = chat.base();
+
+    let mut style = parent.clone();
+    let format = &mut style.format;
+    if let Some(colour) = &base.color {
+        format.color = chat_colour(colour);
+    }
+    if base.bold {
+        format.extra_letter_spacing = 1.0;
+    }
+    if base.italic {
+        format.italics = true;
+    }
+    if base.underlined {
+        format.underline = Stroke::new(1.0, format.color);
+    }
+    if base.strikethrough {
+        format.strikethrough = Stroke::new(1.0, format.color);
+    }
+    style.obfuscated |= base.obfuscated;
+
+    if let Some(click) = &base.click_event {
+        style.click = Some(click);
+    }
+    match &base.hover_event {
+        Some(HoverEvent::ShowText(text)) => style.hover = Some(&**text),
+        // Items and entities need more than the hover text to show
+        Some(_) => style.hover = None,
+        None => {}
+    }
+
+    // Plain text can still have legacy codes in it, which plenty of servers send. Translations
+    // and the like need the game's language files, so let mcproto make what it can of them,
+    // which already includes their children.
+    let (text, done) = match chat {
+        ChatComponent::Text(text) => (text.text.clone(), false),
+        _ => (chat.to_traditional().unwrap_or_default(), true),
+    };
+    let mut job = LayoutJob::default();
+    append_legacy_text(&mut job, &text, &style.format, style.obfuscated);
+    for section in job.sections {
+        let text = &job.text[section.byte_range];
+        if style.click.is_some() {
+            push_span(spans, &style, style.click, text, section.format);
+            continue;
+        }
+
+        // Bare links in the text become clickable too
+        for (part, is_url) in split_urls(text) {
+            if is_url {
+                let mut format = section.format.clone();
+                format.underline = Stroke::new(1.0, format.color);
+                let click = ClickEvent::OpenUrl(part.to_string());
+                push_span(spans, &style, Some(&click), part, format);
+            } else {
+                push_span(spans, &style, None, part, section.format.clone());
+            }
+        }
+    }
+    if done {
+        return;
+    }
+
+    for child in &base.extra {
+        collect_spans(child, &style, spans);
+    }
+}
+
+/// Add some text to the last run if it has the same events, or start a new one
+fn push_span<'a>(
+    spans: &mut Vec<Span<'a>>,
+    style: &SpanStyle<'a>,
+    click: Option<&ClickEvent>,
+    text: &str,
+    format: TextFormat,
+) {
+    if text.is_empty() {
+        return;
+    }
+
+    match spans.last_mut() {
+        Some(span) if span.click.as_ref() == click && span.hover == style.hover => {
+            span.job.append(text, 0.0, format);
+        }
+        _ => {
+            let mut job = LayoutJob::default();
+            job.append(text, 0.0, format);
+            spans.push(Span {
+                job,
+                click: click.cloned(),
+                hover: style.hover,
+            });
+        }
+    }
+}
+
+/// Break text up into the links in it and the text between them, marking which parts are links
+fn split_urls(text: &str) -> Vec<(&str, bool)> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(start) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let length = rest[start..]
+            .find(char::is_whitespace)
+            .unwrap_or(rest.len() - start);
+        // Punctuation straight after a link is more likely to end the sentence than the link
+        let url = rest[start..start + length].trim_end_matches(['.', ',', '!', '?', ')', ';', ':']);
+
+        parts.push((&rest[..start], false));
+        parts.push((url, true));
+        rest = &rest[start + url.len()..];
+    }
+    parts.push((rest, false));
+    parts
+}
+
+/// The colour of a named chat colour, the same as its legacy code
+fn chat_colour(colour: &ColorCode) -> Color32 {
+    let code = match colour {
+        ColorCode::Black => '0',
+        ColorCode::DarkBlue => '1',
+        ColorCode::DarkGreen => '2',
+        ColorCode::DarkAqua => '3',
+        ColorCode::DarkRed => '4',
+        ColorCode::DarkPurple => '5',
+        ColorCode::Gold => '6',
+        ColorCode::Gray => '7',
+        ColorCode::DarkGray => '8',
+        ColorCode::Blue => '9',
+        ColorCode::Green => 'a',
+        ColorCode::Aqua => 'b',
+        ColorCode::Red => 'c',
+        ColorCode::LightPurple => 'd',
+        ColorCode::Yellow => 'e',
+        ColorCode::White => 'f',
+    };
+    legacy_colour(code).unwrap_or(Color32::WHITE)
+}
+
+#[cfg(test)]
+mod tests {
+    use mcproto_rs::types::{BaseComponent, TextComponent};
+
+    use super::*;
+
+    fn font() -> FontId {
+        FontId::proportional(14.0)
+    }
+
+    fn plain(colour: Color32) -> TextFormat {
+        TextFormat::simple(font(), colour)
+    }
+
+    /// A text component styled by `style`
+    fn text(text: &str, style: impl FnOnce(&mut BaseComponent)) -> ChatComponent {
+        let mut base = BaseComponent::default();
+        style(&mut base);
+        ChatComponent::Text(TextComponent {
+            text: text.to_string(),
+            base,
+        })
+    }
+
+    /// The text and format of each part of a laid out job
+    fn segments(job: &LayoutJob) -> Vec<(&str, TextFormat)> {
+        job.sections
+            .iter()
+            .map(|s| (&job.text[s.byte_range.clone()], s.format.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn legacy_colours_and_formatting() {
+        let red = legacy_colour('c').unwrap();
+        let job = legacy_text_job("plain §cred §lbold§r reset", &font(), Color32::WHITE);
+
+        let bold = TextFormat {
+            extra_letter_spacing: 1.0,
+            ..plain(red)
+        };
+        assert_eq!(
+            segments(&job),
+            [
+                ("plain ", plain(Color32::WHITE)),
+                ("red ", plain(red)),
+                ("bold", bold),
+                (" reset", plain(Color32::WHITE)),
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_colour_clears_formatting() {
+        let green = legacy_colour('A').unwrap();
+        let job = legacy_text_job("§o§n§mstyled§Aplain§zunknown", &font(), Color32::WHITE);
+
+        let styled = TextFormat {
+            italics: true,
+            underline: Stroke::new(1.0, Color32::WHITE),
+            strikethrough: Stroke::new(1.0, Color32::WHITE),
+            ..plain(Color32::WHITE)
+        };
+        assert_eq!(
+            segments(&job),
+            [
+                ("styled", styled),
+                ("plain", plain(green)),
+                ("unknown", plain(green)),
+            ]
+        );
+    }
+
+    #[test]
+    fn obfuscated_text_is_a_placeholder() {
+        let job = legacy_text_job("§kab c§rd§k§6e", &font(), Color32::WHITE);
+
+        let gold = legacy_colour('6').unwrap();
+        assert_eq!(
+            segments(&job),
+            [
+                ("** *", plain(Color32::WHITE)),
+                ("d", plain(Color32::WHITE)),
+                ("e", plain(gold)),
+            ]
+        );
+    }
+
+    #[test]
+    fn children_inherit_styles() {
+        let mut chat = text("parent ", |base| {
+            base.color = Some(ColorCode::Red);
+            base.bold = true;
+        });
+        let ChatComponent::Text(parent) = &mut chat else {
+            unreachable!()
+        };
+        parent.base.extra = vec![
+            Box::new(text("inherits ", |_| {})),
+            Box::new(text("own", |base| {
+                base.color = Some(ColorCode::Blue);
+                base.italic = true;
+                base.extra = vec![Box::new(text(" nested", |base| {
+                    base.strikethrough = true;
+                }))];
+            })),
+        ];
+
+        let red = legacy_colour('c').unwrap();
+        let blue = legacy_colour('9').unwrap();
+        let bold = |colour| TextFormat {
+            extra_letter_spacing: 1.0,
+            ..plain(colour)
+        };
+        let own = TextFormat {
+            italics: true,
+            ..bold(blue)
+        };
+        let nested = TextFormat {
+            strikethrough: Stroke::new(1.0, blue),
+            ..own.clone()
+        };
+        assert_eq!(
+            segments(&chat_text_job(&chat, &font(), Color32::WHITE)),
+            [
+                ("parent ", bold(red)),
+                ("inherits ", bold(red)),
+                ("own", own),
+                (" nested", nested),
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_codes_inside_components() {
+        let chat = text("before §eyellow§r after", |base| {
+            base.color = Some(ColorCode::DarkGreen);
+            base.obfuscated = true;
+            base.extra = vec![Box::new(text("§rstill", |_| {}))];
+        });
+
+        let dark_green = legacy_colour('2').unwrap();
+        let yellow = legacy_colour('e').unwrap();
+        assert_eq!(
+            segments(&chat_text_job(&chat, &font(), Color32::WHITE)),
+            [
+                ("****** ", plain(dark_green)),
+                ("yellow", plain(yellow)),
+                (" *****", plain(dark_green)),
+                ("*****", plain(dark_green)),
+            ]
+        );
+    }
+
+    #[test]
+    fn links_are_underlined() {
+        let chat = text("see https://example.com/page.", |base| {
+            base.color = Some(ColorCode::Gray);
+        });
+
+        let gray = legacy_colour('7').unwrap();
+        let link = TextFormat {
+            underline: Stroke::new(1.0, gray),
+            ..plain(gray)
+        };
+        assert_eq!(
+            segments(&chat_text_job(&chat, &font(), Color32::WHITE)),
+            [
+                ("see ", plain(gray)),
+                ("https://example.com/page", link),
+                (".", plain(gray)),
+            ]
+        );
+    }
 }
@@ -0,0 +1,8 @@
+use egui::Context;
+
+/// Copy some text for the player. egui passes it on to the system clipboard where there is one,
+/// and otherwise keeps it itself so it can still be pasted back into the game.
+pub fn copy(gui_ctx: &Context, text: String) {
+    tracing::debug!("Copied {} characters", text.chars().count());
+    gui_ctx.output_mut(|o| o.copied_text = text);
+}
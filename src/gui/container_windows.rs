@@ -0,0 +1,63 @@
+use egui::{Align2, Color32, Context, RichText, Vec2};
+
+use crate::{resources::items, server::container::Container};
+
+use super::chat_windows::chat_component;
+
+pub enum ContainerAction {
+    Nothing,
+    Close,
+}
+
+/// Renders the contents of an open container, e.g. a chest, listing what is in each of its slots.
+/// Items can't be moved yet.
+pub fn render(gui_ctx: &Context, container: &Container) -> ContainerAction {
+    let mut action = ContainerAction::Nothing;
+
+    egui::Window::new("Container")
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .resizable(false)
+        .collapsible(false)
+        .show(gui_ctx, |ui| {
+            let colour = ui.visuals().strong_text_color();
+            chat_component(ui, container.get_title(), colour, Color32::TRANSPARENT);
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("Container Grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (i, item) in container.get_slots().iter().enumerate() {
+                                ui.label(RichText::new(i.to_string()).color(Color32::LIGHT_GRAY));
+                                match item {
+                                    Some(item) => {
+                                        let name = items().get(&(item.item_id as u32)).map_or_else(
+                                            || format!("Unknown ({})", item.item_id),
+                                            |i| i.name.clone(),
+                                        );
+                                        ui.label(name);
+                                        ui.label(format!("x{}", item.count));
+                                    }
+                                    None => {
+                                        ui.label(RichText::new("Empty").color(Color32::DARK_GRAY));
+                                        ui.label("");
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            ui.add_space(5.0);
+            ui.vertical_centered(|ui| {
+                if ui.button("Close").clicked() {
+                    action = ContainerAction::Close;
+                }
+            });
+        });
+
+    action
+}
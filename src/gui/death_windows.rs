@@ -0,0 +1,48 @@
+use egui::{Align2, Color32, Context, RichText, Vec2};
+use mcproto_rs::types::Chat;
+
+use super::chat_windows::chat_component;
+
+pub enum DeathAction {
+    Nothing,
+    Respawn,
+    Disconnect,
+}
+
+/// Renders the death screen with the reason the player died, if the server gave one. The buttons
+/// are disabled while a respawn is on its way.
+pub fn render(gui_ctx: &Context, message: Option<&Chat>, awaiting_respawn: bool) -> DeathAction {
+    let mut action = DeathAction::Nothing;
+
+    egui::Window::new("Death Screen")
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .resizable(false)
+        .collapsible(false)
+        .show(gui_ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new("You died!").heading().strong());
+                if let Some(message) = message {
+                    ui.add_space(5.0);
+                    let colour = ui.visuals().text_color();
+                    chat_component(ui, message, colour, Color32::TRANSPARENT);
+                }
+                ui.add_space(10.0);
+
+                if ui
+                    .add_enabled(!awaiting_respawn, egui::Button::new("Respawn"))
+                    .clicked()
+                {
+                    action = DeathAction::Respawn;
+                }
+                if ui
+                    .add_enabled(!awaiting_respawn, egui::Button::new("Disconnect"))
+                    .clicked()
+                {
+                    action = DeathAction::Disconnect;
+                }
+            });
+        });
+
+    action
+}
@@ -0,0 +1,329 @@
+use egui::{
+    Align2, Color32, Context, FontId, Frame, Id, LayerId, Mesh, Order, Painter, Rect, RichText,
+    Sense, Shape, Stroke, Ui, Vec2,
+};
+use glam::DVec3;
+use mcproto_rs::v1_16_3::GameMode;
+
+use crate::{
+    player::{
+        inventory::{Inventory, ItemStack, HOTBAR_SIZE},
+        Player,
+    },
+    resources::items,
+    world::border::WorldBorder,
+};
+
+const ICON_SIZE: f32 = 14.0;
+const ICON_SPACING: f32 = 2.0;
+/// Seconds between the health bar flashing on and off after taking damage
+const HURT_FLASH_PERIOD: f64 = 0.1;
+
+const HEALTH_COLOUR: Color32 = Color32::from_rgb(200, 20, 20);
+const FOOD_COLOUR: Color32 = Color32::from_rgb(180, 115, 50);
+const ARMOR_COLOUR: Color32 = Color32::from_rgb(200, 200, 210);
+
+const EXPERIENCE_BAR_WIDTH: f32 = 330.0;
+const EXPERIENCE_BAR_HEIGHT: f32 = 5.0;
+const EXPERIENCE_COLOUR: Color32 = Color32::from_rgb(128, 255, 32);
+
+const SLOT_SIZE: f32 = 34.0;
+const SLOT_SPACING: f32 = 3.0;
+const DURABILITY_BAR_HEIGHT: f32 = 3.0;
+/// Seconds the held item's name takes to fade out at the end of being shown
+const HELD_NAME_FADE: f64 = 2.0;
+
+/// How far the border warning reaches in from the edges of the screen, as a fraction of its
+/// smallest side
+const VIGNETTE_DEPTH: f32 = 0.25;
+const VIGNETTE_ALPHA: f32 = 180.0;
+
+/// Renders the player's armor, health, hunger and experience above their hotbar at the bottom
+/// centre of the screen. Only the hotbar applies in creative, and nothing is drawn in spectator.
+pub fn render(gui_ctx: &Context, player: &Player) {
+    if player.gamemode == GameMode::Spectator {
+        return;
+    }
+
+    let response = egui::Window::new("HUD")
+        .title_bar(false)
+        .resizable(false)
+        .interactable(false)
+        .anchor(Align2::CENTER_BOTTOM, Vec2::new(0.0, -10.0))
+        .frame(Frame::none())
+        .show(gui_ctx, |ui| {
+            if player.gamemode != GameMode::Creative {
+                // Armor sits above health like in vanilla, and only once the server has told us
+                if let Some(armor) = player.armor.filter(|armor| *armor > 0.0) {
+                    icon_row(ui, armor as f32, ARMOR_COLOUR, false);
+                }
+
+                ui.horizontal(|ui| {
+                    let flashing =
+                        (player.get_hurt_flash() / HURT_FLASH_PERIOD).ceil() as u32 % 2 == 1;
+                    icon_row(ui, player.get_health(), HEALTH_COLOUR, flashing);
+                    ui.add_space(ICON_SIZE);
+                    icon_row(ui, player.food as f32, FOOD_COLOUR, false);
+                });
+
+                experience_bar(ui, player);
+            }
+
+            hotbar(ui, &player.inventory);
+        });
+
+    if let Some(response) = response {
+        held_item_name(gui_ctx, &player.inventory, response.response.rect);
+    }
+}
+
+/// Draws 10 icons representing a value from 0-20, where each icon is worth 2 points and odd
+/// values are drawn as a half icon. Highlighted icons get a white outline.
+fn icon_row(ui: &mut Ui, value: f32, colour: Color32, highlight: bool) {
+    let size = Vec2::new(10.0 * (ICON_SIZE + ICON_SPACING), ICON_SIZE);
+    let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter();
+
+    let halves = value.clamp(0.0, 20.0).ceil() as u32;
+    for i in 0..10 {
+        let min = rect.min + Vec2::new(i as f32 * (ICON_SIZE + ICON_SPACING), 0.0);
+        let icon = Rect::from_min_size(min, Vec2::splat(ICON_SIZE));
+        painter.rect_filled(icon, 2.0, Color32::from_rgba_unmultiplied(0, 0, 0, 175));
+        if highlight {
+            painter.rect_stroke(icon, 2.0, Stroke::new(1.0, Color32::WHITE));
+        }
+
+        let mut fill = icon.shrink(2.0);
+        match halves.saturating_sub(i * 2) {
+            0 => continue,
+            1 => fill.set_right(fill.center().x),
+            _ => {}
+        }
+        painter.rect_filled(fill, 1.0, colour);
+    }
+}
+
+/// Draws the player's progress towards the next level as a thin bar, with their level centred
+/// above it
+fn experience_bar(ui: &mut Ui, player: &Player) {
+    ui.vertical_centered(|ui| {
+        if player.level > 0 {
+            ui.label(
+                RichText::new(player.level.to_string())
+                    .font(FontId::proportional(14.0))
+                    .color(EXPERIENCE_COLOUR),
+            );
+        }
+
+        let (rect, _) = ui.allocate_exact_size(
+            Vec2::new(EXPERIENCE_BAR_WIDTH, EXPERIENCE_BAR_HEIGHT),
+            Sense::hover(),
+        );
+        let painter = ui.painter();
+        painter.rect_filled(rect, 1.0, Color32::from_rgba_unmultiplied(0, 0, 0, 175));
+
+        // Some servers send values outside of 0-1, or NaN
+        let progress = if player.experience_bar.is_nan() {
+            0.0
+        } else {
+            player.experience_bar.clamp(0.0, 1.0)
+        };
+        if progress > 0.0 {
+            let fill =
+                Rect::from_min_size(rect.min, Vec2::new(rect.width() * progress, rect.height()));
+            painter.rect_filled(fill, 1.0, EXPERIENCE_COLOUR);
+        }
+    });
+}
+
+/// Draws the 9 hotbar slots with the selected one outlined. There are no item icons yet so each
+/// slot shows the initials of its item, with the count and durability along the bottom.
+fn hotbar(ui: &mut Ui, inventory: &Inventory) {
+    let slots = HOTBAR_SIZE as f32;
+    let width = slots * SLOT_SIZE + (slots - 1.0) * SLOT_SPACING;
+    ui.vertical_centered(|ui| {
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(width, SLOT_SIZE), Sense::hover());
+        let painter = ui.painter();
+
+        for (i, item) in inventory.get_hotbar().iter().enumerate() {
+            let min = rect.min + Vec2::new(i as f32 * (SLOT_SIZE + SLOT_SPACING), 0.0);
+            let slot = Rect::from_min_size(min, Vec2::splat(SLOT_SIZE));
+            painter.rect_filled(slot, 2.0, Color32::from_rgba_unmultiplied(0, 0, 0, 175));
+            if i == inventory.get_selected_slot() {
+                painter.rect_stroke(slot, 2.0, Stroke::new(2.0, Color32::WHITE));
+            }
+
+            if let Some(item) = item {
+                slot_item(painter, slot, item);
+            }
+        }
+    });
+}
+
+/// Draws what's in a hotbar slot over its background
+fn slot_item(painter: &Painter, slot: Rect, item: &ItemStack) {
+    let kind = items().get(&(item.item_id as u32));
+    let initials: String = kind.map_or_else(
+        || String::from("?"),
+        |kind| {
+            kind.name
+                .split(' ')
+                .filter_map(|word| word.chars().next())
+                .take(3)
+                .collect()
+        },
+    );
+    painter.text(
+        slot.center(),
+        Align2::CENTER_CENTER,
+        initials,
+        FontId::proportional(14.0),
+        Color32::WHITE,
+    );
+
+    if item.count > 1 {
+        painter.text(
+            slot.right_bottom() - Vec2::new(2.0, 1.0),
+            Align2::RIGHT_BOTTOM,
+            item.count.to_string(),
+            FontId::proportional(11.0),
+            Color32::LIGHT_GRAY,
+        );
+    }
+
+    // Only damaged items get a durability bar, like in vanilla
+    let max_damage = kind.and_then(|kind| kind.max_damage);
+    if let (Some(damage), Some(max_damage)) = (item.damage.filter(|d| *d > 0), max_damage) {
+        let durability = 1.0 - (damage as f32 / max_damage as f32).clamp(0.0, 1.0);
+        let bar = Rect::from_min_size(
+            slot.left_bottom() + Vec2::new(3.0, -DURABILITY_BAR_HEIGHT - 3.0),
+            Vec2::new(SLOT_SIZE - 6.0, DURABILITY_BAR_HEIGHT),
+        );
+        painter.rect_filled(bar, 0.0, Color32::BLACK);
+
+        let mut fill = bar;
+        fill.set_width(bar.width() * durability);
+        // Green when new through to red when about to break
+        let colour = egui::ecolor::Hsva::new(durability / 3.0, 1.0, 1.0, 1.0);
+        painter.rect_filled(fill, 0.0, colour);
+    }
+}
+
+/// Shows the held item's name above the HUD for a moment after changing slot, fading out
+fn held_item_name(gui_ctx: &Context, inventory: &Inventory, hud: Rect) {
+    let timer = inventory.get_held_name_timer();
+    if timer <= 0.0 {
+        return;
+    }
+    let Some(item) = inventory.get_held_item() else {
+        return;
+    };
+
+    let name = items()
+        .get(&(item.item_id as u32))
+        .map_or_else(|| format!("Unknown ({})", item.item_id), |i| i.name.clone());
+    let alpha = (timer / HELD_NAME_FADE).min(1.0) as f32;
+
+    gui_ctx
+        .layer_painter(LayerId::new(Order::Foreground, Id::new("Held item name")))
+        .text(
+            hud.center_top() - Vec2::new(0.0, 4.0),
+            Align2::CENTER_BOTTOM,
+            name,
+            FontId::proportional(16.0),
+            Color32::WHITE.gamma_multiply(alpha),
+        );
+}
+
+/// Small reminders in the bottom left that sneak or sprint are toggled on, since there's no key
+/// being held to show it
+pub fn toggle_indicators(gui_ctx: &Context, sneaking: bool, sprinting: bool) {
+    if !sneaking && !sprinting {
+        return;
+    }
+
+    egui::Window::new("Toggles")
+        .title_bar(false)
+        .resizable(false)
+        .interactable(false)
+        .anchor(Align2::LEFT_BOTTOM, Vec2::new(10.0, -10.0))
+        .frame(Frame::none())
+        .show(gui_ctx, |ui| {
+            for (on, text) in [(sneaking, "Sneaking"), (sprinting, "Sprinting")] {
+                if on {
+                    ui.label(
+                        RichText::new(text)
+                            .font(FontId::proportional(12.0))
+                            .color(Color32::WHITE)
+                            .background_color(Color32::from_rgba_unmultiplied(0, 0, 0, 175)),
+                    );
+                }
+            }
+        });
+}
+
+/// Shows whose eyes the camera is looking through while spectating another entity
+pub fn spectating(gui_ctx: &Context, name: &str) {
+    egui::Window::new("Spectating")
+        .title_bar(false)
+        .resizable(false)
+        .interactable(false)
+        // Above the action bar, clear of the boss bars at the top
+        .anchor(Align2::CENTER_BOTTOM, Vec2::new(0.0, -100.0))
+        .frame(Frame::none())
+        .show(gui_ctx, |ui| {
+            ui.label(
+                RichText::new(format!("Spectating: {name}"))
+                    .font(FontId::proportional(16.0))
+                    .color(Color32::WHITE)
+                    .background_color(Color32::from_rgba_unmultiplied(0, 0, 0, 175)),
+            );
+        });
+}
+
+/// Tints the edges of the screen red while the player is within the border's warning distance,
+/// getting stronger the closer they are
+pub fn border_warning(gui_ctx: &Context, border: &WorldBorder, position: &DVec3) {
+    let warning = border.get_warning_distance();
+    if warning <= 0.0 {
+        return;
+    }
+    let distance = border.distance_to(position).max(0.0);
+    if distance >= warning {
+        return;
+    }
+
+    let strength = 1.0 - (distance / warning) as f32;
+    let colour = Color32::from_rgba_unmultiplied(255, 0, 0, (strength * VIGNETTE_ALPHA) as u8);
+
+    let screen = gui_ctx.screen_rect();
+    let depth = screen.width().min(screen.height()) * VIGNETTE_DEPTH;
+    let inner = screen.shrink(depth);
+
+    // Each edge is a quad that fades from the colour at the screen's edge to nothing inside
+    let mut mesh = Mesh::default();
+    let corners = |rect: Rect| {
+        [
+            rect.left_top(),
+            rect.right_top(),
+            rect.right_bottom(),
+            rect.left_bottom(),
+        ]
+    };
+    let outer = corners(screen);
+    let inner = corners(inner);
+    for i in 0..4 {
+        let next = (i + 1) % 4;
+        let base = mesh.vertices.len() as u32;
+        mesh.colored_vertex(outer[i], colour);
+        mesh.colored_vertex(outer[next], colour);
+        mesh.colored_vertex(inner[next], Color32::TRANSPARENT);
+        mesh.colored_vertex(inner[i], Color32::TRANSPARENT);
+        mesh.add_triangle(base, base + 1, base + 2);
+        mesh.add_triangle(base, base + 2, base + 3);
+    }
+
+    gui_ctx
+        .layer_painter(LayerId::new(Order::Background, Id::new("Border warning")))
+        .add(Shape::mesh(mesh));
+}
@@ -1,12 +1,17 @@
-use crate::server::Server;
+use crate::{server::Server, settings::Settings};
 use egui::Context;
 
+pub mod debug_window;
 pub mod entities_window;
+pub mod inventory_window;
+pub mod player_list_window;
 pub mod players_window;
 pub mod server_info_window;
 
-pub fn render(gui_ctx: &Context, server: &Server) {
+pub fn render(gui_ctx: &Context, server: &mut Server, settings: &mut Settings) {
     server_info_window::render(gui_ctx, server);
     entities_window::render(gui_ctx, server);
     players_window::render(gui_ctx, server);
+    inventory_window::render(gui_ctx, server);
+    player_list_window::render(gui_ctx, server, settings);
 }
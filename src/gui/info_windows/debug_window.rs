@@ -0,0 +1,71 @@
+use egui::{Align2, Color32, Context, Frame, Margin, RichText, Vec2};
+
+use crate::{
+    renderer::WorldRenderer,
+    resources::biomes::biome,
+    server::Server,
+    world::{block_coords, chunks::Chunk},
+};
+
+/// Renders an F3-style overlay in the top right of the screen with where the player is, which way
+/// they're facing and how much is loaded and drawn
+pub fn render(gui_ctx: &Context, server: &Server, renderer: Option<&WorldRenderer>, fps: u32) {
+    let player = server.get_player();
+    let pos = player.get_position();
+    let block = block_coords(pos);
+    let chunk = Chunk::chunk_containing(&block);
+    let orientation = player.get_orientation();
+
+    egui::Window::new("Debug")
+        .title_bar(false)
+        .resizable(false)
+        .interactable(false)
+        .anchor(Align2::RIGHT_TOP, Vec2::new(-5.0, 5.0))
+        .frame(
+            Frame::none()
+                .fill(Color32::from_rgba_unmultiplied(0, 0, 0, 175))
+                .inner_margin(Margin::same(4.0)),
+        )
+        .show(gui_ctx, |ui| {
+            let mut lines = vec![
+                format!("{fps} fps"),
+                format!("XYZ: {:.3} / {:.3} / {:.3}", pos.x, pos.y, pos.z),
+                format!("Block: {} {} {}", block.x, block.y, block.z),
+                format!(
+                    "Chunk: {} {} in {} {}",
+                    block.x.rem_euclid(16),
+                    block.z.rem_euclid(16),
+                    chunk.x,
+                    chunk.y
+                ),
+                format!(
+                    "Facing: {} ({:.1} / {:.1})",
+                    facing(orientation.get_yaw()),
+                    orientation.get_yaw().rem_euclid(360.0),
+                    orientation.get_pitch()
+                ),
+                format!("Biome: {}", biome(server.get_world().get_biome(block)).name),
+                format!("Gamemode: {:?}", player.gamemode),
+                format!("Chunks: {}", server.get_world().get_chunks().len()),
+                format!("Entities: {}", server.get_entities().len()),
+            ];
+            if let Some(renderer) = renderer {
+                let (sections, vertices) = renderer.mesh_stats();
+                lines.push(format!("Meshes: {sections} ({vertices} vertices)"));
+            }
+            for line in lines {
+                ui.label(RichText::new(line).monospace().color(Color32::WHITE));
+            }
+        });
+}
+
+/// The compass direction a yaw points in, and the axis that goes that way
+fn facing(yaw: f64) -> &'static str {
+    // A yaw of 0 looks towards positive z, turning towards negative x
+    match ((yaw.rem_euclid(360.0) + 45.0) / 90.0) as u32 % 4 {
+        0 => "South (+Z)",
+        1 => "West (-X)",
+        2 => "North (-Z)",
+        _ => "East (+X)",
+    }
+}
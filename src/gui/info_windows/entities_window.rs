@@ -2,12 +2,17 @@ use std::collections::HashMap;
 
 use egui::{Context, Id};
 
-use crate::{entities::Entity, resources::entities, server::Server};
+use crate::{entities::Entity, resources::items, server::Server};
 
-pub fn render(gui_ctx: &Context, server: &Server) {
+pub fn render(gui_ctx: &Context, server: &mut Server) {
     egui::Window::new(format!("Entities: {}", server.get_entities().len()))
         .id(Id::new("Entities"))
         .show(gui_ctx, |ui| {
+            let mut show_hitboxes = server.shows_hitboxes();
+            if ui.checkbox(&mut show_hitboxes, "Show hitboxes").changed() {
+                server.set_show_hitboxes(show_hitboxes);
+            }
+
             let mut ents: HashMap<u32, Vec<&Entity>> = HashMap::new();
             for e in server.get_entities().values() {
                 match ents.get_mut(&e.entity_type) {
@@ -29,16 +34,37 @@ pub fn render(gui_ctx: &Context, server: &Server) {
             ents_vec.sort_by(|(id1, _), (id2, _)| id1.cmp(id2));
 
             // List each present type of entity under dropdown menus
-            for (type_id, ent) in ents_vec {
-                let name = entities()
-                    .get(type_id)
-                    .map_or_else(|| "Unknown", |e| e.name.as_str());
+            for (_, ent) in ents_vec {
+                // Every entity in the group has the same type
+                let name = ent.first().map_or("Unknown", |e| e.get_type_name());
 
                 egui::CollapsingHeader::new(format!("{} ({})", name, ent.len()))
                     .id_source(Id::new(name))
                     .show(ui, |ui| {
                         for e in ent {
-                            ui.label(format!("{:.2} / {:.2} / {:.2}", e.pos.x, e.pos.y, e.pos.z));
+                            let mut text =
+                                format!("{:.2} / {:.2} / {:.2}", e.pos.x, e.pos.y, e.pos.z);
+                            if let Some(custom_name) = &e.metadata.custom_name {
+                                text = format!("{custom_name} - {text}");
+                            }
+                            if e.metadata.is_invisible() {
+                                text.push_str(" (invisible)");
+                            }
+                            if let Some(vehicle) = server.get_passengers().get_vehicle(e.id) {
+                                let name = server
+                                    .get_entity_name(vehicle)
+                                    .unwrap_or_else(|| format!("entity {vehicle}"));
+                                text.push_str(&format!(" riding {name}"));
+                            }
+                            ui.label(text);
+
+                            for (slot, item) in e.equipment.iter() {
+                                let name = items().get(&(item.item_id as u32)).map_or_else(
+                                    || format!("Unknown ({})", item.item_id),
+                                    |i| i.name.clone(),
+                                );
+                                ui.label(format!("    {slot}: {name}"));
+                            }
                         }
                     });
             }
@@ -0,0 +1,44 @@
+use egui::{Color32, Context, RichText};
+
+use crate::{
+    player::inventory::{HOTBAR_SIZE, HOTBAR_START},
+    resources::items,
+    server::Server,
+};
+
+pub fn render(gui_ctx: &Context, server: &Server) {
+    let inventory = &server.get_player().inventory;
+    let selected = HOTBAR_START + inventory.get_selected_slot();
+
+    egui::Window::new("Inventory").show(gui_ctx, |ui| {
+        egui::Grid::new("Inventory Grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, item) in inventory.get_slots().iter().enumerate() {
+                    let Some(item) = item else {
+                        continue;
+                    };
+
+                    let name = items()
+                        .get(&(item.item_id as u32))
+                        .map_or_else(|| format!("Unknown ({})", item.item_id), |i| i.name.clone());
+
+                    let slot = if (HOTBAR_START..HOTBAR_START + HOTBAR_SIZE).contains(&i) {
+                        format!("Hotbar {}", i - HOTBAR_START + 1)
+                    } else {
+                        format!("{}", i)
+                    };
+                    let colour = if i == selected {
+                        Color32::WHITE
+                    } else {
+                        Color32::LIGHT_GRAY
+                    };
+
+                    ui.label(RichText::new(slot).color(colour));
+                    ui.label(RichText::new(name).color(colour));
+                    ui.label(RichText::new(format!("x{}", item.count)).color(colour));
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,166 @@
+use std::cmp::Ordering;
+
+use egui::{Align2, Color32, Context, FontId, Id, Label, Rect, RichText, Sense, Ui, Vec2};
+use mcproto_rs::{types::Chat, v1_16_3::GameMode};
+
+use crate::{
+    gui::chat_windows::{chat_text_job, legacy_text_job},
+    server::{remote_player::RemotePlayer, Server},
+    settings::Settings,
+};
+
+/// Which column the player list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SortColumn {
+    #[default]
+    Name,
+    Ping,
+}
+
+/// Renders the player list with the header and footer the server sent. Right clicking a player
+/// lets them be ignored in chat.
+pub fn render(gui_ctx: &Context, server: &Server, settings: &mut Settings) {
+    // Sorting is remembered between frames in egui's memory
+    let sort_id = Id::new("Player list sort");
+    let mut sort: SortColumn = gui_ctx.data(|d| d.get_temp(sort_id)).unwrap_or_default();
+
+    // Ties are broken by name so entries don't jump around as players join and leave
+    let mut players: Vec<&RemotePlayer> = server.get_players().values().collect();
+    match sort {
+        SortColumn::Name => players.sort_by(|a, b| compare_names(a, b)),
+        SortColumn::Ping => {
+            players.sort_by(|a, b| a.ping.cmp(&b.ping).then_with(|| compare_names(a, b)));
+        }
+    }
+
+    egui::Window::new("Player List")
+        .id(Id::new("Player List"))
+        .title_bar(false)
+        .resizable(false)
+        .collapsible(false)
+        .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 10.0))
+        .show(gui_ctx, |ui| {
+            render_chat_centred(ui, server.get_player_list_header());
+
+            egui::Grid::new("Player List Grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    if ui
+                        .selectable_label(sort == SortColumn::Name, "Name")
+                        .clicked()
+                    {
+                        sort = SortColumn::Name;
+                    }
+                    ui.label("Mode");
+                    if ui
+                        .selectable_label(sort == SortColumn::Ping, "Ping")
+                        .clicked()
+                    {
+                        sort = SortColumn::Ping;
+                    }
+                    ui.end_row();
+
+                    let font = FontId::proportional(14.0);
+                    for player in players {
+                        let name = player.get_display_name(server.get_teams());
+                        ui.add(
+                            Label::new(legacy_text_job(&name, &font, Color32::WHITE))
+                                .sense(Sense::click()),
+                        )
+                        .context_menu(|ui| ignore_menu(ui, &player.name, settings));
+                        let (icon, mode) = gamemode_icon(&player.gamemode);
+                        ui.label(RichText::new(icon).color(Color32::LIGHT_GRAY))
+                            .on_hover_text(mode);
+                        ping_bars(ui, player.ping).on_hover_text(format!("{}ms", player.ping));
+                        ui.end_row();
+                    }
+                });
+
+            render_chat_centred(ui, server.get_player_list_footer());
+        });
+
+    gui_ctx.data_mut(|d| d.insert_temp(sort_id, sort));
+}
+
+/// Add or remove a player from the chat ignore list
+fn ignore_menu(ui: &mut Ui, name: &str, settings: &mut Settings) {
+    let ignored = settings
+        .ignored_players
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(name));
+    match ignored {
+        Some(i) => {
+            if ui.button("Unignore").clicked() {
+                settings.ignored_players.remove(i);
+                ui.close_menu();
+            }
+        }
+        None => {
+            if ui.button("Ignore").clicked() {
+                settings.ignored_players.push(name.to_string());
+                ui.close_menu();
+            }
+        }
+    }
+}
+
+fn compare_names(a: &RemotePlayer, b: &RemotePlayer) -> Ordering {
+    a.name
+        .to_lowercase()
+        .cmp(&b.name.to_lowercase())
+        .then_with(|| a.name.cmp(&b.name))
+}
+
+/// Shows the header or footer with each line centred. Servers clear them by sending empty text.
+fn render_chat_centred(ui: &mut Ui, chat: Option<&Chat>) {
+    let Some(chat) = chat else {
+        return;
+    };
+    let job = chat_text_job(chat, &FontId::proportional(14.0), Color32::WHITE);
+    if job.text.trim().is_empty() {
+        return;
+    }
+    ui.vertical_centered(|ui| {
+        ui.label(job);
+    });
+}
+
+/// A short glyph for the gamemode and its full name
+const fn gamemode_icon(gamemode: &GameMode) -> (&'static str, &'static str) {
+    match gamemode {
+        GameMode::Survival => ("S", "Survival"),
+        GameMode::Creative => ("C", "Creative"),
+        GameMode::Adventure => ("A", "Adventure"),
+        GameMode::Spectator => ("SP", "Spectator"),
+    }
+}
+
+/// Draws a 5 bar connection strength indicator, using the same thresholds as vanilla
+fn ping_bars(ui: &mut Ui, ping: i32) -> egui::Response {
+    let bars = match ping {
+        p if p < 0 => 0,
+        p if p < 150 => 5,
+        p if p < 300 => 4,
+        p if p < 600 => 3,
+        p if p < 1000 => 2,
+        _ => 1,
+    };
+    let colour = if bars == 0 {
+        Color32::DARK_GRAY
+    } else {
+        Color32::GREEN
+    };
+
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(20.0, 12.0), Sense::hover());
+    let painter = ui.painter();
+    for i in 0..5 {
+        let height = rect.height() * (i + 1) as f32 / 5.0;
+        let bar = Rect::from_min_max(
+            egui::pos2(rect.left() + i as f32 * 4.0, rect.bottom() - height),
+            egui::pos2(rect.left() + i as f32 * 4.0 + 3.0, rect.bottom()),
+        );
+        painter.rect_filled(bar, 0.0, if i < bars { colour } else { Color32::DARK_GRAY });
+    }
+
+    response
+}
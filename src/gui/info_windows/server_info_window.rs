@@ -1,7 +1,9 @@
 use egui::{Color32, Context, RichText};
 
 use crate::{
-    server::Server,
+    entities::Entity,
+    resources::{blocks, items},
+    server::{digging::REACH, Server},
     world::{
         block_coords,
         chunks::{Chunk, ChunkSection},
@@ -18,6 +20,18 @@ pub fn render(gui_ctx: &Context, server: &Server) {
             );
         });
 
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Ping: "));
+            ui.label(
+                RichText::new(
+                    server
+                        .get_own_ping()
+                        .map_or_else(|| String::from("Unknown"), |p| format!("{p}ms")),
+                )
+                .color(Color32::LIGHT_GRAY),
+            );
+        });
+
         let difficulty_locked: &str;
         if server.is_difficulty_locked() {
             difficulty_locked = "(Locked)";
@@ -69,7 +83,7 @@ pub fn render(gui_ctx: &Context, server: &Server) {
             ui.vertical(|ui| {
                 ui.label(egui::RichText::new("Health"));
                 ui.label(
-                    egui::RichText::new(format!("{:.2}", server.get_player().health))
+                    egui::RichText::new(format!("{:.2}", server.get_player().get_health()))
                         .color(Color32::LIGHT_GRAY),
                 );
             });
@@ -102,6 +116,30 @@ pub fn render(gui_ctx: &Context, server: &Server) {
             );
         });
 
+        if let Some(vehicle) = server.get_passengers().get_vehicle(server.get_player().id) {
+            ui.horizontal(|ui| {
+                let name = server
+                    .get_entity_name(vehicle)
+                    .unwrap_or_else(|| format!("entity {vehicle}"));
+                ui.label("Riding: ");
+                ui.label(RichText::new(name).color(Color32::LIGHT_GRAY));
+            });
+        }
+
+        ui.horizontal(|ui| {
+            let border = server.get_world().border();
+            let distance = border.distance_to(server.get_player().get_position());
+            ui.label("Border: ");
+            ui.label(
+                RichText::new(format!(
+                    "{:.2} away (diameter {:.1})",
+                    distance,
+                    border.get_diameter()
+                ))
+                .color(Color32::LIGHT_GRAY),
+            );
+        });
+
         ui.horizontal(|ui| {
             let look = server.get_player().get_orientation().get_look_vector();
             ui.label("Look: ");
@@ -133,6 +171,20 @@ pub fn render(gui_ctx: &Context, server: &Server) {
             );
         });
 
+        ui.horizontal(|ui| {
+            let world = server.get_world();
+            ui.label("Chunks: ");
+            ui.label(
+                RichText::new(format!(
+                    "{} loaded / {} in view (distance {})",
+                    world.get_chunks().len(),
+                    world.count_chunks_in_view(),
+                    world.get_view_distance()
+                ))
+                .color(Color32::LIGHT_GRAY),
+            );
+        });
+
         ui.horizontal(|ui| {
             ui.label("Block: ");
             ui.label(
@@ -171,7 +223,82 @@ pub fn render(gui_ctx: &Context, server: &Server) {
                     }
                 }
             }
-        })
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Target: ");
+            let text = match server.get_target() {
+                Some(target) => {
+                    let name = blocks()
+                        .get(&target.block)
+                        .map_or("Unknown", |b| b.name.as_str());
+                    format!(
+                        "{} / {} / {} ({:?}) - {}",
+                        target.position.x, target.position.y, target.position.z, target.face, name
+                    )
+                }
+                None => String::from("None"),
+            };
+            ui.label(RichText::new(text).color(Color32::LIGHT_GRAY));
+            if let Some(digging) = server.get_digging() {
+                ui.label(
+                    RichText::new(format!("{:.0}%", digging.get_progress() * 100.0))
+                        .color(Color32::LIGHT_GRAY),
+                );
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Target fluid: ");
+            let player = server.get_player();
+            let hit = server.get_world().raycast_fluids(
+                player.get_eye_position(),
+                player.get_orientation().get_look_vector(),
+                REACH,
+            );
+            let fluid = hit.and_then(|hit| {
+                let block = blocks().get(&hit.block)?;
+                block.is_fluid().then_some((hit, block))
+            });
+            let text = match fluid {
+                Some((hit, block)) => format!(
+                    "{} / {} / {} - {}",
+                    hit.position.x, hit.position.y, hit.position.z, block.name
+                ),
+                None => String::from("None"),
+            };
+            ui.label(RichText::new(text).color(Color32::LIGHT_GRAY));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Target entity: ");
+            let text = match server.get_target_entity() {
+                Some(target) => {
+                    let name = server
+                        .get_entities()
+                        .get(&target.id)
+                        .map_or("Unknown", Entity::get_type_name);
+                    format!("{} ({})", name, target.id)
+                }
+                None => String::from("None"),
+            };
+            ui.label(RichText::new(text).color(Color32::LIGHT_GRAY));
+        });
+
+        if let Some(entity) = server
+            .get_target_entity()
+            .and_then(|target| server.get_entities().get(&target.id))
+        {
+            for (slot, item) in entity.equipment.iter() {
+                let name = items()
+                    .get(&(item.item_id as u32))
+                    .map_or_else(|| format!("Unknown ({})", item.item_id), |i| i.name.clone());
+                ui.horizontal(|ui| {
+                    ui.label(format!("    {slot}: "));
+                    ui.label(RichText::new(name).color(Color32::LIGHT_GRAY));
+                });
+            }
+        }
 
         // let stack = ui.push_style_color(StyleColor::Text, [0.6, 0.6, 0.6, 1.0]);
         // ui.new_line();
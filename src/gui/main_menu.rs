@@ -1,4 +1,6 @@
-use egui::{Align2, Context, Id, ScrollArea, Vec2};
+use std::collections::VecDeque;
+
+use egui::{Align2, Color32, Context, Id, Pos2, ScrollArea, Sense, Shape, Stroke, Ui, Vec2};
 use wgpu_app::utils::persistent_window::PersistentWindow;
 
 use crate::{
@@ -8,6 +10,8 @@ use crate::{
     App,
 };
 
+use super::chat_windows::chat_component;
+
 #[allow(clippy::too_many_lines)]
 pub fn render(gui_ctx: &Context, cli: &mut App) -> Option<Server> {
     let mut serv = None;
@@ -62,12 +66,16 @@ pub fn render(gui_ctx: &Context, cli: &mut App) -> Option<Server> {
                 settings,
                 server_pings,
                 outstanding_server_pings,
+                ping_history,
                 // icon_handles,
                 ..
             } = cli;
             let wm = &mut cli.window_manager;
 
             let mut remove: Option<usize> = None;
+            // Indices of two neighbouring servers to switch places
+            let mut swap: Option<(usize, usize)> = None;
+            let count = settings.saved_servers.len();
             for (i, s) in settings.saved_servers.iter().enumerate() {
                 ui.add_space(15.0);
 
@@ -106,13 +114,19 @@ pub fn render(gui_ctx: &Context, cli: &mut App) -> Option<Server> {
                                 let len = settings.saved_servers.len();
 
                                 let index = i;
+                                let original = s.clone();
                                 let mut new = s.clone();
 
                                 // Edit
                                 wm.push(PersistentWindow::new(Box::new(
                                     move |id, _, gui_ctx, state| {
                                         let current_length = state.settings.saved_servers.len();
-                                        if current_length != len || index >= current_length {
+                                        // Close if the list changed under us, e.g. the
+                                        // server was removed or moved
+                                        if current_length != len
+                                            || index >= current_length
+                                            || state.settings.saved_servers[index] != original
+                                        {
                                             return false;
                                         }
                                         let mut open = true;
@@ -153,6 +167,13 @@ pub fn render(gui_ctx: &Context, cli: &mut App) -> Option<Server> {
                             if ui.button("Remove").clicked() {
                                 remove = Some(i);
                             }
+                            if i > 0 && ui.button("⬆").on_hover_text("Move up").clicked() {
+                                swap = Some((i - 1, i));
+                            }
+                            if i + 1 < count && ui.button("⬇").on_hover_text("Move down").clicked()
+                            {
+                                swap = Some((i, i + 1));
+                            }
                         });
                     });
 
@@ -198,12 +219,19 @@ pub fn render(gui_ctx: &Context, cli: &mut App) -> Option<Server> {
                                         }
                                     });
                                 }
-                                // ui.label(&format!("Ping: {}ms", status.ping));
+
+                                if let Some(history) = ping_history.get(&s.ip) {
+                                    ui.horizontal(|ui| {
+                                        if let Some(ping) = history.back() {
+                                            ui.label(format!("Ping: {ping}ms"));
+                                        }
+                                        ping_sparkline(ui, history);
+                                    });
+                                }
                             });
 
-                            if let Some(desc) = status.description.to_traditional() {
-                                ui.label(&desc);
-                            }
+                            let colour = ui.visuals().text_color();
+                            chat_component(ui, &status.description, colour, Color32::TRANSPARENT);
                         }
                         None => {}
                     }
@@ -216,6 +244,9 @@ pub fn render(gui_ctx: &Context, cli: &mut App) -> Option<Server> {
             if let Some(i) = remove {
                 cli.settings.saved_servers.remove(i);
             }
+            if let Some((a, b)) = swap {
+                cli.settings.saved_servers.swap(a, b);
+            }
         });
     });
 
@@ -226,10 +257,37 @@ fn connect(ip: &str, name: String) -> Result<Server, std::io::Error> {
     match NetworkManager::connect(ip) {
         Ok(server) => {
             tracing::debug!("Connected to server.");
-            server.send_command(NetworkCommand::Login(PROTOCOL, 25565, name));
+            server.send_command(NetworkCommand::Login(PROTOCOL, name));
 
             Ok(server)
         }
         Err(e) => Err(e),
     }
 }
+
+/// Draws a small line graph of recent pings, scaled so the slowest fills the height
+fn ping_sparkline(ui: &mut Ui, history: &VecDeque<u32>) {
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(60.0, 16.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, Color32::from_rgba_unmultiplied(0, 0, 0, 100));
+
+    if history.len() < 2 {
+        return;
+    }
+    let max = history.iter().copied().max().unwrap_or_default().max(1);
+    let step = rect.width() / (history.len() - 1) as f32;
+    let points: Vec<Pos2> = history
+        .iter()
+        .enumerate()
+        .map(|(i, ping)| {
+            Pos2::new(
+                rect.left() + i as f32 * step,
+                rect.bottom() - rect.height() * *ping as f32 / max as f32,
+            )
+        })
+        .collect();
+    painter.add(Shape::line(points, Stroke::new(1.0, Color32::GREEN)));
+
+    let min = history.iter().copied().min().unwrap_or_default();
+    response.on_hover_text(format!("{min}ms - {max}ms"));
+}
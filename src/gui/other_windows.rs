@@ -1,2 +1,4 @@
 pub mod fps_counter;
+pub mod open_url_window;
 pub mod options_window;
+pub mod statistics_window;
@@ -1,6 +1,6 @@
 use egui::{Align2, Color32, Context, Frame, RichText, Vec2};
 
-pub fn render(gui_ctx: &Context, fps: u32, delta: f64) {
+pub fn render(gui_ctx: &Context, fps: u32, delta: f64, ping: Option<u32>) {
     let col: Color32;
 
     if fps < 60 {
@@ -29,5 +29,18 @@ pub fn render(gui_ctx: &Context, fps: u32, delta: f64) {
                     .strong()
                     .heading(),
             );
+            if let Some(ping) = ping {
+                ui.label(
+                    RichText::new(format!("PING: {}ms", ping))
+                        .color(if ping < 150 {
+                            Color32::GREEN
+                        } else {
+                            Color32::RED
+                        })
+                        .background_color(Color32::from_rgba_unmultiplied(0, 0, 0, 175))
+                        .strong()
+                        .heading(),
+                );
+            }
         });
 }
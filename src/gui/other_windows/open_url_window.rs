@@ -0,0 +1,40 @@
+use egui::{Align2, Color32, Id, RichText, Vec2};
+use wgpu_app::utils::persistent_window::PersistentWindow;
+
+use crate::{gui::clipboard, WindowManagerType};
+
+/// Asks before opening a link someone put in chat, since it could go anywhere
+pub fn new_open_url_window(url: String) -> PersistentWindow<WindowManagerType> {
+    PersistentWindow::new(Box::new(move |id, _, gui_ctx, _| {
+        let mut open = true;
+
+        egui::Window::new("Open Link")
+            .id(Id::new(id))
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .resizable(false)
+            .collapsible(false)
+            .show(gui_ctx, |ui| {
+                ui.label(
+                    "Do you want to open this link? Never open links from people you don't trust.",
+                );
+                ui.label(RichText::new(&url).color(Color32::LIGHT_BLUE));
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Open").clicked() {
+                        gui_ctx.open_url(egui::OpenUrl::new_tab(&url));
+                        open = false;
+                    }
+                    if ui.button("Copy").clicked() {
+                        clipboard::copy(gui_ctx, url.clone());
+                        open = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        open = false;
+                    }
+                });
+            });
+
+        open
+    }))
+}
@@ -1,9 +1,9 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, process::Command};
 
-use egui::{Id, ScrollArea};
-use wgpu_app::utils::persistent_window::PersistentWindow;
+use egui::{Color32, Id, RichText, ScrollArea, Ui};
+use wgpu_app::{context::EguiManager, utils::persistent_window::PersistentWindow};
 
-use crate::WindowManagerType;
+use crate::{chat::chat_log::log_directory, settings::Action, WindowManagerType};
 
 pub fn new_options_window() -> PersistentWindow<WindowManagerType> {
     PersistentWindow::new(Box::new(move |id, _, gui_ctx, state| {
@@ -15,22 +15,34 @@ pub fn new_options_window() -> PersistentWindow<WindowManagerType> {
             .show(gui_ctx, |ui| {
                 ScrollArea::vertical().show(ui, |ui| {
                     ui.collapsing("Window", |ui| {
-                        ui.label("No settings here yet");
+                        // Applied to the surface in `App::update`
+                        ui.checkbox(&mut state.settings.vsync, "VSync");
+                        ui.checkbox(&mut state.settings.show_hud, "Show HUD");
+                        ui.horizontal(|ui| {
+                            ui.label("GUI Scale");
+                            ui.add(egui::Slider::new(
+                                &mut state.settings.ui_scale,
+                                RangeInclusive::new(
+                                    EguiManager::MIN_UI_SCALE,
+                                    EguiManager::MAX_UI_SCALE,
+                                ),
+                            ));
+                        });
                     });
 
                     ui.collapsing("Camera", |ui| {
                         ui.horizontal(|ui| {
                             ui.label("FOV");
-                            let mut fov = state.settings.fov;
                             if ui
                                 .add(egui::Slider::new(
-                                    &mut fov,
+                                    &mut state.settings.fov,
                                     RangeInclusive::new(60.0, 120.0),
                                 ))
                                 .changed()
                             {
-                                // state.rend.cam.set_fov(fov);
-                                tracing::error!("Need to set camera fov");
+                                if let Some(renderer) = &mut state.renderer {
+                                    renderer.get_camera_mut().fov = state.settings.fov as f32;
+                                }
                             }
                         });
                         ui.horizontal(|ui| {
@@ -43,6 +55,20 @@ pub fn new_options_window() -> PersistentWindow<WindowManagerType> {
                         });
                     });
 
+                    ui.collapsing("Sound", |ui| {
+                        for (label, volume) in [
+                            ("Master", &mut state.settings.master_volume),
+                            ("Music", &mut state.settings.music_volume),
+                            ("Blocks", &mut state.settings.block_volume),
+                            ("Hostile", &mut state.settings.hostile_volume),
+                        ] {
+                            ui.horizontal(|ui| {
+                                ui.label(label);
+                                ui.add(egui::Slider::new(volume, RangeInclusive::new(0.0, 1.0)));
+                            });
+                        }
+                    });
+
                     ui.collapsing("Input", |ui| {
                         ui.horizontal(|ui| {
                             ui.label("Mouse sensitivity");
@@ -51,6 +77,81 @@ pub fn new_options_window() -> PersistentWindow<WindowManagerType> {
                                 RangeInclusive::new(0.1, 10.0),
                             ));
                         });
+                        ui.checkbox(&mut state.settings.toggle_sneak, "Toggle sneak");
+                        ui.checkbox(&mut state.settings.toggle_sprint, "Toggle sprint");
+                    });
+
+                    ui.collapsing("Chat", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("History length");
+                            ui.add(egui::Slider::new(
+                                &mut state.settings.chat_history,
+                                RangeInclusive::new(50, 5000),
+                            ));
+                        });
+                        ui.checkbox(&mut state.settings.chat_timestamps, "Show timestamps");
+                        ui.checkbox(&mut state.settings.log_chat, "Log chat to disk");
+                        ui.checkbox(
+                            &mut state.settings.highlight_mentions,
+                            "Highlight messages mentioning me",
+                        );
+                        ui.add_enabled(
+                            state.settings.highlight_mentions,
+                            egui::Checkbox::new(
+                                &mut state.settings.mention_ping,
+                                "Ping when mentioned",
+                            ),
+                        );
+
+                        ui.label("Extra highlight keywords");
+                        string_list(ui, &mut state.settings.mention_keywords, "Add keyword");
+
+                        ui.label("Ignored players");
+                        string_list(ui, &mut state.settings.ignored_players, "Ignore player");
+                        if let Some(server) = &state.server {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} messages hidden",
+                                    server.get_chat().get_suppressed()
+                                ))
+                                .color(Color32::LIGHT_GRAY),
+                            );
+                        }
+
+                        if ui.button("Open log folder").clicked() {
+                            open_log_folder();
+                        }
+                    });
+
+                    ui.collapsing("Controls", |ui| {
+                        egui::Grid::new("Controls").show(ui, |ui| {
+                            for action in Action::ALL {
+                                ui.label(action.name());
+
+                                // The key is captured in `App::handle_event`
+                                let text = if state.rebinding == Some(action) {
+                                    RichText::new("Press a key...")
+                                } else {
+                                    let text =
+                                        RichText::new(format!("{:?}", state.settings.key(action)));
+                                    // Keys bound to more than one action are red
+                                    if state.settings.keybinds.has_conflict(action) {
+                                        text.color(Color32::RED)
+                                    } else {
+                                        text
+                                    }
+                                };
+                                if ui.button(text).clicked() {
+                                    state.rebinding = Some(action);
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                        if ui.button("Reset to defaults").clicked() {
+                            state.settings.keybinds.reset();
+                            state.rebinding = None;
+                        }
                     });
                 });
             });
@@ -58,3 +159,48 @@ pub fn new_options_window() -> PersistentWindow<WindowManagerType> {
         open
     }))
 }
+
+/// Edit a list of strings, each with a button to remove it
+fn string_list(ui: &mut Ui, list: &mut Vec<String>, add_text: &str) {
+    let mut removed = None;
+    for (i, item) in list.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(item);
+            if ui.small_button("x").clicked() {
+                removed = Some(i);
+            }
+        });
+    }
+    if let Some(i) = removed {
+        list.remove(i);
+    }
+    if ui.button(add_text).clicked() {
+        list.push(String::new());
+    }
+}
+
+/// Show the chat logs in the system's file manager
+fn open_log_folder() {
+    let dir = match log_directory() {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::warn!("Failed to find the chat log folder: {e}");
+            return;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        tracing::warn!("Failed to create the chat log folder: {e}");
+        return;
+    }
+
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    if let Err(e) = Command::new(program).arg(&dir).spawn() {
+        tracing::warn!("Failed to open the chat log folder: {e}");
+    }
+}
@@ -0,0 +1,54 @@
+use egui::{Color32, Id, RichText, ScrollArea};
+use wgpu_app::utils::persistent_window::PersistentWindow;
+
+use crate::WindowManagerType;
+
+/// Shows the statistics the server last sent. Only one is opened at a time, requesting them
+/// again just refreshes what this shows.
+pub fn new_statistics_window() -> PersistentWindow<WindowManagerType> {
+    PersistentWindow::new(Box::new(move |id, _, gui_ctx, state| {
+        // Nothing to show once we've left the server
+        let Some(server) = &mut state.server else {
+            return false;
+        };
+        let mut open = true;
+
+        egui::Window::new("Statistics")
+            .id(Id::new(id))
+            .open(&mut open)
+            .show(gui_ctx, |ui| {
+                let statistics = match server.get_statistics() {
+                    Some(statistics) if !statistics.is_empty() => statistics,
+                    Some(_) => {
+                        ui.label(RichText::new("No statistics yet").color(Color32::LIGHT_GRAY));
+                        return;
+                    }
+                    None => {
+                        ui.label(
+                            RichText::new("Waiting for the server...").color(Color32::LIGHT_GRAY),
+                        );
+                        return;
+                    }
+                };
+
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (category, stats) in statistics.categories() {
+                        ui.collapsing(category, |ui| {
+                            egui::Grid::new(category).striped(true).show(ui, |ui| {
+                                for stat in stats {
+                                    ui.label(&stat.name);
+                                    ui.label(RichText::new(&stat.value).color(Color32::LIGHT_GRAY));
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    }
+                });
+            });
+
+        if !open {
+            server.close_statistics_window();
+        }
+        open
+    }))
+}
@@ -9,6 +9,8 @@ pub enum PauseAction {
     Nothing,
     Disconnect,
     Unpause,
+    /// Ask the server for the player's statistics
+    Statistics,
 }
 
 /// Returns if the player has chosen to disconnect from the server
@@ -30,6 +32,10 @@ pub fn render(
                 wm.push(options_window::new_options_window());
             }
 
+            if ui.button("Statistics").clicked() {
+                out = PauseAction::Statistics;
+            }
+
             if ui.button("Disconnect").clicked() {
                 out = PauseAction::Disconnect;
             }
@@ -0,0 +1,74 @@
+use egui::{Align2, Color32, Context, FontId, Id, Order, Vec2};
+use mcproto_rs::types::Chat as ChatComponent;
+
+use crate::server::titles::Titles;
+
+use super::chat_windows::chat_text_job;
+
+const TITLE_SIZE: f32 = 48.0;
+const SUBTITLE_SIZE: f32 = 24.0;
+const ACTION_BAR_SIZE: f32 = 16.0;
+
+/// Renders the title and subtitle in the middle of the screen, and the action bar text above the
+/// hotbar
+pub fn render(gui_ctx: &Context, titles: &Titles) {
+    if let Some(title) = titles.get_title() {
+        let alpha = titles.get_title_alpha();
+        text_area(
+            gui_ctx,
+            "Title",
+            title,
+            TITLE_SIZE,
+            alpha,
+            Align2::CENTER_CENTER,
+            Vec2::new(0.0, -TITLE_SIZE),
+        );
+
+        if let Some(subtitle) = titles.get_subtitle() {
+            text_area(
+                gui_ctx,
+                "Subtitle",
+                subtitle,
+                SUBTITLE_SIZE,
+                alpha,
+                Align2::CENTER_CENTER,
+                Vec2::new(0.0, SUBTITLE_SIZE / 2.0),
+            );
+        }
+    }
+
+    if let Some(action_bar) = titles.get_action_bar() {
+        text_area(
+            gui_ctx,
+            "Action Bar",
+            action_bar,
+            ACTION_BAR_SIZE,
+            titles.get_action_bar_alpha(),
+            Align2::CENTER_BOTTOM,
+            Vec2::new(0.0, -70.0),
+        );
+    }
+}
+
+fn text_area(
+    gui_ctx: &Context,
+    id: &str,
+    text: &ChatComponent,
+    size: f32,
+    alpha: f32,
+    anchor: Align2,
+    offset: Vec2,
+) {
+    let mut job = chat_text_job(text, &FontId::proportional(size), Color32::WHITE);
+    for section in &mut job.sections {
+        section.format.color = section.format.color.gamma_multiply(alpha);
+    }
+
+    egui::Area::new(Id::new(id))
+        .anchor(anchor, offset)
+        .order(Order::Background)
+        .interactable(false)
+        .show(gui_ctx, |ui| {
+            ui.add(egui::Label::new(job).wrap(false));
+        });
+}
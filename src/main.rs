@@ -1,15 +1,20 @@
-use std::{collections::HashMap, sync::mpsc::TryRecvError};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::mpsc::TryRecvError,
+};
 
 use mcproto_rs::status;
 use network::NetworkCommand;
+use renderer::WorldRenderer;
 use server::{InputState, Server};
-use settings::Settings;
+use settings::{Action, Settings};
 use tracing_subscriber::{prelude::*, EnvFilter};
 use wgpu_app::{utils::persistent_window::PersistentWindowManager, Application};
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::WindowEvent,
-    window::WindowBuilder,
+    event::{ElementState, KeyEvent, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{Icon, WindowBuilder},
 };
 
 pub mod chat;
@@ -17,21 +22,42 @@ pub mod entities;
 pub mod gui;
 pub mod network;
 pub mod player;
+pub mod renderer;
 pub mod resources;
 pub mod server;
 pub mod settings;
+pub mod sound;
 pub mod world;
 
 type WindowManagerType = App;
 type WindowManager = PersistentWindowManager<WindowManagerType>;
 
+/// Background colour when not connected to a server
+const DEFAULT_CLEAR_COLOUR: wgpu::Color = wgpu::Color {
+    r: 0.3,
+    g: 0.6,
+    b: 0.9,
+    a: 1.0,
+};
+
+/// How quickly the field of view catches up with changes in the player's speed
+const FOV_EASING: f32 = 10.0;
+
+/// How many status pings to remember for each saved server
+const PING_HISTORY_SIZE: usize = 20;
+
 pub struct App {
     settings: Settings,
+    /// The action waiting for a key to be pressed to bind it to, from the options window
+    rebinding: Option<Action>,
 
     server: Option<Server>,
+    renderer: Option<WorldRenderer>,
 
     pub outstanding_server_pings: HashMap<String, Server>,
     pub server_pings: HashMap<String, status::StatusSpec>,
+    /// Recent status ping times for each server address, oldest first. Only kept for this session.
+    pub ping_history: HashMap<String, VecDeque<u32>>,
     // pub icon_handles: HashMap<String, RetainedImage>,
     pub window_manager: PersistentWindowManager<WindowManagerType>,
 }
@@ -42,10 +68,13 @@ impl App {
             settings: Settings::load()
                 .map_err(|e| tracing::error!("Couldn't load settings ({e}), creating new."))
                 .unwrap_or_default(),
+            rebinding: None,
             server: None,
+            renderer: None,
 
             outstanding_server_pings: HashMap::new(),
             server_pings: HashMap::new(),
+            ping_history: HashMap::new(),
 
             window_manager: PersistentWindowManager::new(),
         }
@@ -61,23 +90,41 @@ impl App {
 }
 
 impl Application for App {
-    fn init(&mut self, _ctx: &mut wgpu_app::context::Context) {
+    fn init(&mut self, ctx: &mut wgpu_app::context::Context) {
         tracing::info!("Opening!");
+
+        self.renderer = Some(WorldRenderer::new(&ctx.wgpu_state));
+        // Parse every block model up front rather than stalling on the first chunks meshed
+        let models = resources::block_models().len();
+        tracing::debug!("Parsed {models} block models");
+        ctx.wgpu_state.set_vsync(self.settings.vsync);
+        ctx.egui.set_ui_scale(self.settings.ui_scale);
     }
 
     fn update(&mut self, t: &wgpu_app::Timer, ctx: &mut wgpu_app::context::Context) {
         let delta = t.delta();
 
+        // Does nothing unless the setting has changed
+        ctx.wgpu_state.set_vsync(self.settings.vsync);
+        ctx.egui.set_ui_scale(self.settings.ui_scale);
+
         // Server stuff
         if let Some(server) = &mut self.server {
             // Update
             server.update(ctx, delta, &mut self.settings);
+            if let Some(renderer) = &mut self.renderer {
+                // Ease towards the field of view for the player's speed rather than snapping
+                let target = (self.settings.fov * server.get_player().get_fov_multiplier()) as f32;
+                let camera = renderer.get_camera_mut();
+                camera.fov += (target - camera.fov) * (FOV_EASING * delta as f32).min(1.0);
+
+                renderer.update(&ctx.wgpu_state, server, &self.settings, t.absolute_time());
+            }
 
             // Mouse handling
             ctx.block_gui_tab_input = server.get_input_state() == InputState::InteractingInfo;
             ctx.block_gui_input = server.should_grab_mouse();
-
-            // TODO - Context grab and hide mouse
+            grab_mouse(ctx, server.should_grab_mouse());
 
             // Disconnect
             match &server.connection {
@@ -93,13 +140,23 @@ impl Application for App {
             // Don't get stuck in the main menu without being able to interact with the UI
             ctx.block_gui_input = false;
             ctx.block_gui_tab_input = false;
+            grab_mouse(ctx, false);
+
+            if let Some(renderer) = &mut self.renderer {
+                renderer.clear();
+            }
         }
 
         // Outstanding server pings
         self.outstanding_server_pings
             .retain(|k, v| match v.network.recv.try_recv() {
-                Ok(NetworkCommand::ReceiveStatus(status)) => {
+                Ok(NetworkCommand::ReceiveStatus(status, ping)) => {
                     self.server_pings.insert(k.clone(), status);
+                    let history = self.ping_history.entry(k.clone()).or_default();
+                    history.push_back(ping);
+                    if history.len() > PING_HISTORY_SIZE {
+                        history.pop_front();
+                    }
                     false
                 }
                 Err(TryRecvError::Disconnected) => false,
@@ -128,26 +185,14 @@ impl Application for App {
         // *********************** WGPU
 
         {
-            // Clear screen
-            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.3,
-                            g: 0.6,
-                            b: 0.9,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
+            // Clear screen and draw the world
+            let mut render_pass =
+                ctx.wgpu_state
+                    .begin_cleared_pass(&mut encoder, &view, self.clear_color());
+
+            if let (Some(renderer), Some(_)) = (&self.renderer, &self.server) {
+                renderer.render(&mut render_pass);
+            }
         }
 
         // *********************** Egui
@@ -181,6 +226,20 @@ impl Application for App {
             .ok();
     }
 
+    /// The sky follows the time of day once we're in a world
+    fn clear_color(&self) -> Option<wgpu::Color> {
+        let colour = self.server.as_ref().map_or(DEFAULT_CLEAR_COLOUR, |server| {
+            let sky = server.get_sky_colour(&self.settings.day_colour.map(f64::from));
+            wgpu::Color {
+                r: sky.x,
+                g: sky.y,
+                b: sky.z,
+                a: 1.0,
+            }
+        });
+        Some(colour)
+    }
+
     fn handle_event(
         &mut self,
         _ctx: &mut wgpu_app::context::Context,
@@ -199,11 +258,67 @@ impl Application for App {
             } => {
                 self.settings.window_pos = Some([new_pos.x, new_pos.y]);
             }
+            winit::event::Event::WindowEvent {
+                window_id: _,
+                event:
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                physical_key: PhysicalKey::Code(key),
+                                state: ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+            } => {
+                // Escape cancels rebinding
+                if let Some(action) = self.rebinding.take() {
+                    if *key != KeyCode::Escape {
+                        self.settings.keybinds.set(action, *key);
+                    }
+                }
+            }
             _ => {}
         }
     }
 }
 
+/// Grab and hide the mouse, or free and show it again, if it isn't already
+fn grab_mouse(ctx: &mut wgpu_app::context::Context, grab: bool) {
+    if ctx.is_mouse_grabbed() == grab {
+        return;
+    }
+
+    if let Err(e) = ctx.set_mouse_grabbed(grab) {
+        let action = if grab { "grab" } else { "release" };
+        tracing::warn!("Couldn't {action} the mouse: {e}");
+    }
+    ctx.set_mouse_visible(!grab);
+}
+
+/// The logo, shrunk down to a size window managers are happy with. Without it the window just
+/// gets the platform's default icon.
+fn window_icon() -> Option<Icon> {
+    /// Largest width or height of the icon in pixels
+    const ICON_SIZE: u32 = 64;
+
+    let logo = match image::load_from_memory_with_format(
+        include_bytes!("../logo.png"),
+        image::ImageFormat::Png,
+    ) {
+        Ok(logo) => logo.thumbnail(ICON_SIZE, ICON_SIZE).into_rgba8(),
+        Err(e) => {
+            tracing::warn!("Couldn't decode the window icon: {e}");
+            return None;
+        }
+    };
+
+    let (width, height) = logo.dimensions();
+    Icon::from_rgba(logo.into_raw(), width, height)
+        .map_err(|e| tracing::warn!("Couldn't create the window icon: {e}"))
+        .ok()
+}
+
 fn main() {
     init_tracing();
 
@@ -214,7 +329,8 @@ fn main() {
         .with_title("Mink Raft :3")
         .with_inner_size(PhysicalSize::new(w, h))
         .with_min_inner_size(PhysicalSize::new(200, 200))
-        .with_resizable(true);
+        .with_resizable(true)
+        .with_window_icon(window_icon());
 
     if let Some(&[x, y]) = app.settings.window_pos.as_ref() {
         wb = wb.with_position(PhysicalPosition::new(x, y));
@@ -1,5 +1,5 @@
 use mcproto_rs::protocol::{HasPacketBody, HasPacketId, Id, PacketErr, RawPacket};
-use mcproto_rs::types::{self, BaseComponent, BytesSerializer, TextComponent, VarInt};
+use mcproto_rs::types::{BytesSerializer, VarInt};
 use mcproto_rs::{protocol, v1_16_3};
 use mcproto_rs::{status, v1_16_3::*, Serializer};
 use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib};
@@ -17,11 +17,13 @@ use std::{
 use crate::server::*;
 
 pub const PROTOCOL: i32 = 753;
+pub const DEFAULT_PORT: u16 = 25565;
 pub type PacketType = v1_16_3::Packet753;
 pub type RawPacketType<'a> = v1_16_3::RawPacket753<'a>;
 
 pub struct NetworkManager {
     pub stream: TcpStream,
+    pub address: ServerAddress,
     pub close: bool,
     pub channel: NetworkChannel,
 
@@ -43,20 +45,80 @@ pub struct ServerStatus {
     pub ping: u32,
 }
 
+/// A server address as typed by the user, split into the host and port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerAddress {
+    pub host: String,
+    pub port: u16,
+}
+
+impl ServerAddress {
+    /// Parses an address of the form `host`, `host:port`, `ipv4`, `ipv4:port`, `ipv6`,
+    /// `[ipv6]` or `[ipv6]:port`, using the default port if none is provided
+    ///
+    /// # Errors
+    ///
+    /// If the address is empty, has an unterminated `[`, or has an invalid port
+    pub fn parse(destination: &str) -> Result<ServerAddress, Error> {
+        let destination = destination.trim();
+        let invalid = |msg: &str| Error::new(ErrorKind::InvalidInput, msg.to_string());
+
+        let (host, port) = if let Some(rest) = destination.strip_prefix('[') {
+            // Bracketed IPv6 with an optional port
+            let (host, rest) = rest
+                .split_once(']')
+                .ok_or_else(|| invalid("Missing closing ']' in address"))?;
+            match rest.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None if rest.is_empty() => (host, None),
+                None => return Err(invalid("Unexpected characters after ']' in address")),
+            }
+        } else if destination.matches(':').count() > 1 {
+            // Bare IPv6, which can't carry a port without brackets
+            (destination, None)
+        } else {
+            match destination.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (destination, None),
+            }
+        };
+
+        if host.is_empty() {
+            return Err(invalid("No host provided in address"));
+        }
+
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| invalid("Invalid port in address"))?,
+            None => {
+                tracing::debug!("Server address didn't contain port, using {DEFAULT_PORT}");
+                DEFAULT_PORT
+            }
+        };
+
+        Ok(ServerAddress {
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
 // Types of Messages that can be sent
 #[derive(Debug)]
 pub enum NetworkCommand {
     Ok,
     Error(Error),
     Disconnect,
-    // Login(protocol, port, name)
-    Login(i32, u16, String),
+    // Login(protocol, name)
+    Login(i32, String),
 
     SendPacket(Vec<u8>),
     ReceivePacket(PacketType),
 
     RequestStatus,
-    ReceiveStatus(status::StatusSpec),
+    // ReceiveStatus(status, ping in ms)
+    ReceiveStatus(status::StatusSpec, u32),
 
     Spawn,
 }
@@ -77,22 +139,17 @@ impl NetworkManager {
         let (tx, ri) = mpsc::channel::<NetworkCommand>();
         let (ti, rx) = mpsc::channel::<NetworkCommand>();
 
-        let mut dest: String = destination.to_string();
-
-        // Check for port included in address
-        if !dest.contains(':') {
-            tracing::debug!("Server address didn't contain port, appending :25565");
-            dest.push_str(":25565");
-        }
+        let address = ServerAddress::parse(destination)?;
 
         //Start new thread to be the network manager
         thread::Builder::new()
             .name("NetworkManager".to_string())
             .spawn(move || {
-                match TcpStream::connect(dest) {
+                match TcpStream::connect((address.host.as_str(), address.port)) {
                     Ok(stream) => {
                         let mut nm = Box::new(NetworkManager {
                             stream,
+                            address,
                             compress: false,
                             threshold: 0,
                             close: false,
@@ -111,9 +168,10 @@ impl NetworkManager {
                         }
                         tracing::info!("Closing network connection.");
 
-                        nm.stream
-                            .shutdown(std::net::Shutdown::Both)
-                            .expect("Couldn't shutdown TCPStream");
+                        // The server may have already closed the connection on us
+                        if let Err(e) = nm.stream.shutdown(std::net::Shutdown::Both) {
+                            tracing::debug!("Couldn't shutdown TcpStream: {:?}", e);
+                        }
                     }
                     Err(e) => {
                         tracing::error!("Could not connect to server.");
@@ -158,16 +216,17 @@ impl NetworkManager {
                 Err(e) => {
                     if e.kind() == ErrorKind::WouldBlock {
                         return;
-                    } else {
-                        panic!("Error handling packet: {:?}", e);
                     }
+                    // The stream can't be trusted after a failed read, so give up on the server
+                    tracing::error!("Lost connection to server: {:?}", e);
+                    self.send_message(NetworkCommand::Error(e));
+                    self.close = true;
                 }
             }
         }
     }
 
     /// Attempts to get the next packet in the TcpStream
-    /// Panics if the TcpStream could not read the next data to prevent correupted packets and unexpected behaviour
     /// # Returns
     ///
     /// Returns a Decoded Packet ready for processing, or Error if it failed.
@@ -175,8 +234,12 @@ impl NetworkManager {
     fn next_packet(&mut self) -> io::Result<Result<PacketType, PacketErr>> {
         let mut check = [0u8];
         match self.stream.peek(&mut check) {
+            // Reading nothing means the server has closed the stream
             Ok(0) => {
-                panic!("TcpStream ded???");
+                return Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "Server closed the connection",
+                ));
             }
             Err(e) => {
                 return Err(e);
@@ -258,25 +321,12 @@ impl NetworkManager {
     /// # Returns
     ///
     /// * `Some(())` if it successfully logs in, `None` if it fails
-    fn login(&mut self, protocol: i32, port: u16, name: String) -> Option<()> {
-        use std::net::SocketAddr;
-
-        // Extracts local address from TcpStream
-        let local_addr = match self.stream.local_addr() {
-            Err(e) => {
-                panic!("Failed to get local adress from TcpStream: {}", e);
-            }
-            Ok(addr) => match addr {
-                SocketAddr::V4(local) => local.ip().to_string(),
-                SocketAddr::V6(local) => local.ip().to_string(),
-            },
-        };
-
+    fn login(&mut self, protocol: i32, name: String) -> Option<()> {
         // Construct and send handshake and login packets
         let handshake = HandshakeSpec {
             version: VarInt(protocol),
-            server_address: local_addr,
-            server_port: port,
+            server_address: self.address.host.clone(),
+            server_port: self.address.port,
             next_state: HandshakeNextState::Login,
         };
 
@@ -349,25 +399,13 @@ impl NetworkManager {
         }
     }
 
-    fn status(&mut self) -> Option<status::StatusSpec> {
-        use std::net::SocketAddr;
-
-        // Extracts local address from TcpStream
-        let local_addr = match self.stream.local_addr() {
-            Err(e) => {
-                panic!("Failed to get local adress from TcpStream: {}", e);
-            }
-            Ok(addr) => match addr {
-                SocketAddr::V4(local) => local.ip().to_string(),
-                SocketAddr::V6(local) => local.ip().to_string(),
-            },
-        };
-
+    /// Asks the server for its status, also returning how long it took to respond in ms
+    fn status(&mut self) -> Option<(status::StatusSpec, u32)> {
         // Construct and send handshake and login packets
         let handshake = HandshakeSpec {
             version: VarInt(PROTOCOL),
-            server_address: local_addr,
-            server_port: 0,
+            server_address: self.address.host.clone(),
+            server_port: self.address.port,
             next_state: HandshakeNextState::Status,
         };
 
@@ -384,8 +422,8 @@ impl NetworkManager {
         loop {
             match self.next_packet() {
                 Ok(Ok(PacketType::StatusResponse(pack))) => {
-                    // let ping = (Instant::now() - now).as_millis() as u32;
-                    return Some(pack.response);
+                    let ping = u32::try_from(now.elapsed().as_millis()).unwrap_or(u32::MAX);
+                    return Some((pack.response, ping));
                 }
                 Ok(Ok(pack)) => {
                     tracing::warn!(
@@ -453,18 +491,16 @@ impl NetworkManager {
     /// Handles a message (from another thread)
     fn handle_message(&mut self, msg: NetworkCommand) {
         match msg {
-            NetworkCommand::Login(protocol, port, name) => {
+            NetworkCommand::Login(protocol, name) => {
                 tracing::info!("Attempting to login to server");
-                self.login(protocol, port, name);
+                self.login(protocol, name);
             }
             NetworkCommand::Disconnect => {
-                self.send_packet(&encode(PacketType::PlayDisconnect(PlayDisconnectSpec {
-                    reason: types::Chat::Text(TextComponent {
-                        text: String::from("Player Disconnected"),
-                        base: BaseComponent::default(),
-                    }),
-                })))
-                .expect("Failed to send packet");
+                // There is no serverbound disconnect packet, the server just expects the
+                // connection to be closed. Any packets queued before this have already been sent.
+                if let Err(e) = self.stream.flush() {
+                    tracing::debug!("Couldn't flush TcpStream before disconnecting: {:?}", e);
+                }
                 self.close = true;
             }
             NetworkCommand::SendPacket(dp) => {
@@ -472,8 +508,8 @@ impl NetworkManager {
             }
             NetworkCommand::RequestStatus => {
                 match self.status() {
-                    Some(status) => {
-                        self.send_message(NetworkCommand::ReceiveStatus(status));
+                    Some((status, ping)) => {
+                        self.send_message(NetworkCommand::ReceiveStatus(status, ping));
                     }
                     None => {}
                 }
@@ -512,13 +548,6 @@ impl NetworkManager {
         if self.channel.send.send(comm).is_err() {
             tracing::error!("Couldn't communicated with main thread, assuming connection was closed and disconnecting from server.");
             self.close = true;
-            self.send_packet(&encode(PacketType::PlayDisconnect(PlayDisconnectSpec {
-                reason: types::Chat::Text(types::TextComponent {
-                    text: String::from("Player Disconnected"),
-                    base: types::BaseComponent::default(),
-                }),
-            })))
-            .expect("Failed to send Disconnect packet");
         }
     }
 }
@@ -578,3 +607,78 @@ pub fn encode(packet: PacketType) -> Vec<u8> {
         .expect("Failed to serialize packet");
     serializer.into_bytes()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(destination: &str) -> ServerAddress {
+        ServerAddress::parse(destination).unwrap()
+    }
+
+    fn address(host: &str, port: u16) -> ServerAddress {
+        ServerAddress {
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn bare_host_uses_default_port() {
+        assert_eq!(parse("localhost"), address("localhost", DEFAULT_PORT));
+        assert_eq!(parse("127.0.0.1"), address("127.0.0.1", 25565));
+        assert_eq!(
+            parse("  mc.example.com \n"),
+            address("mc.example.com", 25565)
+        );
+    }
+
+    #[test]
+    fn host_with_port() {
+        assert_eq!(parse("localhost:25566"), address("localhost", 25566));
+        assert_eq!(parse("127.0.0.1:1"), address("127.0.0.1", 1));
+        assert_eq!(
+            parse("mc.example.com:65535"),
+            address("mc.example.com", 65535)
+        );
+    }
+
+    #[test]
+    fn bare_ipv6() {
+        assert_eq!(parse("::1"), address("::1", DEFAULT_PORT));
+        assert_eq!(parse("2001:db8::1"), address("2001:db8::1", DEFAULT_PORT));
+    }
+
+    #[test]
+    fn bracketed_ipv6() {
+        assert_eq!(parse("[::1]"), address("::1", DEFAULT_PORT));
+        assert_eq!(parse("[2001:db8::1]:25566"), address("2001:db8::1", 25566));
+    }
+
+    #[test]
+    fn invalid_ports() {
+        for destination in [
+            "localhost:",
+            "localhost:abc",
+            "localhost:65536",
+            "localhost:-1",
+            "[::1]:",
+            "[::1]:port",
+        ] {
+            assert!(
+                ServerAddress::parse(destination).is_err(),
+                "{destination} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_addresses() {
+        for destination in ["", ":25565", "[::1", "[]:25565", "[::1]25565"] {
+            assert!(
+                ServerAddress::parse(destination).is_err(),
+                "{destination} should be rejected"
+            );
+        }
+    }
+}
@@ -1,17 +1,52 @@
 use glam::DVec3;
-use mcproto_rs::v1_16_3::{ClientChatMode, ClientDisplayedSkinParts, ClientMainHand};
+use mcproto_rs::{
+    uuid::UUID4,
+    v1_16_3::{ClientChatMode, ClientDisplayedSkinParts, ClientMainHand, GameMode},
+};
 
-use super::entities::components::Orientation;
+use super::{entities::components::Orientation, world::World};
+
+use self::{
+    abilities::Abilities,
+    inventory::Inventory,
+    physics::{Physics, DEFAULT_FLY_SPEED},
+};
+
+pub mod abilities;
+pub mod inventory;
+pub mod physics;
+
+/// Height of the player's eyes above their feet
+pub const EYE_HEIGHT: f64 = 1.62;
+/// Seconds the health bar flashes for after taking damage
+const HURT_FLASH_TIME: f64 = 0.5;
 
 pub struct Player {
     pub id: i32,
+    pub uuid: Option<UUID4>,
 
     position: DVec3,
     orientation: Orientation,
+    pub physics: Physics,
+    pub gamemode: GameMode,
+    pub abilities: Abilities,
+    /// From the `generic.movement_speed` attribute, None until the server sends it
+    pub movement_speed: Option<f64>,
 
-    pub health: f32,
+    health: f32,
+    /// Seconds left of the health bar flashing after taking damage
+    hurt_flash: f64,
     pub food: i32,
     pub saturation: f32,
+    /// Armor points from the `generic.armor` attribute, None until the server sends it
+    pub armor: Option<f64>,
+
+    /// Progress towards the next level, from 0 to 1
+    pub experience_bar: f32,
+    pub level: i32,
+    pub total_experience: i32,
+
+    pub inventory: Inventory,
 
     // Client Settings
     pub locale: String,
@@ -26,13 +61,26 @@ impl Player {
     pub fn new() -> Player {
         Player {
             id: 0,
+            uuid: None,
 
             position: DVec3::new(0.0, 0.0, 0.0),
             orientation: Orientation::new_with_values(0.0, 0.0, -89.0, 89.0),
+            physics: Physics::new(),
+            gamemode: GameMode::Survival,
+            abilities: Abilities::default(),
+            movement_speed: None,
 
             health: 20.0,
+            hurt_flash: 0.0,
             food: 20,
             saturation: 5.0,
+            armor: None,
+
+            experience_bar: 0.0,
+            level: 0,
+            total_experience: 0,
+
+            inventory: Inventory::new(),
 
             locale: String::from("en_GB"),
             view_distance: 8,
@@ -43,6 +91,85 @@ impl Player {
         }
     }
 
+    /// Restore health, hunger, experience and abilities to their defaults, e.g. after respawning.
+    /// The server sends the real values again straight after.
+    pub fn reset_status(&mut self) {
+        self.health = 20.0;
+        self.hurt_flash = 0.0;
+        self.food = 20;
+        self.saturation = 5.0;
+        self.experience_bar = 0.0;
+        self.level = 0;
+        self.total_experience = 0;
+        self.abilities = Abilities::default();
+        self.movement_speed = None;
+        self.physics.flying = false;
+        self.physics.fly_speed = DEFAULT_FLY_SPEED;
+    }
+
+    /// Change gamemode, only creative and spectator players can fly and spectators always fly
+    /// through blocks
+    pub fn set_gamemode(&mut self, gamemode: GameMode) {
+        self.gamemode = gamemode;
+        if self.gamemode == GameMode::Spectator {
+            self.physics.noclip = true;
+        } else if !self.can_fly() {
+            self.physics.noclip = false;
+        }
+    }
+
+    pub fn can_fly(&self) -> bool {
+        matches!(self.gamemode, GameMode::Creative | GameMode::Spectator)
+    }
+
+    /// Adventure and spectator players can't break or place blocks, the server would just put
+    /// them back
+    pub fn can_build(&self) -> bool {
+        !matches!(self.gamemode, GameMode::Adventure | GameMode::Spectator)
+    }
+
+    /// How much wider the field of view should be for how fast the player is moving, the same
+    /// as vanilla
+    pub fn get_fov_multiplier(&self) -> f64 {
+        let mut multiplier = if self.physics.flying { 1.1 } else { 1.0 };
+
+        let walk_speed = f64::from(self.abilities.fov_modifier);
+        if let Some(speed) = self.movement_speed {
+            if walk_speed > 0.0 && walk_speed.is_finite() {
+                multiplier *= (speed / walk_speed + 1.0) / 2.0;
+            }
+        }
+
+        if multiplier.is_finite() {
+            multiplier
+        } else {
+            1.0
+        }
+    }
+
+    pub fn get_health(&self) -> f32 {
+        self.health
+    }
+
+    /// Set the player's health, starting the health bar flashing if it went down
+    pub fn set_health(&mut self, health: f32) {
+        if health < self.health {
+            self.hurt_flash = HURT_FLASH_TIME;
+        }
+        self.health = health;
+    }
+
+    /// Seconds left of the health bar flashing, 0 when it isn't
+    pub fn get_hurt_flash(&self) -> f64 {
+        self.hurt_flash
+    }
+
+    /// Count down timed effects like the health bar flashing
+    pub fn update_status(&mut self, delta: f64) {
+        self.hurt_flash = (self.hurt_flash - delta).max(0.0);
+        self.inventory.update(delta);
+    }
+
     pub fn set_position(&mut self, pos: DVec3) {
         self.position = pos;
     }
@@ -50,6 +177,12 @@ impl Player {
         &self.position
     }
 
+    /// Where the player sees from, which rays for aiming at blocks start from
+    #[must_use]
+    pub fn get_eye_position(&self) -> DVec3 {
+        self.position + DVec3::new(0.0, EYE_HEIGHT, 0.0)
+    }
+
     pub fn set_orientation(&mut self, ori: Orientation) {
         self.orientation = ori;
     }
@@ -57,6 +190,11 @@ impl Player {
         &self.orientation
     }
 
+    /// Run a physics tick, moving the player through the world
+    pub fn tick_physics(&mut self, world: &World) {
+        self.physics.tick(&mut self.position, world);
+    }
+
     pub fn get_position_mut(&mut self) -> &mut DVec3 {
         &mut self.position
     }
@@ -0,0 +1,22 @@
+/// What the server allows the player to do, from the player abilities packet
+#[derive(Debug, Clone, Copy)]
+pub struct Abilities {
+    pub invulnerable: bool,
+    /// Double tapping jump starts and stops flying
+    pub allow_flying: bool,
+    /// Blocks break instantly
+    pub creative_mode: bool,
+    /// The walking speed the field of view is relative to, moving faster than this widens it
+    pub fov_modifier: f32,
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Self {
+            invulnerable: false,
+            allow_flying: false,
+            creative_mode: false,
+            fov_modifier: 0.1,
+        }
+    }
+}
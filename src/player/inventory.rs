@@ -0,0 +1,149 @@
+use mcproto_rs::{nbt, types::Slot};
+
+/// Number of slots in the player's inventory window, including crafting and armour slots
+pub const INVENTORY_SIZE: usize = 46;
+/// Index of the first hotbar slot in the inventory window
+pub const HOTBAR_START: usize = 36;
+/// Number of slots in the hotbar
+pub const HOTBAR_SIZE: usize = 9;
+/// Seconds the held item's name is shown for after changing slot
+pub const HELD_NAME_TIME: f64 = 3.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item_id: i32,
+    pub count: i8,
+    /// Serialized NBT data of the item, if it has any
+    pub nbt: Option<Vec<u8>>,
+    /// How worn down a tool or armour piece is, from the `Damage` tag
+    pub damage: Option<i32>,
+}
+
+impl ItemStack {
+    /// Convert the protocol representation of a slot, returning None for an empty slot
+    pub fn from_slot(slot: &Slot) -> Option<ItemStack> {
+        let item = slot.as_ref()?;
+
+        let damage = item.nbt.as_ref().and_then(|nbt| match &nbt.payload {
+            nbt::Tag::Compound(tags) => tags.iter().find_map(|tag| match tag {
+                nbt::NamedTag {
+                    name,
+                    payload: nbt::Tag::Int(damage),
+                } if name == "Damage" => Some(*damage),
+                _ => None,
+            }),
+            _ => None,
+        });
+
+        let nbt = item.nbt.as_ref().map(|nbt| nbt.bytes());
+
+        Some(ItemStack {
+            item_id: item.item_id.0,
+            count: item.item_count,
+            nbt,
+            damage,
+        })
+    }
+}
+
+/// The contents of the player's inventory, indexed the same way as the inventory window
+/// (https://wiki.vg/Inventory#Player_Inventory)
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    /// The item being carried by the mouse in an open window
+    cursor: Option<ItemStack>,
+    /// Hotbar slot currently held, 0-8
+    selected: usize,
+    /// Seconds left of showing the held item's name
+    held_name_timer: f64,
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory {
+            slots: vec![None; INVENTORY_SIZE],
+            cursor: None,
+            selected: 0,
+            held_name_timer: 0.0,
+        }
+    }
+
+    pub fn get_slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn get_slot(&self, slot: usize) -> Option<&ItemStack> {
+        self.slots.get(slot).and_then(Option::as_ref)
+    }
+
+    /// Set the contents of a slot, ignoring slots outside of the inventory
+    pub fn set_slot(&mut self, slot: usize, item: Option<ItemStack>) {
+        match self.slots.get_mut(slot) {
+            Some(s) => *s = item,
+            None => tracing::warn!("Tried to set invalid inventory slot {}", slot),
+        }
+    }
+
+    /// Replace the contents of every slot, as in a Window Items packet
+    pub fn set_all(&mut self, slots: &[Slot]) {
+        if slots.len() != INVENTORY_SIZE {
+            tracing::warn!(
+                "Got {} inventory slots, expected {}",
+                slots.len(),
+                INVENTORY_SIZE
+            );
+        }
+
+        for (i, slot) in slots.iter().take(INVENTORY_SIZE).enumerate() {
+            self.slots[i] = ItemStack::from_slot(slot);
+        }
+    }
+
+    pub fn get_cursor(&self) -> Option<&ItemStack> {
+        self.cursor.as_ref()
+    }
+
+    pub fn set_cursor(&mut self, item: Option<ItemStack>) {
+        self.cursor = item;
+    }
+
+    pub fn get_hotbar(&self) -> &[Option<ItemStack>] {
+        &self.slots[HOTBAR_START..HOTBAR_START + HOTBAR_SIZE]
+    }
+
+    /// Index of the selected hotbar slot, from 0 to 8
+    pub fn get_selected_slot(&self) -> usize {
+        self.selected
+    }
+
+    pub fn set_selected_slot(&mut self, slot: usize) {
+        if slot >= HOTBAR_SIZE {
+            tracing::warn!("Tried to select invalid hotbar slot {}", slot);
+            return;
+        }
+        if slot != self.selected {
+            self.held_name_timer = HELD_NAME_TIME;
+        }
+        self.selected = slot;
+    }
+
+    /// Seconds left of showing the held item's name above the hotbar
+    pub fn get_held_name_timer(&self) -> f64 {
+        self.held_name_timer
+    }
+
+    pub fn update(&mut self, delta: f64) {
+        self.held_name_timer = (self.held_name_timer - delta).max(0.0);
+    }
+
+    /// The item in the selected hotbar slot
+    pub fn get_held_item(&self) -> Option<&ItemStack> {
+        self.get_slot(HOTBAR_START + self.selected)
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
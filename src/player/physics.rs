@@ -0,0 +1,269 @@
+use glam::{DVec3, IVec3};
+
+use crate::{
+    resources::collision_boxes,
+    world::{block_coords, chunks::Chunk, World},
+};
+
+/// Length of a physics tick in seconds, the same as the server's
+pub const TICK: f64 = 0.05;
+
+pub const PLAYER_WIDTH: f64 = 0.6;
+pub const PLAYER_HEIGHT: f64 = 1.8;
+
+/// All of these are in blocks per tick, like vanilla
+const WALK_SPEED: f64 = 0.216;
+const GRAVITY: f64 = 0.08;
+const AIR_DRAG: f64 = 0.98;
+const TERMINAL_VELOCITY: f64 = 3.92;
+const JUMP_VELOCITY: f64 = 0.42;
+/// How much faster sprinting is than walking
+const SPRINT_MULTIPLIER: f64 = 1.3;
+/// How much faster sprinting is than flying normally
+const FLY_SPRINT_MULTIPLIER: f64 = 2.0;
+
+/// Tallest ledge the player can walk up without jumping
+const STEP_HEIGHT: f64 = 0.6;
+
+/// Vanilla's flying speed is an acceleration, these turn it into the top speed it works out to
+const FLY_HORIZONTAL_SCALE: f64 = 10.9;
+const FLY_VERTICAL_SCALE: f64 = 7.5;
+/// The server's default flying speed
+pub const DEFAULT_FLY_SPEED: f64 = 0.05;
+
+/// An axis aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl Aabb {
+    pub const FULL_BLOCK: Aabb = Aabb::new(DVec3::ZERO, DVec3::ONE);
+
+    pub const fn new(min: DVec3, max: DVec3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    /// The player's bounding box when their feet are at `position`
+    pub fn player(position: DVec3) -> Aabb {
+        Aabb::entity(position, PLAYER_WIDTH, PLAYER_HEIGHT)
+    }
+
+    /// The bounding box of an entity standing at `position`, centred on it along x and z
+    pub fn entity(position: DVec3, width: f64, height: f64) -> Aabb {
+        let half = DVec3::new(width / 2.0, 0.0, width / 2.0);
+        Aabb::new(
+            position - half,
+            position + half + DVec3::new(0.0, height, 0.0),
+        )
+    }
+
+    pub fn offset(&self, by: DVec3) -> Aabb {
+        Aabb::new(self.min + by, self.max + by)
+    }
+
+    /// Whether the boxes overlap, touching edges don't count
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|a| self.min[a] < other.max[a] && self.max[a] > other.min[a])
+    }
+
+    /// How far along a ray from `origin` it enters the box, or 0 if it starts inside
+    pub fn ray_distance(&self, origin: DVec3, direction: DVec3) -> Option<f64> {
+        let inverse = direction.recip();
+        let to_min = (self.min - origin) * inverse;
+        let to_max = (self.max - origin) * inverse;
+
+        let near = to_min.min(to_max).max_element().max(0.0);
+        let far = to_min.max(to_max).min_element();
+        (near <= far).then_some(near)
+    }
+
+    /// Grow the box to cover everywhere it passes through when moved by `movement`
+    fn stretch(&self, movement: DVec3) -> Aabb {
+        Aabb::new(
+            self.min + movement.min(DVec3::ZERO),
+            self.max + movement.max(DVec3::ZERO),
+        )
+    }
+
+    /// How far this box can move along an axis (0 for x, 1 for y, 2 for z) towards `other`
+    /// before they collide, up to `distance`
+    fn clip(&self, other: &Aabb, axis: usize, distance: f64) -> f64 {
+        // Boxes that don't overlap on the other axes can't collide
+        for a in (0..3).filter(|a| *a != axis) {
+            if self.max[a] <= other.min[a] || self.min[a] >= other.max[a] {
+                return distance;
+            }
+        }
+
+        if distance > 0.0 && self.max[axis] <= other.min[axis] {
+            distance.min(other.min[axis] - self.max[axis])
+        } else if distance < 0.0 && self.min[axis] >= other.max[axis] {
+            distance.max(other.max[axis] - self.min[axis])
+        } else {
+            distance
+        }
+    }
+}
+
+/// Simple survival movement: gravity, jumping, walking into blocks and up small ledges
+pub struct Physics {
+    /// Blocks per tick
+    pub velocity: DVec3,
+    pub on_ground: bool,
+    /// Fly through blocks without gravity, for debugging
+    pub noclip: bool,
+    /// Creative style flight, no gravity and jump and sneak move up and down
+    pub flying: bool,
+    /// From the server, see `DEFAULT_FLY_SPEED`
+    pub fly_speed: f64,
+
+    /// Direction the player is trying to walk in on the xz plane, with a length of at most 1
+    pub movement: DVec3,
+    /// If the player is trying to jump
+    pub jumping: bool,
+    /// If the player is holding sneak
+    pub sneaking: bool,
+    /// If the player is holding sprint while walking forwards
+    pub sprinting: bool,
+}
+
+impl Physics {
+    pub fn new() -> Physics {
+        Physics {
+            velocity: DVec3::ZERO,
+            on_ground: false,
+            noclip: false,
+            flying: false,
+            fly_speed: DEFAULT_FLY_SPEED,
+
+            movement: DVec3::ZERO,
+            jumping: false,
+            sneaking: false,
+            sprinting: false,
+        }
+    }
+
+    /// Move the player at `position` forward by one tick
+    pub fn tick(&mut self, position: &mut DVec3, world: &World) {
+        // Wait where we are until the chunk we're in arrives, rather than falling out of the world
+        if !world.is_chunk_loaded(&Chunk::chunk_containing(&block_coords(position))) {
+            self.velocity = DVec3::ZERO;
+            return;
+        }
+
+        if self.flying {
+            let sprint = if self.sprinting {
+                FLY_SPRINT_MULTIPLIER
+            } else {
+                1.0
+            };
+            let movement = self.movement.clamp_length_max(1.0)
+                * self.fly_speed
+                * FLY_HORIZONTAL_SCALE
+                * sprint;
+            self.velocity = DVec3::new(movement.x, 0.0, movement.z);
+            if self.jumping {
+                self.velocity.y += self.fly_speed * FLY_VERTICAL_SCALE;
+            }
+            if self.sneaking {
+                self.velocity.y -= self.fly_speed * FLY_VERTICAL_SCALE;
+            }
+        } else {
+            // Sneaking stops sprinting
+            let sprint = if self.sprinting && !self.sneaking {
+                SPRINT_MULTIPLIER
+            } else {
+                1.0
+            };
+            let movement = self.movement.clamp_length_max(1.0) * WALK_SPEED * sprint;
+            self.velocity.x = movement.x;
+            self.velocity.z = movement.z;
+            if self.jumping && self.on_ground {
+                self.velocity.y = JUMP_VELOCITY;
+            }
+        }
+
+        let wanted = self.velocity;
+        let bounds = Aabb::player(*position);
+        let mut moved = collide(world, &bounds, wanted);
+
+        // Try stepping up onto whatever we walked into
+        let blocked_horizontally = moved.x != wanted.x || moved.z != wanted.z;
+        if self.on_ground && blocked_horizontally {
+            let up = collide(world, &bounds, DVec3::new(0.0, STEP_HEIGHT, 0.0));
+            let raised = bounds.offset(up);
+            let across = collide(world, &raised, DVec3::new(wanted.x, 0.0, wanted.z));
+            let shifted = raised.offset(across);
+            let down = collide(world, &shifted, DVec3::new(0.0, -up.y, 0.0));
+
+            let stepped = up + across + down;
+            if stepped.x.hypot(stepped.z) > moved.x.hypot(moved.z) {
+                moved = stepped;
+            }
+        }
+
+        *position += moved;
+
+        self.on_ground = wanted.y < 0.0 && moved.y > wanted.y;
+        if self.flying {
+            // Landing stops flying, velocity is worked out fresh next tick otherwise
+            self.flying = !self.on_ground;
+            self.velocity.y = 0.0;
+            return;
+        }
+        if moved.y != wanted.y {
+            self.velocity.y = 0.0;
+        }
+        self.velocity.y = ((self.velocity.y - GRAVITY) * AIR_DRAG).max(-TERMINAL_VELOCITY);
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How far a box can actually move by `movement` before hitting blocks, moving along y first
+/// and then x and z
+fn collide(world: &World, bounds: &Aabb, movement: DVec3) -> DVec3 {
+    let region = bounds.stretch(movement);
+    let min = block_coords(&region.min);
+    let max = block_coords(&region.max);
+
+    // Fences and walls stick up into the block above them
+    let mut obstacles = Vec::new();
+    for x in min.x..=max.x {
+        for y in min.y - 1..=max.y {
+            for z in min.z..=max.z {
+                let pos = IVec3::new(x, y, z);
+                let Some(block) = world.block_at(&pos) else {
+                    continue;
+                };
+                obstacles.extend(
+                    collision_boxes(block)
+                        .iter()
+                        .map(|b| b.offset(pos.as_dvec3())),
+                );
+            }
+        }
+    }
+
+    let mut bounds = *bounds;
+    let mut moved = DVec3::ZERO;
+    for axis in [1, 0, 2] {
+        let mut distance = movement[axis];
+        for obstacle in &obstacles {
+            distance = bounds.clip(obstacle, axis, distance);
+        }
+
+        let mut offset = DVec3::ZERO;
+        offset[axis] = distance;
+        bounds = bounds.offset(offset);
+        moved[axis] = distance;
+    }
+
+    moved
+}
@@ -0,0 +1,624 @@
+use std::collections::HashMap;
+
+use glam::{DVec3, Vec3Swizzles};
+use wgpu::util::DeviceExt;
+use wgpu_app::context::WgpuState;
+
+use crate::{
+    player::physics::Aabb,
+    resources::{
+        block_textures, blocks, collision_boxes, missing_texture_image, BLOCK_TEXTURE_SIZE,
+    },
+    server::{InputState, Server},
+    settings::Settings,
+    world::{block_coords, coords, SectionLocation},
+};
+
+use self::{animation::TextureAnimator, camera::Camera};
+
+pub mod animation;
+pub mod camera;
+
+/// Field of view used until the camera is configured otherwise
+const DEFAULT_FOV: f32 = 90.0;
+/// Colour of entity hitboxes when they're shown
+const HITBOX_COLOUR: [f32; 3] = [1.0, 1.0, 1.0];
+/// Blocks behind the player's eyes the camera sits in third person
+const THIRD_PERSON_DISTANCE: f64 = 4.0;
+/// Colour of the outline around the block being looked at
+const TARGET_COLOUR: [f32; 3] = [0.0, 0.0, 0.0];
+/// How far the target outline sits outside the block so it isn't hidden by its faces
+const OUTLINE_GROWTH: f64 = 0.002;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct BlockVertex {
+    pub position: [f32; 3],
+    /// u, v and the index of the texture in the block texture array
+    pub tex_coords: [f32; 3],
+}
+
+impl BlockVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<BlockVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub colour: [f32; 3],
+}
+
+impl LineVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraUniform {
+    view_proj: [[f32; 4]; 4],
+    position: [f32; 4],
+}
+
+impl CameraUniform {
+    fn new(camera: &Camera) -> CameraUniform {
+        CameraUniform {
+            view_proj: camera.view_projection_matrix().to_cols_array_2d(),
+            position: camera.get_position().extend(1.0).to_array(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FogUniform {
+    colour: [f32; 4],
+    near: f32,
+    far: f32,
+    _padding: [f32; 2],
+}
+
+impl FogUniform {
+    fn new(colour: [f32; 3], near: f32, far: f32) -> FogUniform {
+        FogUniform {
+            colour: [colour[0], colour[1], colour[2], 1.0],
+            near,
+            far,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+/// A vertex buffer on the GPU and how many vertices it holds, e.g. the mesh of a chunk section
+struct SectionMesh {
+    buffer: wgpu::Buffer,
+    vertices: u32,
+}
+
+/// Draws the chunks of the world from the player's point of view
+pub struct WorldRenderer {
+    pipeline: wgpu::RenderPipeline,
+    line_pipeline: wgpu::RenderPipeline,
+
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    fog_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+
+    texture_bind_group: wgpu::BindGroup,
+    block_texture: wgpu::Texture,
+    animator: TextureAnimator,
+
+    meshes: HashMap<SectionLocation, SectionMesh>,
+    /// Outlines of every entity's bounding box, when they're turned on
+    hitboxes: Option<SectionMesh>,
+    /// Outline of the block the player is looking at
+    target_outline: Option<SectionMesh>,
+}
+
+impl WorldRenderer {
+    pub fn new(wgpu_state: &WgpuState) -> WorldRenderer {
+        let device = &wgpu_state.device;
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("renderer/world.wgsl"));
+
+        // Camera
+        let camera = Camera::new(DEFAULT_FOV);
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[CameraUniform::new(&camera)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let fog_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fog Buffer"),
+            contents: bytemuck::cast_slice(&[FogUniform::new([1.0; 3], 0.0, f32::MAX)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Camera Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera Bind Group"),
+            layout: &camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: fog_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Block textures
+        let (block_texture, layers) = create_block_texture_array(wgpu_state);
+        let texture_view = block_texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Block Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Block Texture Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2Array,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Block Texture Bind Group"),
+            layout: &texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        // Pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("World Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("World Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[BlockVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu_state.config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // Block model faces aren't wound consistently
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: WgpuState::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        // Debug lines only need the camera
+        let line_shader = device.create_shader_module(wgpu::include_wgsl!("renderer/lines.wgsl"));
+        let line_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Line Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let line_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Line Pipeline"),
+            layout: Some(&line_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &line_shader,
+                entry_point: "vs_main",
+                buffers: &[LineVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &line_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu_state.config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            // Hidden behind blocks, but lines don't hide anything themselves
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: WgpuState::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        WorldRenderer {
+            pipeline,
+            line_pipeline,
+
+            camera,
+            camera_buffer,
+            fog_buffer,
+            camera_bind_group,
+
+            texture_bind_group,
+            block_texture,
+            animator: TextureAnimator::new(layers),
+
+            meshes: HashMap::new(),
+            hitboxes: None,
+            target_outline: None,
+        }
+    }
+
+    pub fn get_camera(&self) -> &Camera {
+        &self.camera
+    }
+    pub fn get_camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Move the camera to the player, update the fog, drop the meshes of unloaded chunks and upload
+    /// any newly generated meshes. `time` is how long the game has been running, for animated
+    /// textures
+    pub fn update(
+        &mut self,
+        wgpu_state: &WgpuState,
+        server: &mut Server,
+        settings: &Settings,
+        time: f64,
+    ) {
+        self.animator
+            .update(&wgpu_state.queue, &self.block_texture, time);
+
+        // Camera
+        // Spectating puts the camera in another entity's head
+        let (eyes, yaw, pitch) = match server.get_camera_entity() {
+            Some(entity) => {
+                let (yaw, pitch) = entity.get_look_degrees();
+                (
+                    entity.pos + DVec3::new(0.0, entity.get_eye_height(), 0.0),
+                    yaw,
+                    pitch,
+                )
+            }
+            None => {
+                let player = server.get_player();
+                let orientation = player.get_orientation();
+                (
+                    player.get_eye_position(),
+                    orientation.get_yaw(),
+                    orientation.get_pitch(),
+                )
+            }
+        };
+        self.camera.set_orientation(yaw as f32, pitch as f32);
+        // Third person pulls the camera back behind the player, through any blocks in the way
+        let eyes = if server.is_third_person() && server.get_camera_entity().is_none() {
+            eyes - self.camera.get_look_vector().as_dvec3() * THIRD_PERSON_DISTANCE
+        } else {
+            eyes
+        };
+        self.camera.set_position(eyes.as_vec3());
+        self.camera.aspect = wgpu_state.size.width as f32 / wgpu_state.size.height.max(1) as f32;
+        wgpu_state.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraUniform::new(&self.camera)]),
+        );
+
+        // Fog fades into the sky
+        let sky = server.get_sky_colour(&settings.day_colour.map(f64::from));
+        let (fog_near, fog_far) = server.get_fog_range(settings);
+        wgpu_state.queue.write_buffer(
+            &self.fog_buffer,
+            0,
+            bytemuck::cast_slice(&[FogUniform::new(sky.as_vec3().to_array(), fog_near, fog_far)]),
+        );
+
+        // Meshes
+        let world = server.get_world_mut();
+        self.meshes
+            .retain(|loc, _| world.is_chunk_loaded(&loc.xz()));
+        let center = coords::section_location(&block_coords(&eyes));
+        for (loc, verts) in world.generate_meshes(center) {
+            self.load_mesh(&wgpu_state.device, loc, &verts);
+        }
+
+        // Entities move every frame so their hitboxes are rebuilt each time
+        self.hitboxes = None;
+        if server.shows_hitboxes() {
+            let verts: Vec<LineVertex> = server
+                .get_entities()
+                .values()
+                .filter_map(|entity| entity.get_bounding_box())
+                .flat_map(|bounds| box_outline(&bounds, HITBOX_COLOUR))
+                .collect();
+            self.hitboxes = line_mesh(&wgpu_state.device, "Hitbox Vertex Buffer", &verts);
+        }
+
+        // Outline the block being looked at, like the crosshair it's hidden behind menus
+        self.target_outline = None;
+        let playing = matches!(
+            server.get_input_state(),
+            InputState::Playing | InputState::ShowingInfo
+        );
+        if let Some(target) = server.get_target().filter(|_| playing) {
+            let boxes: &[Aabb] = match blocks().get(&target.block).map(collision_boxes) {
+                Some(boxes) if !boxes.is_empty() => boxes,
+                _ => &[Aabb::FULL_BLOCK],
+            };
+            let grow = DVec3::splat(OUTLINE_GROWTH);
+            let verts: Vec<LineVertex> = boxes
+                .iter()
+                .map(|b| Aabb::new(b.min - grow, b.max + grow).offset(target.position.as_dvec3()))
+                .flat_map(|bounds| box_outline(&bounds, TARGET_COLOUR))
+                .collect();
+            self.target_outline = line_mesh(&wgpu_state.device, "Target Vertex Buffer", &verts);
+        }
+    }
+
+    /// Replace the mesh of a chunk section, removing it entirely if there's nothing to draw
+    fn load_mesh(&mut self, device: &wgpu::Device, loc: SectionLocation, verts: &[BlockVertex]) {
+        if verts.is_empty() {
+            self.meshes.remove(&loc);
+            return;
+        }
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Chunk Section Vertex Buffer"),
+            contents: bytemuck::cast_slice(verts),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.meshes.insert(
+            loc,
+            SectionMesh {
+                buffer,
+                vertices: verts.len() as u32,
+            },
+        );
+    }
+
+    /// How many chunk sections have a mesh, and how many vertices they have altogether
+    pub fn mesh_stats(&self) -> (usize, u64) {
+        let vertices = self.meshes.values().map(|m| u64::from(m.vertices)).sum();
+        (self.meshes.len(), vertices)
+    }
+
+    /// Drop every chunk mesh, e.g. after leaving a server
+    pub fn clear(&mut self) {
+        self.meshes.clear();
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+
+        for mesh in self.meshes.values() {
+            render_pass.set_vertex_buffer(0, mesh.buffer.slice(..));
+            render_pass.draw(0..mesh.vertices, 0..1);
+        }
+
+        render_pass.set_pipeline(&self.line_pipeline);
+        for lines in [&self.hitboxes, &self.target_outline].into_iter().flatten() {
+            render_pass.set_vertex_buffer(0, lines.buffer.slice(..));
+            render_pass.draw(0..lines.vertices, 0..1);
+        }
+    }
+}
+
+/// Upload lines to the GPU, or nothing if there aren't any
+fn line_mesh(device: &wgpu::Device, label: &str, verts: &[LineVertex]) -> Option<SectionMesh> {
+    if verts.is_empty() {
+        return None;
+    }
+
+    let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents: bytemuck::cast_slice(verts),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+    Some(SectionMesh {
+        buffer,
+        vertices: verts.len() as u32,
+    })
+}
+
+/// The 12 edges of a box as pairs of vertices for a line list
+fn box_outline(bounds: &Aabb, colour: [f32; 3]) -> Vec<LineVertex> {
+    let (min, max) = (bounds.min.as_vec3(), bounds.max.as_vec3());
+    let corner = |i: usize| {
+        let x = if i & 1 == 0 { min.x } else { max.x };
+        let y = if i & 2 == 0 { min.y } else { max.y };
+        let z = if i & 4 == 0 { min.z } else { max.z };
+        LineVertex {
+            position: [x, y, z],
+            colour,
+        }
+    };
+
+    // Corners are numbered by which axes are at their max, so each edge joins two corners that
+    // differ in one bit
+    let mut verts = Vec::with_capacity(24);
+    for i in 0..8 {
+        for axis in [1, 2, 4] {
+            if i & axis == 0 {
+                verts.push(corner(i));
+                verts.push(corner(i | axis));
+            }
+        }
+    }
+    verts
+}
+
+/// Upload the first frame of every block texture into a texture array, one layer per texture.
+/// Returns the texture, so animated textures can be updated later, and how many layers it has
+fn create_block_texture_array(wgpu_state: &WgpuState) -> (wgpu::Texture, u32) {
+    let textures = block_textures();
+
+    let mut layers = textures
+        .values()
+        .map(|t| t.index as u32 + 1)
+        .max()
+        .unwrap_or(1);
+    let max_layers = wgpu_state.device.limits().max_texture_array_layers;
+    if layers > max_layers {
+        tracing::error!(
+            "Too many block textures ({layers}), only the first {max_layers} will be available"
+        );
+        layers = max_layers;
+    }
+
+    let size = wgpu::Extent3d {
+        width: BLOCK_TEXTURE_SIZE,
+        height: BLOCK_TEXTURE_SIZE,
+        depth_or_array_layers: layers,
+    };
+    let texture = wgpu_state.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Block Textures"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    let missing = missing_texture_image();
+    for tex in textures.values() {
+        if tex.index as u32 >= layers {
+            continue;
+        }
+
+        let frame = tex.frames.first().unwrap_or(&missing);
+        write_texture_layer(&wgpu_state.queue, &texture, tex.index as u32, frame);
+    }
+
+    (texture, layers)
+}
+
+/// Replace one layer of the block texture array
+fn write_texture_layer(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    layer: u32,
+    image: &image::RgbaImage,
+) {
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        image,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * BLOCK_TEXTURE_SIZE),
+            rows_per_image: Some(BLOCK_TEXTURE_SIZE),
+        },
+        wgpu::Extent3d {
+            width: BLOCK_TEXTURE_SIZE,
+            height: BLOCK_TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+    );
+}
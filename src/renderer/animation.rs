@@ -0,0 +1,77 @@
+use image::RgbaImage;
+
+use crate::resources::{block_textures, BlockTexture};
+
+use super::write_texture_layer;
+
+/// Animation frame times are counted in game ticks
+const TICKS_PER_SECOND: f64 = 20.0;
+
+/// An animated block texture and what was last uploaded for it
+struct Animation {
+    texture: &'static BlockTexture,
+    /// The frame shown, or for interpolated textures the tick, so unchanged frames aren't
+    /// uploaded again
+    shown: Option<u64>,
+}
+
+/// Cycles the frames of animated block textures like water, lava and fire in the block texture
+/// array. Textures with a single frame are left alone.
+pub struct TextureAnimator {
+    animations: Vec<Animation>,
+}
+
+impl TextureAnimator {
+    /// Animate every texture with more than one frame that has a layer in the texture array
+    pub fn new(layers: u32) -> TextureAnimator {
+        let animations = block_textures()
+            .values()
+            .filter(|t| t.frames.len() > 1 && (t.index as u32) < layers)
+            .map(|texture| Animation {
+                texture,
+                shown: None,
+            })
+            .collect();
+
+        TextureAnimator { animations }
+    }
+
+    /// Upload the frame each texture should be showing `time` seconds in
+    pub fn update(&mut self, queue: &wgpu::Queue, texture: &wgpu::Texture, time: f64) {
+        let ticks = (time * TICKS_PER_SECOND) as u64;
+
+        for animation in &mut self.animations {
+            let tex = animation.texture;
+            let frametime = tex.frametime.max(1) as u64;
+            let frames = tex.frames.len() as u64;
+            let frame = ticks / frametime % frames;
+
+            // Interpolated textures change every tick, the rest only when the frame does
+            let state = if tex.interpolation { ticks } else { frame };
+            if animation.shown == Some(state) {
+                continue;
+            }
+            animation.shown = Some(state);
+
+            let current = &tex.frames[frame as usize];
+            let layer = tex.index as u32;
+            if tex.interpolation {
+                let next = &tex.frames[((frame + 1) % frames) as usize];
+                let amount = (ticks % frametime) as f32 / frametime as f32;
+                write_texture_layer(queue, texture, layer, &blend(current, next, amount));
+            } else {
+                write_texture_layer(queue, texture, layer, current);
+            }
+        }
+    }
+}
+
+/// Mix two frames together, `amount` of the way from `from` to `to`
+fn blend(from: &RgbaImage, to: &RgbaImage, amount: f32) -> RgbaImage {
+    RgbaImage::from_fn(from.width(), from.height(), |x, y| {
+        let (a, b) = (from.get_pixel(x, y).0, to.get_pixel(x, y).0);
+        image::Rgba(std::array::from_fn(|i| {
+            (f32::from(a[i]) + (f32::from(b[i]) - f32::from(a[i])) * amount).round() as u8
+        }))
+    })
+}
@@ -0,0 +1,64 @@
+use glam::{Mat4, Vec3};
+
+/// Closest distance to the camera that will be drawn
+const NEAR: f32 = 0.05;
+/// Furthest distance from the camera that will be drawn
+const FAR: f32 = 1000.0;
+
+/// A perspective camera looking out from the player's eyes
+pub struct Camera {
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+
+    /// Vertical field of view in degrees
+    pub fov: f32,
+    /// Width divided by height of the viewport
+    pub aspect: f32,
+}
+
+impl Camera {
+    pub fn new(fov: f32) -> Camera {
+        Camera {
+            position: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+
+            fov,
+            aspect: 1.0,
+        }
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+    pub fn get_position(&self) -> &Vec3 {
+        &self.position
+    }
+
+    /// Set the yaw and pitch of the camera in degrees, using Minecraft's conventions
+    pub fn set_orientation(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch;
+    }
+
+    /// Returns a unit vector in the direction the camera is facing
+    pub fn get_look_vector(&self) -> Vec3 {
+        let x = -self.pitch.to_radians().cos() * self.yaw.to_radians().sin();
+        let y = -self.pitch.to_radians().sin();
+        let z = self.pitch.to_radians().cos() * self.yaw.to_radians().cos();
+        Vec3::new(x, y, z)
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_to_rh(self.position, self.get_look_vector(), Vec3::Y)
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        Mat4::perspective_rh(self.fov.to_radians(), self.aspect, NEAR, FAR)
+    }
+
+    pub fn view_projection_matrix(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+}
@@ -0,0 +1,58 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    position: vec4<f32>,
+};
+
+struct FogUniform {
+    colour: vec4<f32>,
+    near: f32,
+    far: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+@group(0) @binding(1)
+var<uniform> fog: FogUniform;
+
+@group(1) @binding(0)
+var block_textures: texture_2d_array<f32>;
+@group(1) @binding(1)
+var block_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    // u, v, texture index
+    @location(1) tex_coords: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) @interpolate(flat) texture: u32,
+    // Distance from the camera, for fog
+    @location(2) distance: f32,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(in.position, 1.0);
+    // Block models have v pointing up, textures have it pointing down
+    out.tex_coords = vec2<f32>(in.tex_coords.x, 1.0 - in.tex_coords.y);
+    out.texture = u32(in.tex_coords.z + 0.5);
+    out.distance = distance(in.position, camera.position.xyz);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSample(block_textures, block_sampler, in.tex_coords, in.texture);
+    // Cutout textures like leaves and glass
+    if colour.a < 0.5 {
+        discard;
+    }
+
+    // Fade distant geometry into the sky
+    let fog_amount = clamp((in.distance - fog.near) / max(fog.far - fog.near, 0.001), 0.0, 1.0);
+    return vec4<f32>(mix(colour.rgb, fog.colour.rgb, fog_amount), 1.0);
+}
@@ -1,8 +1,14 @@
 use std::{collections::HashMap, sync::OnceLock};
 
+use glam::DVec3;
 use inflector::Inflector;
 use serde_json::{self, Value};
 
+use crate::player::physics::Aabb;
+
+use self::block_models::BlockModel;
+
+pub mod biomes;
 pub mod block_models;
 
 pub struct Entity {
@@ -13,6 +19,13 @@ pub struct Entity {
     pub height: f32,
 }
 
+pub struct Item {
+    pub name: String,
+    pub id: u32,
+    /// How much damage the item can take before breaking, for tools and armour
+    pub max_damage: Option<u32>,
+}
+
 #[derive(Debug)]
 pub struct BlockState {
     pub name: String,
@@ -21,6 +34,66 @@ pub struct BlockState {
     pub collision_shape: Option<u64>,
 }
 
+impl BlockState {
+    /// Blocks that placing another block on top of replaces, rather than being placed against
+    pub fn is_replaceable(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "Air"
+                | "Cave Air"
+                | "Void Air"
+                | "Water"
+                | "Lava"
+                | "Grass"
+                | "Tall Grass"
+                | "Fern"
+                | "Large Fern"
+                | "Dead Bush"
+                | "Vine"
+                | "Seagrass"
+                | "Tall Seagrass"
+                | "Fire"
+                | "Soul Fire"
+                | "Structure Void"
+        )
+    }
+
+    /// Water and lava, which have no collision but can still be aimed at with a bucket
+    pub fn is_fluid(&self) -> bool {
+        matches!(self.name.as_str(), "Water" | "Lava" | "Bubble Column")
+    }
+
+    /// Blocks that open a window when used, instead of having things placed against them
+    pub fn is_container(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "Chest"
+                | "Trapped Chest"
+                | "Ender Chest"
+                | "Barrel"
+                | "Furnace"
+                | "Blast Furnace"
+                | "Smoker"
+                | "Crafting Table"
+                | "Hopper"
+                | "Dispenser"
+                | "Dropper"
+                | "Brewing Stand"
+                | "Enchanting Table"
+                | "Anvil"
+                | "Chipped Anvil"
+                | "Damaged Anvil"
+                | "Beacon"
+                | "Loom"
+                | "Stonecutter"
+                | "Grindstone"
+                | "Cartography Table"
+                | "Smithing Table"
+                | "Lectern"
+        ) || self.name.ends_with("Shulker Box")
+    }
+}
+
 #[derive(Debug)]
 pub struct BlockTexture {
     pub index: usize,
@@ -71,6 +144,126 @@ pub fn entities() -> &'static HashMap<u32, Entity> {
     })
 }
 
+pub fn items() -> &'static HashMap<u32, Item> {
+    static ITEMS: OnceLock<HashMap<u32, Item>> = OnceLock::new();
+
+    ITEMS.get_or_init(|| {
+        let mut items = HashMap::new();
+
+        let json: HashMap<String, Value> =
+            serde_json::from_slice(include_bytes!("../assets/items.min.json"))
+                .expect("Failed to interpret items.json");
+        for (name, val) in json.iter() {
+            if let Some(id) = val.get("id").and_then(Value::as_u64) {
+                items.insert(
+                    id as u32,
+                    Item {
+                        name: format_name(name),
+                        id: id as u32,
+                        max_damage: val
+                            .get("max_damage")
+                            .and_then(Value::as_u64)
+                            .filter(|max| *max > 0)
+                            .map(|max| max as u32),
+                    },
+                );
+            }
+        }
+
+        items
+    })
+}
+
+/// Names of the entries in a registry from the registries dump, keyed by their protocol id. The
+/// dump is read at runtime so a missing file only loses the names rather than failing to build.
+fn registry(name: &str) -> HashMap<u32, String> {
+    let mut entries = HashMap::new();
+
+    let json: Value = match std::fs::read("assets/registries.min.json")
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()))
+    {
+        Ok(json) => json,
+        Err(e) => {
+            tracing::error!("Couldn't load registries.json, did you run get_assets.sh? ({e})");
+            return entries;
+        }
+    };
+
+    let Some(registry) = json
+        .get(format!("minecraft:{name}"))
+        .or_else(|| json.get(name))
+        .and_then(|registry| registry.get("entries"))
+        .and_then(Value::as_object)
+    else {
+        tracing::error!("Couldn't find the {name} registry in registries.json");
+        return entries;
+    };
+    for (name, val) in registry {
+        if let Some(id) = val.get("id").and_then(Value::as_u64) {
+            entries.insert(id as u32, name.clone());
+        }
+    }
+
+    entries
+}
+
+/// Sound event names keyed by their protocol id
+pub fn sound_events() -> &'static HashMap<u32, String> {
+    static SOUND_EVENTS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+    SOUND_EVENTS.get_or_init(|| registry("sound_event"))
+}
+
+/// Statistic categories keyed by their protocol id, e.g. `minecraft:mined`
+pub fn stat_types() -> &'static HashMap<u32, String> {
+    static STAT_TYPES: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+    STAT_TYPES.get_or_init(|| registry("stat_type"))
+}
+
+/// Statistics in the `minecraft:custom` category keyed by their protocol id, e.g.
+/// `minecraft:jump`
+pub fn custom_stats() -> &'static HashMap<u32, String> {
+    static CUSTOM_STATS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+    CUSTOM_STATS.get_or_init(|| registry("custom_stat"))
+}
+
+/// Block names keyed by their registry id, which unlike `blocks` isn't per state
+pub fn block_types() -> &'static HashMap<u32, String> {
+    static BLOCK_TYPES: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+    BLOCK_TYPES.get_or_init(|| registry("block"))
+}
+
+/// The block state an item places, for items that are blocks. Items are matched to blocks by
+/// name and place the block's first state.
+pub fn item_block(item_id: u32) -> Option<&'static BlockState> {
+    static ITEM_BLOCKS: OnceLock<HashMap<u32, u32>> = OnceLock::new();
+
+    let item_blocks = ITEM_BLOCKS.get_or_init(|| {
+        let mut first_states: HashMap<&str, u32> = HashMap::new();
+        for block in blocks().values() {
+            first_states
+                .entry(block.name.as_str())
+                .and_modify(|id| *id = (*id).min(block.id))
+                .or_insert(block.id);
+        }
+
+        items()
+            .values()
+            .filter_map(|item| {
+                first_states
+                    .get(item.name.as_str())
+                    .map(|block| (item.id, *block))
+            })
+            .collect()
+    });
+
+    item_blocks.get(&item_id).and_then(|id| blocks().get(id))
+}
+
 pub fn blocks() -> &'static HashMap<u32, BlockState> {
     static BLOCKS: OnceLock<HashMap<u32, BlockState>> = OnceLock::new();
 
@@ -148,103 +341,286 @@ pub fn blocks() -> &'static HashMap<u32, BlockState> {
     })
 }
 
-/*
-pub static ref BLOCK_MODELS_RAW: HashMap<String, Value> =
-    serde_json::from_slice(include_bytes!("../assets/models.min.json"))
-        .expect("Failed to interpret models.json");
-pub static ref BLOCK_MODELS_PARSED: HashMap<String, BlockModel> = {
-    let mut models = HashMap::new();
+/// Collision shapes made up of boxes within a block, indexed by `BlockState::collision_shape`.
+/// Read from the shapes dump at runtime, empty if it couldn't be loaded.
+pub fn collision_shapes() -> &'static Vec<Vec<Aabb>> {
+    static COLLISION_SHAPES: OnceLock<Vec<Vec<Aabb>>> = OnceLock::new();
+
+    COLLISION_SHAPES.get_or_init(|| {
+        let json: Value = match std::fs::read("assets/shapes.min.json")
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()))
+        {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Couldn't load shapes.json, did you run get_assets.sh? ({e})");
+                return Vec::new();
+            }
+        };
+
+        // Corners are either [x, y, z] or a single number used for every axis
+        let corner = |val: Option<&Value>| match val? {
+            Value::Number(n) => Some(DVec3::splat(n.as_f64()?)),
+            Value::Array(a) => Some(DVec3::new(
+                a.first()?.as_f64()?,
+                a.get(1)?.as_f64()?,
+                a.get(2)?.as_f64()?,
+            )),
+            _ => None,
+        };
+        let aabbs: Vec<Option<Aabb>> = json
+            .get("aabbs")
+            .and_then(Value::as_array)
+            .map(|aabbs| {
+                aabbs
+                    .iter()
+                    .map(|aabb| {
+                        Some(Aabb::new(
+                            corner(aabb.get("from"))?,
+                            corner(aabb.get("to"))?,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Shapes are either a list of indices into the boxes or a single index
+        let Some(shapes) = json.get("shapes").and_then(Value::as_array) else {
+            tracing::error!("Couldn't find shapes in shapes.json");
+            return Vec::new();
+        };
+        shapes
+            .iter()
+            .map(|shape| {
+                let indices = match shape {
+                    Value::Array(indices) => indices.iter().filter_map(Value::as_u64).collect(),
+                    other => other.as_u64().into_iter().collect::<Vec<_>>(),
+                };
+                indices
+                    .into_iter()
+                    .filter_map(|i| *aabbs.get(i as usize)?)
+                    .collect()
+            })
+            .collect()
+    })
+}
 
-    for (key, data) in BLOCK_MODELS_RAW.iter() {
-        if models.contains_key(key) { continue; }
+/// The boxes a block state collides with, relative to the block's position
+pub fn collision_boxes(block: &BlockState) -> &'static [Aabb] {
+    const FULL_BLOCK: &[Aabb] = &[Aabb::FULL_BLOCK];
 
-        match BlockModel::parse(data, Some(&mut models)) {
-            Ok(model) => { models.insert(key.clone(), model); },
-            Err(e) => { log::debug!("Couldn't parse block model: {:?}", e); },
-        }
+    let Some(shape) = block.collision_shape else {
+        return &[];
+    };
+
+    let shapes = collision_shapes();
+    if shapes.is_empty() {
+        // Without the shapes we can only guess, the first shape is the empty one used by air
+        return if shape == 0 { &[] } else { FULL_BLOCK };
     }
+    shapes.get(shape as usize).map_or(&[], Vec::as_slice)
+}
 
-    models
-};
-pub static ref BLOCK_TEXTURES: HashMap<String, BlockTexture> = {
-    // Get list of texture and metadata files available
-    let mut textures: Vec<_> = std::fs::read_dir("assets/textures/block/")
-        .expect("Couldn't find textures directory")
-        .filter_map(|f| f.ok())
-        .filter(|f| {
-            let name = f.file_name();
-            let name = name.to_string_lossy();
-            name.ends_with(".png") || name.ends_with(".mcmeta")
-        }).collect();
-    let metadata: Vec<_> = textures.drain_filter(|f| {
-        f.file_name().to_string_lossy().ends_with(".mcmeta")
-    }).collect();
-
-    let mut out = HashMap::new();
-
-    // Load textures
-    image::load(Cursor::new(&include_bytes!("../assets/missing_texture.png")), image::ImageFormat::Png).unwrap().to_rgba8();
-    out.insert(String::new(), MISSING_TEXTURE);
-
-    let mut index: usize = 1; // Reserve index 0 for missing texture
-    for tex in textures {
-        let full_name = tex.file_name();
-        let full_name = full_name.to_string_lossy();
-        let name = full_name.split(".").nth(0).unwrap();
-
-        let data = std::fs::read(tex.path());
-        if data.is_err() {continue;}
-        let data = data.unwrap();
-        let img = image::load(Cursor::new(&data), image::ImageFormat::Png).unwrap().to_rgba8();
-
-        let mut frames = Vec::new();
-        if img.height() == 16 {
-            // Load single texture
-            frames.push(img);
-        } else {
-            // Load as multiple textures
-            let num_frames = img.height() / 16;
-            for i in 0..num_frames {
-                frames.push(image::SubImage::new(&img, 0, i * 16, 16, 16).to_image());
-            }
-        }
-        let inc = frames.len();
+/// Directory block textures are loaded from at runtime, populated by `get_assets.sh`
+pub const BLOCK_TEXTURES_DIR: &str = "assets/textures/block/";
+/// Width and height of a single block texture frame
+pub const BLOCK_TEXTURE_SIZE: u32 = 16;
 
-        out.insert(format!("minecraft:block/{}", name), BlockTexture {
-            index,
-            interpolation: false,
-            frames,
-            frametime: 0,
-        });
+pub fn block_models_raw() -> &'static HashMap<String, Value> {
+    static BLOCK_MODELS_RAW: OnceLock<HashMap<String, Value>> = OnceLock::new();
 
-        index += inc;
-    }
+    BLOCK_MODELS_RAW.get_or_init(|| {
+        serde_json::from_slice(include_bytes!("../assets/models.min.json"))
+            .expect("Failed to interpret models.json")
+    })
+}
 
-    // Add any metadata
-    for metadata in metadata {
-        let full_name = metadata.file_name();
-        let full_name = full_name.to_string_lossy();
-        let name = full_name.split(".").nth(0).unwrap();
+/// Get the raw json of a block model, accepting keys with or without the `minecraft:` namespace
+pub fn block_model_raw(key: &str) -> Option<&'static Value> {
+    let models = block_models_raw();
+    match key.strip_prefix("minecraft:") {
+        Some(stripped) => models.get(key).or_else(|| models.get(stripped)),
+        None => models
+            .get(key)
+            .or_else(|| models.get(&format!("minecraft:{key}"))),
+    }
+}
 
-        if !out.contains_key(name){continue;}
-        let tex = out.get_mut(name).unwrap();
+/// Every block model, parsed once and keyed by name as in the models dump. Models that can't be
+/// parsed, e.g. because their parent is missing, are logged and left out.
+pub fn block_models() -> &'static HashMap<String, BlockModel> {
+    static BLOCK_MODELS: OnceLock<HashMap<String, BlockModel>> = OnceLock::new();
 
-        let contents = std::fs::read_to_string(metadata.path()).unwrap();
-        let meta = serde_json::from_str::<serde_json::Value>(&contents).unwrap();
+    BLOCK_MODELS.get_or_init(|| {
+        let mut models = HashMap::new();
 
-        if let Some(anim) = meta.get("animation") {
-            if let Some(interp) = anim.get("interpolate") {
-                tex.interpolation = interp.as_bool().unwrap();
+        for (key, data) in block_models_raw() {
+            // Parents are added to the cache as they're parsed
+            if models.contains_key(key) {
+                continue;
             }
-            if let Some(frametime) = anim.get("frametime") {
-                tex.frametime = frametime.as_u64().unwrap() as usize;
+
+            match BlockModel::parse(data, Some(&mut models)) {
+                Ok(model) => {
+                    models.insert(key.clone(), model);
+                }
+                Err(e) => tracing::warn!("Couldn't parse block model {key}: {e}"),
             }
         }
+
+        models
+    })
+}
+
+/// Get a parsed block model, accepting keys with or without the `minecraft:` namespace
+pub fn block_model(key: &str) -> Option<&'static BlockModel> {
+    let models = block_models();
+    match key.strip_prefix("minecraft:") {
+        Some(stripped) => models.get(key).or_else(|| models.get(stripped)),
+        None => models
+            .get(key)
+            .or_else(|| models.get(&format!("minecraft:{key}"))),
     }
+}
 
-    out
-};
-*/
+/// Block textures keyed by their resource name (e.g. `minecraft:block/stone`). Each texture is
+/// given an index into the renderer's texture array, with index 0 reserved for the missing
+/// texture.
+pub fn block_textures() -> &'static HashMap<String, BlockTexture> {
+    static BLOCK_TEXTURES: OnceLock<HashMap<String, BlockTexture>> = OnceLock::new();
+
+    BLOCK_TEXTURES.get_or_init(|| {
+        let mut out = HashMap::new();
+        out.insert(String::new(), MISSING_TEXTURE);
+
+        // Get list of texture and metadata files available
+        let files: Vec<_> = match std::fs::read_dir(BLOCK_TEXTURES_DIR) {
+            Ok(dir) => dir
+                .filter_map(Result::ok)
+                .filter(|f| {
+                    let name = f.file_name();
+                    let name = name.to_string_lossy();
+                    name.ends_with(".png") || name.ends_with(".mcmeta")
+                })
+                .collect(),
+            Err(e) => {
+                tracing::error!(
+                    "Couldn't find block textures directory, did you run get_assets.sh? ({e})"
+                );
+                return out;
+            }
+        };
+        let (metadata, textures): (Vec<_>, Vec<_>) = files
+            .into_iter()
+            .partition(|f| f.file_name().to_string_lossy().ends_with(".mcmeta"));
+
+        // Load textures
+        let mut index: usize = 1; // Reserve index 0 for missing texture
+        for tex in textures {
+            let full_name = tex.file_name();
+            let full_name = full_name.to_string_lossy();
+            let name = full_name.split('.').next().unwrap();
+
+            let img = match std::fs::read(tex.path())
+                .map_err(|e| e.to_string())
+                .and_then(|data| {
+                    image::load_from_memory_with_format(&data, image::ImageFormat::Png)
+                        .map_err(|e| e.to_string())
+                }) {
+                Ok(img) => img.to_rgba8(),
+                Err(e) => {
+                    tracing::error!("Couldn't load texture {full_name}: {e}");
+                    continue;
+                }
+            };
+
+            // Every layer of the texture array has to be the same size
+            if img.width() != BLOCK_TEXTURE_SIZE || img.height() % BLOCK_TEXTURE_SIZE != 0 {
+                tracing::debug!(
+                    "Skipping texture {full_name} with unsupported size {}x{}",
+                    img.width(),
+                    img.height()
+                );
+                continue;
+            }
+
+            // Animated textures have their frames stacked vertically
+            let frames = (0..img.height() / BLOCK_TEXTURE_SIZE)
+                .map(|i| {
+                    image::imageops::crop_imm(
+                        &img,
+                        0,
+                        i * BLOCK_TEXTURE_SIZE,
+                        BLOCK_TEXTURE_SIZE,
+                        BLOCK_TEXTURE_SIZE,
+                    )
+                    .to_image()
+                })
+                .collect();
+
+            out.insert(
+                format!("minecraft:block/{}", name),
+                BlockTexture {
+                    index,
+                    interpolation: false,
+                    frames,
+                    frametime: 1,
+                },
+            );
+
+            index += 1;
+        }
+
+        // Add any metadata
+        for metadata in metadata {
+            let full_name = metadata.file_name();
+            let full_name = full_name.to_string_lossy();
+            let name = format!("minecraft:block/{}", full_name.split('.').next().unwrap());
+
+            let Some(tex) = out.get_mut(&name) else {
+                continue;
+            };
+
+            let Some(meta) = std::fs::read_to_string(metadata.path())
+                .ok()
+                .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+            else {
+                tracing::error!("Couldn't read texture metadata {full_name}");
+                continue;
+            };
+
+            if let Some(anim) = meta.get("animation") {
+                if let Some(interp) = anim.get("interpolate").and_then(Value::as_bool) {
+                    tex.interpolation = interp;
+                }
+                if let Some(frametime) = anim.get("frametime").and_then(Value::as_u64) {
+                    tex.frametime = frametime as usize;
+                }
+            }
+        }
+
+        out
+    })
+}
+
+/// Get a block texture by name, accepting names with or without the `minecraft:` namespace
+pub fn block_texture(name: &str) -> Option<&'static BlockTexture> {
+    let textures = block_textures();
+    textures
+        .get(name)
+        .or_else(|| textures.get(&format!("minecraft:{name}")))
+}
+
+/// Generates the magenta and black checkerboard used in place of missing textures
+pub fn missing_texture_image() -> image::RgbaImage {
+    image::RgbaImage::from_fn(BLOCK_TEXTURE_SIZE, BLOCK_TEXTURE_SIZE, |x, y| {
+        if (x / 8 + y / 8) % 2 == 0 {
+            image::Rgba([248, 0, 248, 255])
+        } else {
+            image::Rgba([0, 0, 0, 255])
+        }
+    })
+}
 
 pub fn format_name(name: &str) -> String {
     name.replace("minecraft:", "")
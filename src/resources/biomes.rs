@@ -0,0 +1,149 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use image::RgbaImage;
+use serde_json::Value;
+
+use super::format_name;
+
+/// Biome used in place of any the server sends that we don't know about, as vanilla does
+pub const PLAINS_ID: u32 = 1;
+/// Water colour of biomes that don't have their own
+const DEFAULT_WATER_COLOUR: [u8; 3] = [0x3f, 0x76, 0xe4];
+/// Grass and foliage colours of plains, used when the colormaps couldn't be loaded
+const DEFAULT_GRASS_COLOUR: [u8; 3] = [0x91, 0xbd, 0x59];
+const DEFAULT_FOLIAGE_COLOUR: [u8; 3] = [0x77, 0xab, 0x2f];
+/// Directory the grass and foliage colormaps are loaded from, populated by `get_assets.sh`
+const COLORMAP_DIR: &str = "assets/textures/colormap/";
+
+#[derive(Debug)]
+pub struct Biome {
+    pub name: String,
+    pub id: u32,
+    pub temperature: f32,
+    /// How much it rains, which along with temperature decides the colour of grass and leaves
+    pub downfall: f32,
+    pub water_colour: [u8; 3],
+}
+
+impl Biome {
+    fn plains() -> Biome {
+        Biome {
+            name: "Plains".to_string(),
+            id: PLAINS_ID,
+            temperature: 0.8,
+            downfall: 0.4,
+            water_colour: DEFAULT_WATER_COLOUR,
+        }
+    }
+
+    /// Tint for grass blocks and plants
+    pub fn grass_colour(&self) -> [u8; 3] {
+        static GRASS: OnceLock<Option<RgbaImage>> = OnceLock::new();
+
+        self.sample(colormap(&GRASS, "grass"))
+            .unwrap_or(DEFAULT_GRASS_COLOUR)
+    }
+
+    /// Tint for leaves and vines
+    pub fn foliage_colour(&self) -> [u8; 3] {
+        static FOLIAGE: OnceLock<Option<RgbaImage>> = OnceLock::new();
+
+        self.sample(colormap(&FOLIAGE, "foliage"))
+            .unwrap_or(DEFAULT_FOLIAGE_COLOUR)
+    }
+
+    /// Pick the colour for this biome's climate from a colormap. Hotter biomes are to the left
+    /// and wetter ones towards the top, with only the lower left triangle used
+    fn sample(&self, colormap: Option<&RgbaImage>) -> Option<[u8; 3]> {
+        let colormap = colormap?;
+        let temperature = self.temperature.clamp(0.0, 1.0);
+        let downfall = self.downfall.clamp(0.0, 1.0) * temperature;
+
+        let x = ((1.0 - temperature) * (colormap.width() - 1) as f32) as u32;
+        let y = ((1.0 - downfall) * (colormap.height() - 1) as f32) as u32;
+        let [r, g, b, _] = colormap.get_pixel(x, y).0;
+        Some([r, g, b])
+    }
+}
+
+/// Biomes keyed by their protocol id. Read from the biomes dump at runtime, empty if it couldn't
+/// be loaded.
+pub fn biomes() -> &'static HashMap<u32, Biome> {
+    static BIOMES: OnceLock<HashMap<u32, Biome>> = OnceLock::new();
+
+    BIOMES.get_or_init(|| {
+        let mut biomes = HashMap::new();
+
+        let json: HashMap<String, Value> = match std::fs::read("assets/biomes.min.json")
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()))
+        {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Couldn't load biomes.json, did you run get_assets.sh? ({e})");
+                return biomes;
+            }
+        };
+
+        for (name, val) in json.iter() {
+            let Some(id) = val.get("id").and_then(Value::as_u64) else {
+                continue;
+            };
+            let float = |key: &str, default: f32| {
+                val.get(key)
+                    .and_then(Value::as_f64)
+                    .map_or(default, |v| v as f32)
+            };
+            let water_colour = val
+                .get("water_color")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_WATER_COLOUR, |c| {
+                    [(c >> 16) as u8, (c >> 8) as u8, c as u8]
+                });
+
+            biomes.insert(
+                id as u32,
+                Biome {
+                    name: format_name(name),
+                    id: id as u32,
+                    temperature: float("temperature", 0.8),
+                    downfall: float("downfall", 0.4),
+                    water_colour,
+                },
+            );
+        }
+
+        biomes
+    })
+}
+
+/// The biome with the given id, or plains if it's unknown
+pub fn biome(id: u32) -> &'static Biome {
+    static PLAINS: OnceLock<Biome> = OnceLock::new();
+
+    biomes()
+        .get(&id)
+        .or_else(|| biomes().get(&PLAINS_ID))
+        .unwrap_or_else(|| PLAINS.get_or_init(Biome::plains))
+}
+
+/// A colormap, loaded into `cell` the first time it's needed
+fn colormap(cell: &'static OnceLock<Option<RgbaImage>>, name: &str) -> Option<&'static RgbaImage> {
+    cell.get_or_init(|| {
+        let path = format!("{COLORMAP_DIR}{name}.png");
+        match std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                image::load_from_memory_with_format(&data, image::ImageFormat::Png)
+                    .map_err(|e| e.to_string())
+            }) {
+            Ok(img) => Some(img.to_rgba8()),
+            Err(e) => {
+                tracing::error!("Couldn't load colormap {path}: {e}");
+                None
+            }
+        }
+    })
+    .as_ref()
+    .filter(|img| img.width() > 0 && img.height() > 0)
+}
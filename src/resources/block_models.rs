@@ -1,7 +1,28 @@
-/*
 use std::{collections::HashMap, error::Error};
 
-use glam::{Vec2, Vec3};
+use glam::{Quat, Vec2, Vec3};
+
+use crate::renderer::BlockVertex;
+
+use super::{block_model_raw, block_texture, MISSING_TEXTURE};
+
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err(format!($($arg)*).into())
+    };
+}
+
+macro_rules! require_with {
+    ($opt:expr, $($arg:tt)*) => {
+        match $opt {
+            Some(val) => val,
+            None => bail!($($arg)*),
+        }
+    };
+}
+
+/// The directions a face can point in, as they're named in models
+pub const FACE_DIRECTIONS: [&str; 6] = ["up", "down", "north", "east", "south", "west"];
 
 #[derive(Clone, Debug)]
 pub struct BlockModel {
@@ -167,58 +188,44 @@ impl BlockModel {
         base
     }
 
-    pub fn generate_mesh(
-        &self,
-        above: u16,
-        below: u16,
-        north: u16,
-        east: u16,
-        south: u16,
-        west: u16,
-    ) -> Vec<BlockVertex> {
-        let mut verts = Vec::new();
-
-        let should_cull_face = |cullface: &str| {
-            let target = match cullface {
-                "up" => above,
-                "down" => below,
-                "north" => north,
-                "east" => east,
-                "south" => south,
-                "west" => west,
-                _ => 0,
-            };
-
-            if target == 0 {
-                return false;
-            }
-
-            if let Some(block) = BLOCKS.get(&target.into()) {
-                return match &block.models {
-                    Some(models) => {
-                        let model = models.get(0).map(|s| s.as_ref()).unwrap_or("");
+    /// Whether neighbouring blocks can cull their faces against this model
+    pub const fn culls_against(&self) -> bool {
+        self.cull_against
+    }
 
-                        let exceptions: Vec<&str> = vec!["glass", "leaves", "water", "spawner"];
-                        for exception in exceptions {
-                            if model.contains(exception) {
-                                return false;
-                            }
-                        }
+    /// The texture of each face in `FACE_DIRECTIONS` order if this model is a plain full cube,
+    /// whose faces can be merged with those of matching neighbours
+    pub fn cube_textures(&self) -> Option<[f32; 6]> {
+        let [element] = self.elements.as_slice() else {
+            return None;
+        };
+        if element.from != Vec3::ZERO || element.to != Vec3::ONE || element.rot.is_some() {
+            return None;
+        }
 
-                        BLOCK_MODELS_PARSED
-                            .get(model)
-                            .map(|m| m.cull_against)
-                            .unwrap_or(false)
-                    }
-                    None => false,
-                };
+        let mut textures = [0.0; 6];
+        for (texture, direction) in textures.iter_mut().zip(FACE_DIRECTIONS) {
+            let face = element.faces.get(direction)?;
+            if face.uv != (Vec2::ZERO, Vec2::ONE)
+                || face.rotation != 0.0
+                || face.cullface != direction
+            {
+                return None;
             }
+            *texture = get_texture_index(&self.textures, &face.texture);
+        }
+        Some(textures)
+    }
 
-            false
-        };
+    /// Generate the vertices for this model at the origin. `should_cull_face` is given the
+    /// `cullface` direction of each face and returns whether the neighbour in that direction
+    /// hides it.
+    pub fn generate_mesh(&self, should_cull_face: impl Fn(&str) -> bool) -> Vec<BlockVertex> {
+        let mut verts = Vec::new();
 
         // Generate mesh for each element
         for element in &self.elements {
+            let start = verts.len();
             for (key, face) in &element.faces {
                 if should_cull_face(&face.cullface) {
                     continue;
@@ -226,164 +233,12 @@ impl BlockModel {
 
                 let texture = get_texture_index(&self.textures, &face.texture);
 
-                match key.as_ref() {
-                    "up" => {
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.1.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.0.x, face.uv.1.y, texture],
-                        });
-                    }
-                    "down" => {
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.0.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.1.x, face.uv.0.y, texture],
-                        });
-                    }
-                    "north" => {
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.1.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                    }
-                    "east" => {
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                    }
-                    "south" => {
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.0.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.to.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.0.y, texture],
-                        });
-                    }
-                    "west" => {
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.to.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.1.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.from.z],
-                            tex_coords: [face.uv.0.x, face.uv.0.y, texture],
-                        });
-                        verts.push(BlockVertex {
-                            position: [element.from.x, element.from.y, element.to.z],
-                            tex_coords: [face.uv.1.x, face.uv.0.y, texture],
-                        });
-                    }
-                    _ => {}
+                push_face(&mut verts, key, element.from, element.to, face.uv, texture);
+            }
+
+            if let Some(rot) = &element.rot {
+                for vert in &mut verts[start..] {
+                    vert.position = rot.apply(Vec3::from(vert.position)).to_array();
                 }
             }
         }
@@ -399,7 +254,7 @@ impl BlockModel {
 
         // Load parent model
         if let Some(serde_json::Value::String(parent)) = json.get("parent") {
-            match parent.as_str() {
+            match parent.trim_start_matches("minecraft:") {
                 "block/block" => base = BlockModel::block_block(),
                 "block/cube" => base = BlockModel::block_cube(),
                 "block/thin_block" => base = BlockModel::block_thin_block(),
@@ -408,7 +263,7 @@ impl BlockModel {
                     if let Some(cache) = cache {
                         // Parse parent if it isn't already parsed and add it to the cache
                         if cache.get(parent).is_none() {
-                            if let Some(parent_raw) = BLOCK_MODELS_RAW.get(parent) {
+                            if let Some(parent_raw) = block_model_raw(parent) {
                                 let parent_parsed = Self::parse(parent_raw, Some(cache))?;
                                 cache.insert(parent.clone(), parent_parsed);
                             } else {
@@ -483,7 +338,7 @@ impl Display {
             }
 
             base.rotation.x =
-                require_with!(rot.get(0).unwrap().as_f64(), "Wrong type for rotation.") as f32;
+                require_with!(rot.first().unwrap().as_f64(), "Wrong type for rotation.") as f32;
             base.rotation.y =
                 require_with!(rot.get(1).unwrap().as_f64(), "Wrong type for rotation.") as f32;
             base.rotation.z =
@@ -497,7 +352,7 @@ impl Display {
             }
 
             base.translation.x = require_with!(
-                trans.get(0).unwrap().as_f64(),
+                trans.first().unwrap().as_f64(),
                 "Wrong type for translation."
             ) as f32;
             base.translation.y = require_with!(
@@ -517,7 +372,7 @@ impl Display {
             }
 
             base.scale.x =
-                require_with!(scale.get(0).unwrap().as_f64(), "Wrong type for scale.") as f32;
+                require_with!(scale.first().unwrap().as_f64(), "Wrong type for scale.") as f32;
             base.scale.y =
                 require_with!(scale.get(1).unwrap().as_f64(), "Wrong type for scale.") as f32;
             base.scale.z =
@@ -549,7 +404,7 @@ impl Element {
             }
 
             base.from.x = require_with!(
-                from.get(0).unwrap().as_f64(),
+                from.first().unwrap().as_f64(),
                 "Wrong type for Element from."
             ) as f32
                 / 16.0;
@@ -571,7 +426,7 @@ impl Element {
                 bail!("Incorrect number of arguments in Element to");
             }
 
-            base.to.x = require_with!(to.get(0).unwrap().as_f64(), "Wrong type for Element to.")
+            base.to.x = require_with!(to.first().unwrap().as_f64(), "Wrong type for Element to.")
                 as f32
                 / 16.0;
             base.to.y = require_with!(to.get(1).unwrap().as_f64(), "Wrong type for Element to.")
@@ -623,7 +478,7 @@ impl Rotation {
             }
 
             base.origin.x = require_with!(
-                origin.get(0).unwrap().as_f64(),
+                origin.first().unwrap().as_f64(),
                 "Wrong type for Element origin."
             ) as f32
                 / 16.0;
@@ -665,6 +520,24 @@ impl Rotation {
 
         Ok(base)
     }
+
+    /// Rotate a point of the element around the origin. Rescaled elements are stretched across
+    /// the other two axes so they still span the whole block, like the crossed planes of plants
+    pub fn apply(&self, pos: Vec3) -> Vec3 {
+        // The angle is stored in steps of 22.5 degrees
+        let angle = (self.angle * 22.5).to_radians();
+        let (rotation, across) = match self.axis {
+            RotationAxis::X => (Quat::from_rotation_x(angle), Vec3::new(0.0, 1.0, 1.0)),
+            RotationAxis::Y => (Quat::from_rotation_y(angle), Vec3::new(1.0, 0.0, 1.0)),
+            RotationAxis::Z => (Quat::from_rotation_z(angle), Vec3::new(1.0, 1.0, 0.0)),
+        };
+
+        let mut offset = rotation * (pos - self.origin);
+        if self.rescale {
+            offset *= Vec3::ONE + across * (1.0 / angle.cos() - 1.0);
+        }
+        offset + self.origin
+    }
 }
 
 impl Face {
@@ -687,7 +560,7 @@ impl Face {
                 bail!("UV coordinates didn't have 4 values.");
             }
 
-            base.uv.0.x = require_with!(uv.get(0).unwrap().as_f64(), "Couldn't read UV coordinate")
+            base.uv.0.x = require_with!(uv.first().unwrap().as_f64(), "Couldn't read UV coordinate")
                 as f32
                 / 16.0;
             base.uv.0.y = require_with!(uv.get(1).unwrap().as_f64(), "Couldn't read UV coordinate")
@@ -727,24 +600,323 @@ impl Face {
     }
 }
 
+/// Push the two triangles of one face of the box from `from` to `to`, where `face` is the
+/// direction it faces. Anything but the six directions pushes nothing
+pub fn push_face(
+    verts: &mut Vec<BlockVertex>,
+    face: &str,
+    from: Vec3,
+    to: Vec3,
+    uv: (Vec2, Vec2),
+    texture: f32,
+) {
+    match face {
+        "up" => {
+            verts.push(BlockVertex {
+                position: [to.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, to.y, from.z],
+                tex_coords: [uv.1.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, to.z],
+                tex_coords: [uv.0.x, uv.1.y, texture],
+            });
+        }
+        "down" => {
+            verts.push(BlockVertex {
+                position: [to.x, from.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, to.z],
+                tex_coords: [uv.0.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, from.z],
+                tex_coords: [uv.1.x, uv.0.y, texture],
+            });
+        }
+        "north" => {
+            verts.push(BlockVertex {
+                position: [to.x, to.y, from.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, from.z],
+                tex_coords: [uv.0.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, to.y, from.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, from.z],
+                tex_coords: [uv.1.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+        }
+        "east" => {
+            verts.push(BlockVertex {
+                position: [to.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, to.y, from.z],
+                tex_coords: [uv.0.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, to.z],
+                tex_coords: [uv.1.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+        }
+        "south" => {
+            verts.push(BlockVertex {
+                position: [to.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, to.z],
+                tex_coords: [uv.0.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, to.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, to.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [to.x, from.y, to.z],
+                tex_coords: [uv.1.x, uv.0.y, texture],
+            });
+        }
+        "west" => {
+            verts.push(BlockVertex {
+                position: [from.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, from.z],
+                tex_coords: [uv.0.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, to.y, to.z],
+                tex_coords: [uv.1.x, uv.1.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, from.z],
+                tex_coords: [uv.0.x, uv.0.y, texture],
+            });
+            verts.push(BlockVertex {
+                position: [from.x, from.y, to.z],
+                tex_coords: [uv.1.x, uv.0.y, texture],
+            });
+        }
+        _ => {}
+    }
+}
+
 fn get_texture_index(texture_map: &HashMap<String, String>, texture: &str) -> f32 {
-    let texture_key: &str = texture_map
-        .get(&texture[1..])
+    let texture_key: &str = texture
+        .strip_prefix('#')
+        .and_then(|key| texture_map.get(key))
         .map(|s| s.as_str())
         .unwrap_or(texture);
-    let index = BLOCK_TEXTURES
-        .get(texture_key)
-        .unwrap_or(
-            BLOCK_TEXTURES
-                .get(&format!("minecraft:{}", texture_key))
-                .unwrap_or(&MISSING_TEXTURE),
-        )
-        .index;
+    let index = block_texture(texture_key).map_or(MISSING_TEXTURE.index, |tex| tex.index);
 
     if index == 0 {
-        log::error!("Missing texture: {}", texture_key);
+        tracing::debug!("Missing texture: {}", texture_key);
     }
 
     index as f32
 }
-*/
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A rotation around a point away from the centre of the block
+    fn rotation(axis: &str, angle: f32, rescale: bool) -> Rotation {
+        Rotation::parse(&json!({
+            "origin": [4, 8, 12],
+            "axis": axis,
+            "angle": angle,
+            "rescale": rescale,
+        }))
+        .unwrap()
+    }
+
+    fn assert_near(got: Vec3, expected: Vec3) {
+        assert!(got.abs_diff_eq(expected, 1e-5), "{got} != {expected}");
+    }
+
+    #[test]
+    fn quarter_turns() {
+        let y = rotation("y", 90.0, false);
+        assert_near(
+            y.apply(Vec3::new(1.0, 0.0, 0.75)),
+            Vec3::new(0.25, 0.0, 0.0),
+        );
+        assert_near(
+            y.apply(Vec3::new(0.25, 1.0, 1.0)),
+            Vec3::new(0.5, 1.0, 0.75),
+        );
+
+        let x = rotation("x", 90.0, false);
+        assert_near(
+            x.apply(Vec3::new(0.0, 1.0, 0.75)),
+            Vec3::new(0.0, 0.5, 1.25),
+        );
+
+        let z = rotation("z", 90.0, false);
+        assert_near(
+            z.apply(Vec3::new(1.0, 0.5, 0.0)),
+            Vec3::new(0.25, 1.25, 0.0),
+        );
+    }
+
+    #[test]
+    fn eighth_turns() {
+        let offset = 0.75 * std::f32::consts::FRAC_1_SQRT_2;
+
+        let forwards = rotation("y", 45.0, false);
+        assert_near(
+            forwards.apply(Vec3::new(1.0, 0.0, 0.75)),
+            Vec3::new(0.25 + offset, 0.0, 0.75 - offset),
+        );
+
+        let backwards = rotation("y", -45.0, false);
+        assert_near(
+            backwards.apply(Vec3::new(1.0, 0.0, 0.75)),
+            Vec3::new(0.25 + offset, 0.0, 0.75 + offset),
+        );
+    }
+
+    #[test]
+    fn rescale_stretches_across_the_axis() {
+        // A 45 degree turn is stretched by the square root of 2, reaching as far as it did before
+        let forwards = rotation("y", 45.0, true);
+        assert_near(
+            forwards.apply(Vec3::new(1.0, 0.0, 0.75)),
+            Vec3::new(1.0, 0.0, 0.0),
+        );
+        let backwards = rotation("y", -45.0, true);
+        assert_near(
+            backwards.apply(Vec3::new(1.0, 1.0, 0.75)),
+            Vec3::new(1.0, 1.0, 1.5),
+        );
+
+        let x = rotation("x", 45.0, true);
+        assert_near(
+            x.apply(Vec3::new(0.0, 1.0, 0.75)),
+            Vec3::new(0.0, 1.0, 1.25),
+        );
+
+        // Nothing moves along the axis
+        let z = rotation("z", 22.5, true);
+        assert_near(
+            z.apply(Vec3::new(0.25, 0.5, 0.0)),
+            Vec3::new(0.25, 0.5, 0.0),
+        );
+    }
+
+    #[test]
+    fn generate_mesh_only_rotates_rotated_elements() {
+        let face = |name: &str| (String::from(name), Face::empty());
+        let mut model = BlockModel::empty();
+        model.elements.push(Element {
+            from: Vec3::ZERO,
+            to: Vec3::ONE,
+            faces: HashMap::from([face("down")]),
+            ..Element::empty()
+        });
+        model.elements.push(Element {
+            from: Vec3::new(0.25, 0.0, 0.75),
+            to: Vec3::new(1.0, 1.0, 0.75),
+            rot: Some(rotation("y", 90.0, false)),
+            faces: HashMap::from([face("up")]),
+            ..Element::empty()
+        });
+
+        let verts = model.generate_mesh(|_| false);
+        assert_eq!(verts.len(), 12);
+        // The first element is left alone, while the top of the second swings a quarter turn
+        // around the origin, taking its corner from the south east to the north west
+        assert_near(Vec3::from(verts[0].position), Vec3::new(1.0, 0.0, 1.0));
+        assert_near(Vec3::from(verts[6].position), Vec3::new(0.25, 1.0, 0.0));
+    }
+
+    #[test]
+    fn origin_stays_put() {
+        let origin = Vec3::new(0.25, 0.5, 0.75);
+        for axis in ["x", "y", "z"] {
+            for angle in [-45.0, -22.5, 22.5, 45.0, 90.0] {
+                let rescale = angle != 90.0;
+                assert_near(rotation(axis, angle, rescale).apply(origin), origin);
+            }
+        }
+
+        let none = Rotation::empty();
+        assert_near(
+            none.apply(Vec3::new(0.1, 0.2, 0.3)),
+            Vec3::new(0.1, 0.2, 0.3),
+        );
+    }
+}
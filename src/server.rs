@@ -2,53 +2,164 @@ use std::{collections::HashMap, f64::consts::PI, ops::AddAssign};
 
 use glam::{DVec3, IVec2};
 use mcproto_rs::{
-    types::{self, EntityLocation, VarInt},
+    types::{self, Chat as ChatComponent, EntityLocation, IntPosition, VarInt},
     uuid::UUID4,
     v1_16_3::{
-        ClientStatusAction, Difficulty, GameMode, PlayClientChatMessageSpec,
+        BossBarAction, ChatPosition, ClientPlayerAbilities, ClientStatusAction, CombatEvent,
+        Difficulty, DiggingFace, EntityActionKind, EntityPropertyModifierOperation,
+        EntityPropertySpec, GameChangeReason, GameMode, Hand, InteractAtSpec, InteractKind,
+        PlayBlockPlacementSpec, PlayClientAnimationSpec, PlayClientChatMessageSpec,
+        PlayClientCloseWindowSpec, PlayClientHeldItemChangeSpec, PlayClientPlayerAbilitiesSpec,
         PlayClientPlayerPositionAndRotationSpec, PlayClientSettingsSpec, PlayClientStatusSpec,
-        PlayTeleportConfirmSpec, PlayerInfoAction,
+        PlayClientVehicleMoveSpec, PlayClientWindowConfirmationSpec, PlayEntityActionSpec,
+        PlayInteractEntitySpec, PlayPlayerDiggingSpec, PlayPlayerMovementSpec,
+        PlayPlayerPositionSpec, PlayPlayerRotationSpec, PlayServerChatMessageSpec,
+        PlaySteerVehicleSpec, PlayTeleportConfirmSpec, PlayUseItemSpec, PlayerDiggingStatus,
+        PlayerInfoAction, SoundCategory as PacketSoundCategory, SteerVehicleFlags, TitleActionSpec,
+        WorldBorderAction,
     },
 };
 use wgpu_app::{context::Context, Timer};
-use winit::keyboard::KeyCode;
+use winit::{keyboard::KeyCode, window::UserAttentionType};
 
 use crate::{
-    gui::{chat_windows, info_windows, pause_windows},
+    gui::{
+        boss_bar_windows, chat_windows, container_windows, death_windows, info_windows,
+        other_windows::{open_url_window, statistics_window},
+        pause_windows, title_windows,
+    },
     network::{encode, NetworkChannel, NetworkCommand, PacketType},
-    // resources::PLAYER_INDEX,
-    settings::Settings,
-    world::chunks::Chunk,
+    resources::{blocks, collision_boxes, item_block, sound_events, PLAYER_INDEX},
+    settings::{Action, Settings},
+    sound::{SoundCategory, SoundEvent, SoundManager},
+    world::{
+        chunks::BlockIndex,
+        raycast::{raycast_entities, BlockFace, EntityHit, RayHit},
+        Dimension, WorldCoords,
+    },
     WindowManager,
 };
 
-use self::remote_player::RemotePlayer;
+use self::{
+    boss_bar::BossBar,
+    commands::CommandTree,
+    container::Container,
+    day_time::DayTime,
+    digging::{Digging, SWING_PERIOD},
+    latency::LatencyTracker,
+    movement::{MovementTracker, MovementUpdate, PendingTeleport},
+    remote_player::RemotePlayer,
+    statistics::Statistics,
+    teams::Teams,
+    titles::Titles,
+    vehicles::{Passengers, Steering, VEHICLE_SPEED},
+    weather::Weather,
+};
 
-use super::{chat::Chat, entities::Entity, player::Player, world::World};
+use super::{
+    chat::{self, chat_log::ChatLog, Chat},
+    entities::Entity,
+    player::{
+        inventory::{ItemStack, HOTBAR_SIZE, HOTBAR_START},
+        physics::{self, Aabb},
+        Player,
+    },
+    world::World,
+};
 
+pub mod boss_bar;
+pub mod commands;
+pub mod container;
+pub mod day_time;
+pub mod digging;
+pub mod latency;
+pub mod movement;
 pub mod remote_player;
+pub mod statistics;
+pub mod teams;
+pub mod titles;
+pub mod vehicles;
+pub mod weather;
 
 pub struct Server {
     network_destination: String,
     pub network: NetworkChannel,
 
     input_state: InputState,
+    /// Set once the player has asked to respawn, until the server teleports them
+    awaiting_respawn: bool,
+    /// Why the player last died, from the combat event the server sends
+    death_message: Option<ChatComponent>,
 
     world_time: i64,
-    day_time: i64,
+    day_time: DayTime,
 
     position_update_timer: Timer,
+    /// What we've told the server about where the player is, and teleports to confirm once the
+    /// packets that arrived this frame have been handled
+    movement: MovementTracker,
+    physics_timer: Timer,
 
     player: Player,
+    /// Scrolling that hasn't added up to a whole hotbar slot yet
+    hotbar_scroll: f32,
+    /// Seconds left to press jump again to toggle flying
+    jump_tap_timer: f64,
     chat: Chat,
+    chat_log: ChatLog,
+    titles: Titles,
+    boss_bars: HashMap<UUID4, BossBar>,
+    /// Incremented for each boss bar added, so they keep a stable order on screen
+    boss_bar_count: u64,
 
     world: World,
+    /// Identifier of the dimension the player is in, e.g. `minecraft:overworld`
+    dimension: String,
+    /// The block the player is looking at, if it's within reach
+    target: Option<RayHit>,
+    /// The entity the player is looking at, if it's within reach and in front of `target`
+    target_entity: Option<EntityHit>,
+    digging: Option<Digging>,
+    /// Seconds until holding left click at nothing swings the arm again
+    swing_cooldown: f64,
+    /// Blocks we've placed ahead of the server confirming them
+    predicted_blocks: HashMap<WorldCoords, BlockIndex>,
 
     entities: HashMap<i32, Entity>,
     players: HashMap<UUID4, RemotePlayer>,
+    teams: Teams,
+    commands: CommandTree,
+    /// The window the server has opened, if any
+    container: Option<Container>,
+    /// Entity the server wants us to see through, None for the player. It may not have spawned
+    /// yet, so it's looked up each frame.
+    camera_target: Option<i32>,
+    /// Looking at the player from behind rather than through their eyes
+    third_person: bool,
+    /// Outline the bounding box of every entity, for debugging
+    show_hitboxes: bool,
+    /// Whether sneak and sprint are on, when they're set to toggle rather than be held
+    sneak_toggled: bool,
+    sprint_toggled: bool,
+    /// What we last told the server about the player sneaking and sprinting
+    sent_sneaking: bool,
+    sent_sprinting: bool,
+    passengers: Passengers,
+    /// How the player is steering the vehicle they're riding this frame
+    steering: Steering,
+    /// The last statistics the server sent, None until they're first requested
+    statistics: Option<Statistics>,
+    /// So asking for statistics again refreshes the window that's already open
+    statistics_window_open: bool,
+    latency: LatencyTracker,
+    /// Only started once we've joined the game, so status pings don't open audio devices
+    sound: Option<SoundManager>,
+    player_list_header: Option<ChatComponent>,
+    player_list_footer: Option<ChatComponent>,
 
     difficulty: Difficulty,
     difficulty_locked: bool,
+    weather: Weather,
 
     pub connection: ConnectionState,
 }
@@ -68,6 +179,9 @@ pub enum ConnectionState {
 /// `InteractingInfo` - Debug and other useful info is visible, mouse is visible and can interact
 /// with the info windows
 /// `ChatOpen` - Chat is visible and interactable, mouse is visible and can scroll through the chat
+/// `Dead` - The death screen is visible, the player can't move until they respawn
+/// `ContainerOpen` - A window the server opened, e.g. a chest, is visible and the mouse can
+/// interact with it
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum InputState {
     Playing,
@@ -75,32 +189,70 @@ pub enum InputState {
     ShowingInfo,
     InteractingInfo,
     ChatOpen,
+    Dead,
+    ContainerOpen,
 }
 
 impl Server {
     #[must_use]
     pub fn new(network_destination: String, network: NetworkChannel) -> Self {
+        let chat_log = ChatLog::new(&network_destination);
         Self {
             network_destination,
             network,
 
             input_state: InputState::Playing,
+            awaiting_respawn: false,
+            death_message: None,
 
             world_time: 0,
-            day_time: 0,
+            day_time: DayTime::new(),
 
             player: Player::new(),
+            hotbar_scroll: 0.0,
+            jump_tap_timer: 0.0,
             chat: Chat::new(),
+            chat_log,
+            titles: Titles::new(),
+            boss_bars: HashMap::new(),
+            boss_bar_count: 0,
 
             world: World::new(),
+            dimension: String::from("minecraft:overworld"),
+            target: None,
+            target_entity: None,
+            digging: None,
+            swing_cooldown: 0.0,
+            predicted_blocks: HashMap::new(),
 
             position_update_timer: Timer::new_with_period(0.05),
+            movement: MovementTracker::new(),
+            physics_timer: Timer::new_with_period(physics::TICK),
 
             entities: HashMap::new(),
             players: HashMap::new(),
+            teams: Teams::new(),
+            commands: CommandTree::new(),
+            container: None,
+            camera_target: None,
+            third_person: false,
+            show_hitboxes: false,
+            sneak_toggled: false,
+            sprint_toggled: false,
+            sent_sneaking: false,
+            sent_sprinting: false,
+            passengers: Passengers::new(),
+            steering: Steering::default(),
+            statistics: None,
+            statistics_window_open: false,
+            latency: LatencyTracker::new(),
+            sound: None,
+            player_list_header: None,
+            player_list_footer: None,
 
             difficulty: Difficulty::Easy,
             difficulty_locked: false,
+            weather: Weather::new(),
 
             connection: ConnectionState::Connected,
         }
@@ -116,6 +268,11 @@ impl Server {
         self.input_state
     }
 
+    #[must_use]
+    pub fn get_weather(&self) -> &Weather {
+        &self.weather
+    }
+
     #[must_use]
     pub fn get_world_time(&self) -> i64 {
         self.world_time
@@ -123,7 +280,13 @@ impl Server {
 
     #[must_use]
     pub fn get_day_time(&self) -> i64 {
-        self.day_time
+        self.day_time.get_ticks()
+    }
+
+    /// How far through the day it is from 0.0 to 1.0, smoothed between the server's updates
+    #[must_use]
+    pub fn get_time_fraction(&self) -> f64 {
+        self.day_time.get_fraction()
     }
 
     #[must_use]
@@ -145,6 +308,35 @@ impl Server {
         &self.world
     }
 
+    pub fn get_world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    #[must_use]
+    pub fn get_dimension(&self) -> &str {
+        &self.dimension
+    }
+
+    #[must_use]
+    pub fn get_dimension_kind(&self) -> Dimension {
+        Dimension::from_identifier(&self.dimension)
+    }
+
+    #[must_use]
+    pub fn get_target(&self) -> Option<&RayHit> {
+        self.target.as_ref()
+    }
+
+    #[must_use]
+    pub fn get_target_entity(&self) -> Option<&EntityHit> {
+        self.target_entity.as_ref()
+    }
+
+    #[must_use]
+    pub fn get_digging(&self) -> Option<&Digging> {
+        self.digging.as_ref()
+    }
+
     #[must_use]
     pub fn get_entities(&self) -> &HashMap<i32, Entity> {
         &self.entities
@@ -169,8 +361,74 @@ impl Server {
         self.input_state = state;
     }
 
+    /// Open the pause menu. Toggled sneak and sprint turn off so the player doesn't come back to
+    /// them still on without noticing.
+    fn pause(&mut self) {
+        self.input_state = InputState::Paused;
+        self.reset_toggles();
+    }
+
+    fn reset_toggles(&mut self) {
+        self.sneak_toggled = false;
+        self.sprint_toggled = false;
+    }
+
+    #[must_use]
+    pub fn is_sneak_toggled(&self) -> bool {
+        self.sneak_toggled
+    }
+
+    #[must_use]
+    pub fn is_sprint_toggled(&self) -> bool {
+        self.sprint_toggled
+    }
+
     pub fn join_game(&mut self, player_id: i32) {
         self.player.id = player_id;
+        self.camera_target = None;
+        self.passengers.clear();
+    }
+
+    /// The entity the server has put the camera on instead of the player, if it has spawned
+    #[must_use]
+    pub fn get_camera_entity(&self) -> Option<&Entity> {
+        self.camera_target.and_then(|id| self.entities.get(&id))
+    }
+
+    #[must_use]
+    pub fn is_third_person(&self) -> bool {
+        self.third_person
+    }
+
+    #[must_use]
+    pub fn shows_hitboxes(&self) -> bool {
+        self.show_hitboxes
+    }
+
+    pub fn set_show_hitboxes(&mut self, show: bool) {
+        self.show_hitboxes = show;
+    }
+
+    /// What to call the entity the camera is following, if it isn't the player
+    #[must_use]
+    pub fn get_spectating_name(&self) -> Option<String> {
+        self.get_entity_name(self.camera_target?)
+    }
+
+    /// The name of a player entity, or what kind of entity it is otherwise
+    #[must_use]
+    pub fn get_entity_name(&self, id: i32) -> Option<String> {
+        let entity = self.entities.get(&id)?;
+        let name = match self.players.get(&entity.get_uuid()) {
+            Some(player) => player.name.clone(),
+            None => entity.get_type_name().to_owned(),
+        };
+        Some(name)
+    }
+
+    #[must_use]
+    pub fn get_passengers(&self) -> &Passengers {
+        &self.passengers
     }
 
     #[must_use]
@@ -178,22 +436,133 @@ impl Server {
         &self.players
     }
 
+    #[must_use]
+    pub fn get_teams(&self) -> &Teams {
+        &self.teams
+    }
+
+    pub fn get_commands(&self) -> &CommandTree {
+        &self.commands
+    }
+
+    #[must_use]
+    pub fn get_statistics(&self) -> Option<&Statistics> {
+        self.statistics.as_ref()
+    }
+
+    pub fn close_statistics_window(&mut self) {
+        self.statistics_window_open = false;
+    }
+
+    #[must_use]
+    pub fn get_player_list_header(&self) -> Option<&ChatComponent> {
+        self.player_list_header.as_ref()
+    }
+
+    #[must_use]
+    pub fn get_player_list_footer(&self) -> Option<&ChatComponent> {
+        self.player_list_footer.as_ref()
+    }
+
+    /// Our own latency to the server in milliseconds averaged over the last few updates, as
+    /// measured by the server from our keep-alive responses
+    #[must_use]
+    pub fn get_own_ping(&self) -> Option<u32> {
+        self.latency.average()
+    }
+
+    /// Records a latency update from the server if it is for our own player
+    fn update_own_ping(&mut self, uuid: UUID4, ping: i32) {
+        if self.player.uuid == Some(uuid) && ping >= 0 {
+            self.latency.add_sample(ping as u32);
+        }
+    }
+
     /// Generates a sky colour based on a provided base colour and the current time of day on the
-    /// server
+    /// server. Dimensions without a day/night cycle always have the same sky colour
     #[must_use]
     pub fn get_sky_colour(&self, col: &[f64; 3]) -> DVec3 {
-        const LIGHTEST: i64 = 9_000;
-        let lerp = (((self.day_time - LIGHTEST) as f64 / 24_000.0) * PI * 2.0).cos() / 2.0 + 0.5;
+        if let Some(colour) = self.get_dimension_kind().fixed_sky_colour() {
+            return colour;
+        }
+
+        const LIGHTEST: f64 = 0.375;
+        let lerp = ((self.get_time_fraction() - LIGHTEST) * PI * 2.0).cos() / 2.0 + 0.5;
         let dark = DVec3::new(0.001, 0.002, 0.005);
         let light = DVec3::from(*col);
-        dark.lerp(light, lerp)
+        self.weather.apply_to_sky(dark.lerp(light, lerp))
+    }
+
+    /// Returns the near and far distances of the fog for the current dimension
+    #[must_use]
+    pub fn get_fog_range(&self, settings: &Settings) -> (f32, f32) {
+        let scale = self.get_dimension_kind().fog_scale();
+        (settings.fog_near * scale, settings.fog_far * scale)
+    }
+
+    /// If a chat message was sent by a player on the ignore list. Only player chat is filtered,
+    /// never system messages.
+    fn is_ignored(
+        &self,
+        chat: &PlayServerChatMessageSpec,
+        text: &str,
+        settings: &Settings,
+    ) -> bool {
+        if settings.ignored_players.is_empty() || !matches!(chat.position, ChatPosition::ChatBox) {
+            return false;
+        }
+
+        // Servers that relay chat from elsewhere leave the sender empty, so then look for the
+        // name in front of the message, e.g. `<[Team] Name>` or `[Rank] Name:`
+        let sender = match self.players.get(&chat.sender) {
+            Some(player) => player.name.clone(),
+            None => {
+                let text = chat::strip_codes(text);
+                let end = text.find(['>', ':']).unwrap_or(0);
+                text[..end].to_string()
+            }
+        };
+        chat::mentions(&sender, &settings.ignored_players)
+    }
+
+    /// If a chat message mentions the player's name or one of their keywords
+    fn is_mention(&self, text: &str, settings: &Settings) -> bool {
+        settings.highlight_mentions
+            && (chat::mentions(text, &[&settings.name])
+                || chat::mentions(text, &settings.mention_keywords))
+    }
+
+    /// Let the player know they've been mentioned with a sound, and by asking the OS to flash the
+    /// window if it isn't focused
+    fn ping_mention(&self, ctx: &Context, settings: &Settings) {
+        const MENTION_SOUND: &str = "minecraft:block.note_block.pling";
+
+        self.play_sound(
+            &SoundEvent {
+                name: String::from(MENTION_SOUND),
+                category: SoundCategory::Other,
+                position: *self.player.get_position(),
+                volume: 1.0,
+                pitch: 1.0,
+            },
+            settings,
+        );
+        ctx.wgpu_state
+            .window
+            .request_user_attention(Some(UserAttentionType::Informational));
+    }
+
+    /// Play a sound heard from the player's position, if the audio thread has been started
+    fn play_sound(&self, sound: &SoundEvent, settings: &Settings) {
+        if let Some(manager) = &self.sound {
+            manager.play_at(sound, *self.player.get_position(), settings);
+        }
     }
 
     /// Attempts to send a packet over the provided (possible) network channel
     pub fn send_packet(&self, packet: Vec<u8>) {
         if let Err(e) = self.network.send.send(NetworkCommand::SendPacket(packet)) {
             tracing::error!("Failed to communicate with network commander: {:?}", e);
-            panic!("Disconnected");
         }
     }
 
@@ -201,7 +570,6 @@ impl Server {
     pub fn send_command(&self, command: NetworkCommand) {
         if let Err(e) = self.network.send.send(command) {
             tracing::error!("Failed to communicate with network commander: {:?}", e);
-            panic!("Disconnected");
         }
     }
 
@@ -212,10 +580,47 @@ impl Server {
             InputState::ShowingInfo => true,
             InputState::InteractingInfo => false,
             InputState::ChatOpen => false,
+            InputState::Dead => false,
+            InputState::ContainerOpen => false,
+        }
+    }
+
+    /// Asks the server to respawn the player
+    pub fn respawn(&mut self) {
+        self.send_packet(encode(PacketType::PlayClientStatus(PlayClientStatusSpec {
+            action: ClientStatusAction::PerformRespawn,
+        })));
+        self.awaiting_respawn = true;
+    }
+
+    /// Asks the server for the player's statistics, opening a window to show them in if there
+    /// isn't one already
+    pub fn request_statistics(&mut self, windows: &mut WindowManager) {
+        self.send_packet(encode(PacketType::PlayClientStatus(PlayClientStatusSpec {
+            action: ClientStatusAction::RequestStats,
+        })));
+        if !self.statistics_window_open {
+            windows.push(statistics_window::new_statistics_window());
+            self.statistics_window_open = true;
         }
     }
 
-    pub fn render(&mut self, gui_ctx: &egui::Context, windows: &mut WindowManager) {
+    /// Send a chat message, or a command if it starts with a slash
+    pub fn send_chat_message(&mut self, message: String) {
+        self.chat_log.log_sent(&message);
+        self.send_packet(encode(PacketType::PlayClientChatMessage(
+            PlayClientChatMessageSpec { message },
+        )));
+    }
+
+    pub fn render(
+        &mut self,
+        gui_ctx: &egui::Context,
+        windows: &mut WindowManager,
+        settings: &mut Settings,
+    ) {
+        boss_bar_windows::render(gui_ctx, &self.boss_bars);
+        title_windows::render(gui_ctx, &self.titles);
         if self.input_state != InputState::ChatOpen {
             chat_windows::render_inactive(self, gui_ctx);
         }
@@ -224,37 +629,118 @@ impl Server {
             InputState::Playing => {}
             InputState::Paused => match pause_windows::render(gui_ctx, windows) {
                 pause_windows::PauseAction::Disconnect => self.disconnect(),
+                pause_windows::PauseAction::Statistics => self.request_statistics(windows),
                 pause_windows::PauseAction::Unpause => self.set_input_state(InputState::Playing),
                 pause_windows::PauseAction::Nothing => {}
             },
             InputState::ShowingInfo | InputState::InteractingInfo => {
-                info_windows::render(gui_ctx, self)
+                info_windows::render(gui_ctx, self, settings)
+            }
+            InputState::ChatOpen => {
+                match chat_windows::render_active(self, gui_ctx, settings.chat_timestamps) {
+                    chat_windows::ChatAction::OpenUrl(url) => {
+                        windows.push(open_url_window::new_open_url_window(url));
+                    }
+                    chat_windows::ChatAction::RunCommand(command) => {
+                        self.send_chat_message(command)
+                    }
+                    chat_windows::ChatAction::SuggestCommand(command) => {
+                        self.chat.set_current_message(command);
+                    }
+                    chat_windows::ChatAction::Paste(text) => self.chat.type_text(&text),
+                    chat_windows::ChatAction::Nothing => {}
+                }
+            }
+            InputState::Dead => match death_windows::render(
+                gui_ctx,
+                self.death_message.as_ref(),
+                self.awaiting_respawn,
+            ) {
+                death_windows::DeathAction::Respawn => self.respawn(),
+                death_windows::DeathAction::Disconnect => self.disconnect(),
+                death_windows::DeathAction::Nothing => {}
+            },
+            InputState::ContainerOpen => {
+                let action = match &self.container {
+                    Some(container) => container_windows::render(gui_ctx, container),
+                    None => container_windows::ContainerAction::Close,
+                };
+                if let container_windows::ContainerAction::Close = action {
+                    self.close_container();
+                }
             }
-            InputState::ChatOpen => chat_windows::render_active(self, gui_ctx),
         }
     }
 
-    pub fn update(&mut self, ctx: &Context, delta: f64, settings: &mut Settings) {
-        // self.world.generate_meshes(&ctx.dis, true);
+    pub fn update(&mut self, ctx: &mut Context, delta: f64, settings: &mut Settings) {
+        // Nothing left to do once we've disconnected, the network thread may be gone
+        if self.connection != ConnectionState::Connected {
+            return;
+        }
 
         // Update entities
         for ent in self.entities.values_mut() {
             ent.update(delta);
         }
-
-        // Handle input
+        self.titles.update(delta);
+        self.day_time.update(delta);
+        self.chat.set_max_history(settings.chat_history);
+        self.chat_log.set_enabled(settings.log_chat);
+        self.chat_log.update(delta);
+        self.player.update_status(delta);
+
+        // Handle input, movement is collected fresh each frame for the next physics tick
+        self.player.physics.movement = DVec3::ZERO;
+        self.player.physics.jumping = false;
+        self.player.physics.sneaking = false;
+        self.player.physics.sprinting = false;
+        self.steering = Steering::default();
         match self.input_state {
             InputState::Playing => self.handle_playing_state(ctx, delta, settings),
             InputState::Paused => self.handle_paused_state(ctx, delta, settings),
             InputState::ShowingInfo => self.handle_show_info_state(ctx, delta, settings),
             InputState::InteractingInfo => self.handle_interact_info_state(ctx, delta, settings),
             InputState::ChatOpen => self.handle_chat_open_state(ctx, delta, settings),
+            InputState::Dead => {}
+            InputState::ContainerOpen => self.handle_container_open_state(ctx),
+        }
+
+        self.ride_vehicle(delta);
+        self.send_entity_actions();
+
+        // Physics runs in fixed steps so movement doesn't depend on the framerate, riding
+        // leaves it to the vehicle
+        let steps = self.physics_timer.go_fixed();
+        let riding = self.passengers.get_vehicle(self.player.id).is_some();
+        if !self.player.physics.noclip && !riding && self.input_state != InputState::Dead {
+            let was_flying = self.player.physics.flying;
+            for _ in 0..steps {
+                self.player.tick_physics(&self.world);
+            }
+            // Landing stops flying
+            if was_flying && !self.player.physics.flying {
+                self.send_abilities();
+            }
         }
 
+        // The border can move in on the player as well as them walking into it
+        self.world.border_mut().update(delta);
+        let pos = *self.player.get_position();
+        let clamped = self.world.border().clamp(pos, physics::PLAYER_WIDTH);
+        if clamped != pos {
+            self.player.set_position(clamped);
+        }
+
+        self.update_target();
+        self.handle_entity_interaction(ctx);
+        self.handle_digging(ctx, delta);
+        self.handle_placing(ctx);
+        self.handle_empty_clicks(ctx, delta);
+
         // Handle messages from the NetworkManager
         loop {
             match self.network.recv.try_recv() {
-                Ok(comm) => self.handle_message(comm, ctx),
+                Ok(comm) => self.handle_message(comm, ctx, settings),
                 Err(e) => match e {
                     std::sync::mpsc::TryRecvError::Empty => break,
                     std::sync::mpsc::TryRecvError::Disconnected => {
@@ -271,41 +757,538 @@ impl Server {
             }
         }
 
-        // Send player position updates
-        if self.position_update_timer.go().is_some() && self.player.id != 0 {
+        // Teleports have to be confirmed before the server will accept any other movement
+        self.confirm_teleports();
+
+        // Send player position updates every tick, or sooner if they've moved a long way
+        let due = self.position_update_timer.go().is_some()
+            || self.movement.moved_far(*self.player.get_position());
+        if due && self.player.id != 0 {
+            self.send_movement();
+        }
+    }
+
+    /// Confirm each teleport the server has sent in the order they arrived, followed by where it
+    /// put us, like vanilla
+    fn confirm_teleports(&mut self) {
+        while let Some(teleport) = self.movement.confirm_teleport() {
+            self.send_packet(encode(PacketType::PlayTeleportConfirm(
+                PlayTeleportConfirmSpec {
+                    teleport_id: VarInt(teleport.id),
+                },
+            )));
             self.send_packet(encode(PacketType::PlayClientPlayerPositionAndRotation(
                 PlayClientPlayerPositionAndRotationSpec {
+                    on_ground: false,
                     feet_location: EntityLocation {
                         position: types::Vec3 {
-                            x: self.get_player().get_position().x,
-                            y: self.get_player().get_position().y,
-                            z: self.get_player().get_position().z,
+                            x: teleport.position.x,
+                            y: teleport.position.y,
+                            z: teleport.position.z,
                         },
                         rotation: types::EntityRotation {
-                            yaw: self.get_player().get_orientation().get_yaw() as f32,
-                            pitch: self.get_player().get_orientation().get_pitch() as f32,
+                            yaw: teleport.yaw,
+                            pitch: teleport.pitch,
                         },
                     },
-                    on_ground: true,
                 },
             )));
         }
     }
 
+    /// Carry the player along with the vehicle they're riding, steering it if they're in control
+    fn ride_vehicle(&mut self, delta: f64) {
+        let Some(vehicle_id) = self.passengers.get_vehicle(self.player.id) else {
+            return;
+        };
+        let controlling = self.passengers.is_controlling(self.player.id);
+        let (forward, right) = self.walk_directions();
+        let yaw = self.player.get_orientation().get_yaw();
+        let steering = self.steering;
+
+        // It may not have spawned yet
+        let Some(vehicle) = self.entities.get_mut(&vehicle_id) else {
+            return;
+        };
+        if controlling && vehicles::is_steerable(vehicle) {
+            let movement =
+                forward * f64::from(steering.forward) - right * f64::from(steering.sideways);
+            vehicle.pos += movement.normalize_or_zero() * VEHICLE_SPEED * delta;
+            vehicle.last_pos = vehicle.pos;
+            // The server leaves moving it to us, so it shouldn't fall under its own gravity
+            vehicle.vel = DVec3::ZERO;
+            vehicle.on_ground = true;
+            // Entity orientations are kept as fractions of a turn
+            vehicle.ori.set_yaw(yaw / 360.0);
+        }
+
+        let seat = vehicle.pos + DVec3::new(0.0, vehicles::seat_height(vehicle), 0.0);
+        self.player.set_position(seat);
+        self.player.physics.velocity = DVec3::ZERO;
+    }
+
+    /// While riding, the server hears which way the player is looking and how they're steering
+    /// rather than where they are, along with where the vehicle is if they're driving it
+    fn send_vehicle_movement(&mut self, vehicle_id: i32) {
+        let orientation = self.player.get_orientation();
+        let look = types::EntityRotation {
+            yaw: orientation.get_yaw() as f32,
+            pitch: orientation.get_pitch() as f32,
+        };
+        self.send_packet(encode(PacketType::PlayPlayerRotation(
+            PlayPlayerRotationSpec {
+                rotation: look,
+                on_ground: false,
+            },
+        )));
+
+        let steering = self.steering;
+        let mut flags = SteerVehicleFlags::default();
+        flags.set_jump(steering.jump);
+        flags.set_unmount(steering.unmount);
+        self.send_packet(encode(PacketType::PlaySteerVehicle(PlaySteerVehicleSpec {
+            sideways: steering.sideways,
+            forward: steering.forward,
+            flags,
+        })));
+
+        if !self.passengers.is_controlling(self.player.id) {
+            return;
+        }
+        let Some(vehicle) = self
+            .entities
+            .get(&vehicle_id)
+            .filter(|vehicle| vehicles::is_steerable(vehicle))
+        else {
+            return;
+        };
+        let (yaw, pitch) = vehicle.get_look_degrees();
+        let location = EntityLocation {
+            position: types::Vec3 {
+                x: vehicle.pos.x,
+                y: vehicle.pos.y,
+                z: vehicle.pos.z,
+            },
+            rotation: types::EntityRotation {
+                yaw: yaw as f32,
+                pitch: pitch as f32,
+            },
+        };
+        self.send_packet(encode(PacketType::PlayClientVehicleMove(
+            PlayClientVehicleMoveSpec { location },
+        )));
+    }
+
+    /// Send the smallest movement packet that covers how the player has moved and turned since
+    /// the last one
+    fn send_movement(&mut self) {
+        if let Some(vehicle) = self.passengers.get_vehicle(self.player.id) {
+            self.send_vehicle_movement(vehicle);
+            return;
+        }
+
+        let position = *self.player.get_position();
+        let rotation = types::EntityRotation {
+            yaw: self.player.get_orientation().get_yaw() as f32,
+            pitch: self.player.get_orientation().get_pitch() as f32,
+        };
+        let on_ground = self.player.physics.on_ground || self.player.physics.noclip;
+        let feet_location = types::Vec3 {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        };
+
+        let packet = match self
+            .movement
+            .update(position, rotation.yaw, rotation.pitch, on_ground)
+        {
+            MovementUpdate::Nothing => return,
+            MovementUpdate::Heartbeat => {
+                PacketType::PlayPlayerMovement(PlayPlayerMovementSpec { on_ground })
+            }
+            MovementUpdate::Position => PacketType::PlayPlayerPosition(PlayPlayerPositionSpec {
+                feet_position: feet_location,
+                on_ground,
+            }),
+            MovementUpdate::Rotation => PacketType::PlayPlayerRotation(PlayPlayerRotationSpec {
+                rotation,
+                on_ground,
+            }),
+            MovementUpdate::PositionAndRotation => PacketType::PlayClientPlayerPositionAndRotation(
+                PlayClientPlayerPositionAndRotationSpec {
+                    feet_location: EntityLocation {
+                        position: feet_location,
+                        rotation,
+                    },
+                    on_ground,
+                },
+            ),
+        };
+        self.send_packet(encode(packet));
+    }
+
     fn handle_playing_state(&mut self, ctx: &Context, delta: f64, settings: &mut Settings) {
-        if ctx.keyboard.pressed_this_frame(KeyCode::Escape) {
-            self.input_state = InputState::Paused;
-        } else if ctx.keyboard.pressed_this_frame(KeyCode::KeyT) {
+        if ctx.keyboard.pressed_this_frame(settings.key(Action::Pause)) {
+            self.pause();
+        } else if ctx
+            .keyboard
+            .pressed_this_frame(settings.key(Action::OpenChat))
+        {
             self.input_state = InputState::ChatOpen;
-        } else if ctx.keyboard.pressed_this_frame(KeyCode::Slash) {
+        } else if ctx
+            .keyboard
+            .pressed_this_frame(settings.key(Action::OpenCommand))
+        {
             self.input_state = InputState::ChatOpen;
             self.chat.set_current_message(String::from("/"));
-        } else if ctx.keyboard.pressed_this_frame(KeyCode::Tab) {
+        } else if ctx
+            .keyboard
+            .pressed_this_frame(settings.key(Action::PlayerList))
+        {
             self.input_state = InputState::ShowingInfo;
         }
 
+        if ctx
+            .keyboard
+            .pressed_this_frame(settings.key(Action::TogglePerspective))
+        {
+            self.third_person = !self.third_person;
+        }
+
+        // Spectators can't turn it off
+        if self.player.gamemode == GameMode::Creative
+            && ctx
+                .keyboard
+                .pressed_this_frame(settings.key(Action::ToggleNoclip))
+        {
+            let physics = &mut self.player.physics;
+            physics.noclip = !physics.noclip;
+            physics.velocity = DVec3::ZERO;
+            tracing::info!("Noclip {}", if physics.noclip { "on" } else { "off" });
+        }
+
+        self.handle_hotbar_input(ctx);
+        self.handle_flight_toggle(ctx, delta, settings);
         self.handle_keyboard_movement(ctx, delta, settings);
         self.handle_mouse_movement(ctx, delta, settings);
+        #[cfg(feature = "gamepad")]
+        self.handle_gamepad_input(ctx, delta, settings);
+    }
+
+    /// Find the block or entity the player is looking at, whichever is closer
+    fn update_target(&mut self) {
+        let eyes = self.player.get_eye_position();
+        let look = self.player.get_orientation().get_look_vector();
+        self.target = self.world.raycast(eyes, look, digging::REACH);
+        self.target_entity = raycast_entities(
+            self.entities.values().filter(|e| e.id != self.player.id),
+            eyes,
+            look,
+            digging::REACH,
+        );
+
+        if let (Some(block), Some(entity)) = (&self.target, &self.target_entity) {
+            if entity.distance < block.distance {
+                self.target = None;
+            } else {
+                self.target_entity = None;
+            }
+        }
+    }
+
+    /// Left click attacks the targeted entity and right click interacts with it
+    fn handle_entity_interaction(&mut self, ctx: &Context) {
+        if self.input_state != InputState::Playing {
+            return;
+        }
+        let Some(target) = self.target_entity else {
+            return;
+        };
+        let sneaking = self.player.physics.sneaking;
+
+        if ctx.mouse.pressed_this_frame(0) {
+            self.send_interact_entity(target.id, InteractKind::Attack, sneaking);
+            self.swing_arm();
+        } else if ctx.mouse.pressed_this_frame(2) {
+            // Like vanilla, say exactly where the entity was clicked first, for armour stands
+            let Some(entity) = self.entities.get(&target.id) else {
+                return;
+            };
+            let hit = target.point - entity.pos;
+            self.send_interact_entity(
+                target.id,
+                InteractKind::InteractAt(InteractAtSpec {
+                    target_position: types::Vec3 {
+                        x: hit.x as f32,
+                        y: hit.y as f32,
+                        z: hit.z as f32,
+                    },
+                    hand: Hand::MainHand,
+                }),
+                sneaking,
+            );
+            self.send_interact_entity(target.id, InteractKind::Interact(Hand::MainHand), sneaking);
+            self.swing_arm();
+        }
+    }
+
+    fn send_interact_entity(&self, id: i32, kind: InteractKind, sneaking: bool) {
+        self.send_packet(encode(PacketType::PlayInteractEntity(
+            PlayInteractEntitySpec {
+                entity_id: VarInt(id),
+                kind,
+                sneaking,
+            },
+        )));
+    }
+
+    /// Holding left click breaks the targeted block, stopping if the button is released or the
+    /// player looks away. Blocks break straight away in creative.
+    fn handle_digging(&mut self, ctx: &Context, delta: f64) {
+        let holding = self.input_state == InputState::Playing
+            && ctx.mouse.is_pressed(0)
+            && self.player.can_build();
+        let target = self.target.filter(|_| holding);
+
+        if let Some(digging) = &self.digging {
+            if target.map(|t| t.position) != Some(digging.position) {
+                let (position, face) = (digging.position, digging.face);
+                self.digging = None;
+                self.send_digging(PlayerDiggingStatus::Cancelled, position, face);
+            }
+        }
+
+        let Some(target) = target else {
+            return;
+        };
+
+        match &mut self.digging {
+            Some(digging) => {
+                let swing = digging.update(delta);
+                let finished = digging.is_finished();
+                let (position, face) = (digging.position, digging.face);
+
+                if swing {
+                    self.swing_arm();
+                }
+                if finished {
+                    self.digging = None;
+                    self.send_digging(PlayerDiggingStatus::Finished, position, face);
+                }
+            }
+            None => {
+                // Holding the button down in creative shouldn't clear out a block every frame
+                let creative = self.player.gamemode == GameMode::Creative
+                    || self.player.abilities.creative_mode;
+                if creative && !ctx.mouse.pressed_this_frame(0) {
+                    return;
+                }
+
+                self.send_digging(PlayerDiggingStatus::Started, target.position, target.face);
+                self.swing_arm();
+                if creative {
+                    self.send_digging(PlayerDiggingStatus::Finished, target.position, target.face);
+                } else {
+                    self.digging = Some(Digging::new(target.position, target.face));
+                }
+            }
+        }
+    }
+
+    fn send_digging(&self, status: PlayerDiggingStatus, position: WorldCoords, face: BlockFace) {
+        self.send_packet(encode(PacketType::PlayPlayerDigging(
+            PlayPlayerDiggingSpec {
+                status,
+                location: int_position(position),
+                face: digging_face(face),
+            },
+        )));
+    }
+
+    /// Right click uses the targeted block, which places the held block against it unless it's
+    /// a container or the player can't build. Placed blocks show up straight away rather than
+    /// waiting for the server.
+    fn handle_placing(&mut self, ctx: &Context) {
+        if self.input_state != InputState::Playing || !ctx.mouse.pressed_this_frame(2) {
+            return;
+        }
+        let Some(target) = self.target else {
+            return;
+        };
+
+        let cursor = (target.point - target.position.as_dvec3()).clamp(DVec3::ZERO, DVec3::ONE);
+        self.send_packet(encode(PacketType::PlayBlockPlacement(
+            PlayBlockPlacementSpec {
+                hand: Hand::MainHand,
+                location: int_position(target.position),
+                face: digging_face(target.face),
+                cursor_position: types::Vec3 {
+                    x: cursor.x as f32,
+                    y: cursor.y as f32,
+                    z: cursor.z as f32,
+                },
+                inside_block: false,
+            },
+        )));
+        self.swing_arm();
+
+        if let Some((position, block)) = self.predict_placement(&target) {
+            self.world.set_block(&position, block);
+            self.predicted_blocks.insert(position, block);
+        }
+    }
+
+    /// Where the held block would end up when used on `target` and what it would be, if it can
+    /// be placed there
+    fn predict_placement(&self, target: &RayHit) -> Option<(WorldCoords, BlockIndex)> {
+        if !self.player.can_build() {
+            return None;
+        }
+
+        // Sneaking places against containers rather than opening them
+        let clicked = blocks().get(&target.block)?;
+        if clicked.is_container() && !self.player.physics.sneaking {
+            return None;
+        }
+
+        let held = self.player.inventory.get_held_item()?;
+        let placed = item_block(held.item_id as u32)?;
+
+        let position = if clicked.is_replaceable() {
+            target.position
+        } else {
+            target.position + target.face.normal()
+        };
+        if !self.world.block_at(&position)?.is_replaceable() {
+            return None;
+        }
+
+        // Don't place blocks inside ourselves
+        let player = Aabb::player(*self.player.get_position());
+        if collision_boxes(placed)
+            .iter()
+            .any(|b| b.offset(position.as_dvec3()).intersects(&player))
+        {
+            return None;
+        }
+
+        Some((position, placed.id as BlockIndex))
+    }
+
+    /// Clicking at nothing still swings the arm or uses the held item so the server sees the
+    /// player as active. Holding left click keeps swinging a few times a second.
+    fn handle_empty_clicks(&mut self, ctx: &Context, delta: f64) {
+        self.swing_cooldown = (self.swing_cooldown - delta).max(0.0);
+        // Clicks are for the GUI rather than the world whenever the mouse is free
+        if !self.should_grab_mouse() || self.target.is_some() || self.target_entity.is_some() {
+            return;
+        }
+
+        if ctx.mouse.is_pressed(0) && self.swing_cooldown <= 0.0 {
+            self.swing_arm();
+            self.swing_cooldown = SWING_PERIOD;
+        }
+        if ctx.mouse.pressed_this_frame(2) {
+            self.use_item(Hand::MainHand);
+        }
+    }
+
+    pub fn use_item(&self, hand: Hand) {
+        self.send_packet(encode(PacketType::PlayUseItem(PlayUseItemSpec { hand })));
+    }
+
+    pub fn swing_arm(&self) {
+        self.send_packet(encode(PacketType::PlayClientAnimation(
+            PlayClientAnimationSpec {
+                hand: Hand::MainHand,
+            },
+        )));
+    }
+
+    /// The left stick moves, the right stick looks around and Start pauses
+    #[cfg(feature = "gamepad")]
+    fn handle_gamepad_input(&mut self, ctx: &Context, delta: f64, settings: &mut Settings) {
+        use wgpu_app::io::gamepad::Button;
+
+        /// Degrees per second the camera turns with the right stick held all the way over
+        const LOOK_SPEED: f64 = 180.0;
+
+        if ctx.gamepad.pressed_this_frame(Button::Start) {
+            self.pause();
+            return;
+        }
+
+        let (forward, right) = self.walk_directions();
+        let (strafe, ahead) = ctx.gamepad.left_stick();
+        let walk = forward * f64::from(ahead) + right * f64::from(strafe);
+
+        let mut rise = 0.0;
+        if ctx.gamepad.is_pressed(Button::South) {
+            rise += 1.0;
+        }
+        if ctx.gamepad.is_pressed(Button::East) {
+            rise -= 1.0;
+        }
+        self.move_player(walk, rise, delta);
+
+        if self.camera_target.is_none() {
+            let (yaw, pitch) = ctx.gamepad.right_stick();
+            let speed = LOOK_SPEED * settings.mouse_sensitivity * delta;
+            self.player
+                .get_orientation_mut()
+                .rotate(f64::from(yaw) * speed, -f64::from(pitch) * speed);
+        }
+    }
+
+    /// Number keys and the scroll wheel change the selected hotbar slot
+    fn handle_hotbar_input(&mut self, ctx: &Context) {
+        const HOTBAR_KEYS: [KeyCode; HOTBAR_SIZE] = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+
+        let current = self.player.inventory.get_selected_slot();
+        let mut selected = HOTBAR_KEYS
+            .iter()
+            .position(|key| ctx.keyboard.pressed_this_frame(*key));
+
+        // Scrolling down moves right along the hotbar one slot per notch. Touchpads scroll by
+        // fractions of a notch so the leftover is kept for the next frame.
+        self.hotbar_scroll += ctx.mouse.get_scroll_delta().1;
+        let notches = self.hotbar_scroll.trunc();
+        self.hotbar_scroll -= notches;
+        if selected.is_none() && notches != 0.0 {
+            let step = -(notches as i32);
+            selected = Some((current as i32 + step).rem_euclid(HOTBAR_SIZE as i32) as usize);
+        }
+
+        if let Some(slot) = selected {
+            if slot != current {
+                self.select_hotbar_slot(slot);
+            }
+        }
+    }
+
+    /// Select a hotbar slot (0-8) and let the server know
+    pub fn select_hotbar_slot(&mut self, slot: usize) {
+        self.player.inventory.set_selected_slot(slot);
+        self.send_packet(encode(PacketType::PlayClientHeldItemChange(
+            PlayClientHeldItemChangeSpec { slot: slot as i16 },
+        )));
+    }
+
+    fn handle_container_open_state(&mut self, ctx: &Context) {
+        if ctx.keyboard.pressed_this_frame(KeyCode::Escape) {
+            self.close_container();
+        }
     }
 
     fn handle_paused_state(&mut self, ctx: &Context, _delta: f64, _settings: &mut Settings) {
@@ -316,10 +1299,13 @@ impl Server {
 
     fn handle_show_info_state(&mut self, ctx: &Context, delta: f64, settings: &mut Settings) {
         if ctx.keyboard.pressed_this_frame(KeyCode::Escape) {
-            self.input_state = InputState::Paused;
+            self.pause();
         } else if ctx.mouse.pressed_this_frame(0) {
             self.input_state = InputState::InteractingInfo;
-        } else if ctx.keyboard.released_this_frame(KeyCode::Tab) {
+        } else if ctx
+            .keyboard
+            .released_this_frame(settings.key(Action::PlayerList))
+        {
             self.input_state = InputState::Playing;
         }
 
@@ -329,29 +1315,126 @@ impl Server {
 
     fn handle_interact_info_state(&mut self, ctx: &Context, delta: f64, settings: &mut Settings) {
         if ctx.keyboard.pressed_this_frame(KeyCode::Escape) {
-            self.input_state = InputState::Paused;
-        } else if ctx.keyboard.released_this_frame(KeyCode::Tab) {
+            self.pause();
+        } else if ctx
+            .keyboard
+            .released_this_frame(settings.key(Action::PlayerList))
+        {
             self.input_state = InputState::Playing;
         }
 
         self.handle_keyboard_movement(ctx, delta, settings);
     }
 
-    fn handle_chat_open_state(&mut self, ctx: &Context, _delta: f64, _settings: &mut Settings) {
+    fn handle_chat_open_state(&mut self, ctx: &mut Context, _delta: f64, _settings: &mut Settings) {
+        let typed = ctx.keyboard.take_text_input();
+        if !typed.is_empty() {
+            self.chat.type_text(&typed);
+        }
+        if ctx.keyboard.pressed_this_frame(KeyCode::Backspace) {
+            self.chat.backspace();
+        }
+
         if ctx.keyboard.pressed_this_frame(KeyCode::Escape) {
+            self.chat.reset_recall();
             self.input_state = InputState::Playing;
         } else if ctx.keyboard.pressed_this_frame(KeyCode::Enter) {
-            let text = self.chat.get_current_message_and_clear();
-            if !text.is_empty() {
-                self.send_packet(encode(PacketType::PlayClientChatMessage(
-                    PlayClientChatMessageSpec { message: text },
-                )));
+            // Long messages go as several, which the chat input warns about. Commands that are
+            // too long stay in the input to be shortened.
+            match chat::split_message(self.chat.get_current_message()) {
+                Ok(parts) => {
+                    let text = self.chat.get_current_message_and_clear();
+                    for part in parts {
+                        self.send_chat_message(part);
+                    }
+                    self.chat.push_history(text);
+                    self.input_state = InputState::Playing;
+                }
+                Err(e) => tracing::warn!("Not sending command: {e}"),
             }
-            self.input_state = InputState::Playing;
+        } else if ctx.keyboard.pressed_this_frame(KeyCode::ArrowUp) {
+            self.chat.history_prev();
+        } else if ctx.keyboard.pressed_this_frame(KeyCode::ArrowDown) {
+            self.chat.history_next();
+        } else if ctx.keyboard.pressed_this_frame(KeyCode::Tab) {
+            let input = self.chat.get_current_message();
+            if let Some(completion) = self.commands.check(input).completion {
+                let completed = format!("{input}{completion}");
+                self.chat.set_current_message(completed);
+            }
+        }
+    }
+
+    /// Double tapping jump starts or stops flying, if the server allows it
+    fn handle_flight_toggle(&mut self, ctx: &Context, delta: f64, settings: &Settings) {
+        /// Seconds between presses for them to count as a double tap, 7 ticks like vanilla
+        const DOUBLE_TAP_TIME: f64 = 0.35;
+
+        self.jump_tap_timer = (self.jump_tap_timer - delta).max(0.0);
+        if !self.player.abilities.allow_flying
+            || self.player.physics.noclip
+            || !ctx.keyboard.pressed_this_frame(settings.key(Action::Jump))
+        {
+            return;
+        }
+
+        if self.jump_tap_timer > 0.0 {
+            self.jump_tap_timer = 0.0;
+            let physics = &mut self.player.physics;
+            physics.flying = !physics.flying;
+            physics.velocity = DVec3::ZERO;
+            self.send_abilities();
+        } else {
+            self.jump_tap_timer = DOUBLE_TAP_TIME;
         }
     }
 
+    /// Let the server know when the player starts or stops sneaking or sprinting, once per change
+    fn send_entity_actions(&mut self) {
+        let physics = &self.player.physics;
+        let (sneaking, sprinting) = (physics.sneaking, physics.sprinting);
+
+        if sneaking != self.sent_sneaking {
+            self.sent_sneaking = sneaking;
+            self.send_entity_action(if sneaking {
+                EntityActionKind::StartSneaking
+            } else {
+                EntityActionKind::StopSneaking
+            });
+        }
+        if sprinting != self.sent_sprinting {
+            self.sent_sprinting = sprinting;
+            self.send_entity_action(if sprinting {
+                EntityActionKind::StartSprinting
+            } else {
+                EntityActionKind::StopSprinting
+            });
+        }
+    }
+
+    fn send_entity_action(&self, action: EntityActionKind) {
+        self.send_packet(encode(PacketType::PlayEntityAction(PlayEntityActionSpec {
+            entity_id: VarInt(self.player.id),
+            action,
+            // Misspelt in mcproto-rs
+            jump_boot: VarInt(0),
+        })));
+    }
+
+    /// Let the server know if we're flying
+    fn send_abilities(&self) {
+        let mut flags = ClientPlayerAbilities::default();
+        flags.set_flying(self.player.physics.flying);
+        self.send_packet(encode(PacketType::PlayClientPlayerAbilities(
+            PlayClientPlayerAbilitiesSpec { flags },
+        )));
+    }
+
     pub fn handle_mouse_movement(&mut self, ctx: &Context, _delta: f64, settings: &mut Settings) {
+        if self.camera_target.is_some() {
+            return;
+        }
+
         let off = ctx.mouse.get_delta();
         self.player.get_orientation_mut().rotate(
             off.0 as f64 * 0.05 * settings.mouse_sensitivity,
@@ -359,79 +1442,129 @@ impl Server {
         );
     }
 
-    pub fn handle_keyboard_movement(
-        &mut self,
-        ctx: &Context,
-        delta: f64,
-        _settings: &mut Settings,
-    ) {
-        let vel = 14.0 * delta;
-
-        if ctx.keyboard.is_pressed(KeyCode::KeyW) {
-            let mut dir = self.player.get_orientation().get_look_vector();
-            dir.y = 0.0;
-            dir = dir.normalize();
-            dir *= vel;
-            self.player.get_position_mut().add_assign(dir);
-        }
-
-        if ctx.keyboard.is_pressed(KeyCode::KeyS) {
-            let mut dir = self.player.get_orientation().get_look_vector();
-            dir.y = 0.0;
-            dir = dir.normalize();
-            dir *= -vel;
-            self.player.get_position_mut().add_assign(dir);
-        }
-
-        if ctx.keyboard.is_pressed(KeyCode::KeyA) {
-            let mut dir = self.player.get_orientation().get_look_vector();
-            dir.y = 0.0;
-            dir = dir.normalize();
-            dir *= -vel;
-            dir.y = dir.x; // Just using this value as temp to swap x and z
-            dir.x = -dir.z;
-            dir.z = dir.y;
-            dir.y = 0.0;
-            self.player.get_position_mut().add_assign(dir);
-        }
-
-        if ctx.keyboard.is_pressed(KeyCode::KeyD) {
-            let mut dir = self.player.get_orientation().get_look_vector();
-            dir.y = 0.0;
-            dir = dir.normalize();
-            dir *= vel;
-            dir.y = dir.x; // Just using this value as temp to swap x and z
-            dir.x = -dir.z;
-            dir.z = dir.y;
-            dir.y = 0.0;
-            self.player.get_position_mut().add_assign(dir);
-        }
-
-        if ctx.keyboard.is_pressed(KeyCode::Space) {
-            self.player
-                .get_position_mut()
-                .add_assign(DVec3::new(0.0, vel, 0.0));
+    pub fn handle_keyboard_movement(&mut self, ctx: &Context, delta: f64, settings: &mut Settings) {
+        let (forward, right) = self.walk_directions();
+        let key = |action| ctx.keyboard.is_pressed(settings.key(action));
+
+        // Riding steers the vehicle instead, and sneaking gets off it
+        if self.passengers.get_vehicle(self.player.id).is_some() {
+            let axis = |positive, negative| {
+                f32::from(u8::from(key(positive))) - f32::from(u8::from(key(negative)))
+            };
+            self.steering = Steering {
+                sideways: axis(Action::Left, Action::Right),
+                forward: axis(Action::Forward, Action::Back),
+                jump: key(Action::Jump),
+                unmount: key(Action::Sneak),
+            };
+            return;
+        }
+
+        let mut walk = DVec3::ZERO;
+        if key(Action::Forward) {
+            walk += forward;
+        }
+        if key(Action::Back) {
+            walk -= forward;
         }
+        if key(Action::Left) {
+            walk -= right;
+        }
+        if key(Action::Right) {
+            walk += right;
+        }
+
+        // Toggled sneak and sprint flip on each press rather than needing the key held
+        let pressed = |action| ctx.keyboard.pressed_this_frame(settings.key(action));
+        if settings.toggle_sneak && pressed(Action::Sneak) {
+            self.sneak_toggled = !self.sneak_toggled;
+        }
+        if settings.toggle_sprint && pressed(Action::Sprint) {
+            self.sprint_toggled = !self.sprint_toggled;
+        }
+        let sneaking = if settings.toggle_sneak {
+            self.sneak_toggled
+        } else {
+            key(Action::Sneak)
+        };
+        let sprinting = if settings.toggle_sprint {
+            self.sprint_toggled
+        } else {
+            key(Action::Sprint)
+        };
+
+        let mut rise = 0.0;
+        if key(Action::Jump) {
+            rise += 1.0;
+        }
+        if sneaking {
+            rise -= 1.0;
+        }
+        // Only forwards, like vanilla
+        self.player.physics.sprinting = sprinting && key(Action::Forward);
 
-        if ctx.keyboard.is_pressed(KeyCode::ShiftLeft) {
+        self.move_player(walk, rise, delta);
+    }
+
+    /// Unit vectors pointing forwards and to the right of where the player is looking, along the
+    /// ground. Only the yaw matters, so looking straight up or down still walks the right way.
+    fn walk_directions(&self) -> (DVec3, DVec3) {
+        movement::walk_directions(self.player.get_orientation().get_yaw())
+    }
+
+    /// Move the player along the ground by `walk`, and up or down if `rise` is positive or
+    /// negative. With noclip the player flies straight there, otherwise this is the input to the
+    /// next physics tick.
+    fn move_player(&mut self, walk: DVec3, rise: f64, delta: f64) {
+        // The player stays put while the camera belongs to another entity, and goes wherever
+        // their vehicle does
+        if self.camera_target.is_some() || self.passengers.get_vehicle(self.player.id).is_some() {
+            return;
+        }
+
+        if self.player.physics.noclip {
+            let vel = 14.0 * delta;
             self.player
                 .get_position_mut()
-                .add_assign(DVec3::new(0.0, -vel, 0.0));
+                .add_assign((walk + DVec3::new(0.0, rise, 0.0)) * vel);
+        } else {
+            let physics = &mut self.player.physics;
+            physics.movement += walk;
+            physics.jumping |= rise > 0.0;
+            physics.sneaking |= rise < 0.0;
+        }
+    }
+
+    /// Close the open container, if there is one, and let the server know
+    fn close_container(&mut self) {
+        if let Some(container) = self.container.take() {
+            self.send_packet(encode(PacketType::PlayClientCloseWindow(
+                PlayClientCloseWindowSpec {
+                    window_id: container.get_id(),
+                },
+            )));
+        }
+        if self.input_state == InputState::ContainerOpen {
+            self.input_state = InputState::Playing;
         }
     }
 
     pub fn disconnect(&mut self) {
         tracing::info!("Disconnecting from server.");
-        self.network
-            .send
-            .send(NetworkCommand::Disconnect)
-            .expect("Failed to send message to network thread.");
+        // The network thread may have already closed if the server dropped us first
+        if self.network.send.send(NetworkCommand::Disconnect).is_err() {
+            tracing::debug!("Network thread already closed.");
+        }
         self.connection = ConnectionState::ClientDisconnected;
+        self.boss_bars.clear();
+        self.chat_log.flush();
+        // Stop the audio thread now rather than whenever the server happens to be dropped
+        self.sound = None;
     }
 
     /// Handles a message from the `NetworkManager`
     #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
-    fn handle_message(&mut self, comm: NetworkCommand, _ctx: &Context) {
+    fn handle_message(&mut self, comm: NetworkCommand, ctx: &Context, settings: &Settings) {
         #[allow(clippy::enum_glob_use)]
         use NetworkCommand::*;
 
@@ -447,13 +1580,97 @@ impl Server {
 
                     PacketType::PlayTimeUpdate(pack) => {
                         self.world_time = pack.world_age;
-                        self.day_time = pack.time_of_day;
+                        self.day_time.set(pack.time_of_day);
                     }
 
                     PacketType::PlayUpdatehealth(pack) => {
-                        self.player.health = pack.health;
+                        self.player.set_health(pack.health);
                         self.player.food = pack.food.0;
                         self.player.saturation = pack.saturation;
+
+                        if self.player.get_health() <= 0.0 && self.input_state != InputState::Dead {
+                            tracing::info!("Player died.");
+                            self.awaiting_respawn = false;
+                            self.input_state = InputState::Dead;
+                        }
+                    }
+
+                    PacketType::PlaySetExperience(pack) => {
+                        self.player.experience_bar = pack.experience_bar;
+                        self.player.level = pack.level.0;
+                        self.player.total_experience = pack.total_experience.0;
+                    }
+
+                    PacketType::PlayServerPlayerAbilities(pack) => {
+                        let abilities = &mut self.player.abilities;
+                        abilities.invulnerable = pack.flags.is_invulnerable();
+                        abilities.allow_flying = pack.flags.is_flight_allowed();
+                        abilities.creative_mode = pack.flags.is_instant_break();
+                        abilities.fov_modifier = pack.field_of_view_modifier;
+
+                        let physics = &mut self.player.physics;
+                        physics.flying = pack.flags.is_flying();
+                        physics.fly_speed = f64::from(pack.flying_speed);
+                    }
+
+                    PacketType::PlayChangeGameState(pack) => match pack.reason {
+                        GameChangeReason::ChangeGameMode(gamemode) => {
+                            tracing::info!("Gamemode changed to {:?}", gamemode);
+                            self.player.set_gamemode(gamemode);
+                        }
+                        GameChangeReason::BeginRaining => self.weather.raining = true,
+                        GameChangeReason::EndRaining => self.weather.raining = false,
+                        GameChangeReason::RainLevelChange(level) => self.weather.rain_level = level,
+                        GameChangeReason::ThunderLevelChange(level) => {
+                            self.weather.thunder_level = level;
+                        }
+                        // There are no credits to show, so skip straight to respawning
+                        GameChangeReason::WinGame(_) => {
+                            tracing::info!("Won the game, respawning");
+                            self.respawn();
+                        }
+                        reason => tracing::debug!("Unhandled game state change: {:?}", reason),
+                    },
+
+                    PacketType::PlayWorldBorder(pack) => {
+                        let border = self.world.border_mut();
+                        match pack.action {
+                            WorldBorderAction::SetSize(spec) => border.set_diameter(spec.diameter),
+                            WorldBorderAction::LerpSize(spec) => border.lerp_to(
+                                spec.old_diameter,
+                                spec.new_diameter,
+                                spec.speed.0 as f64 / 1000.0,
+                            ),
+                            WorldBorderAction::SetCenter(spec) => border.set_centre(spec.x, spec.z),
+                            WorldBorderAction::Initialize(spec) => {
+                                border.set_centre(spec.position.x, spec.position.z);
+                                border.lerp_to(
+                                    spec.old_diameter,
+                                    spec.new_diameter,
+                                    spec.speed.0 as f64 / 1000.0,
+                                );
+                                border.set_warning_time(f64::from(spec.warning_time.0));
+                                border.set_warning_blocks(f64::from(spec.warning_blocks.0));
+                            }
+                            WorldBorderAction::SetWarningTime(spec) => {
+                                border.set_warning_time(f64::from(spec.warning_time.0));
+                            }
+                            WorldBorderAction::SetWarningBlocks(spec) => {
+                                border.set_warning_blocks(f64::from(spec.warning_blocks.0));
+                            }
+                        }
+                    }
+
+                    PacketType::PlayCombatEvent(pack) => {
+                        if let CombatEvent::EntityDead(dead) = pack.event {
+                            if dead.player_id.0 == self.player.id {
+                                tracing::info!(
+                                    "Died: {}",
+                                    dead.message.to_traditional().unwrap_or_default()
+                                );
+                                self.death_message = Some(dead.message);
+                            }
+                        }
                     }
 
                     PacketType::PlayDisconnect(pack) => {
@@ -464,8 +1681,9 @@ impl Server {
                         );
                     }
 
-                    PacketType::LoginSuccess(_) => {
+                    PacketType::LoginSuccess(pack) => {
                         tracing::info!("Successfully Logged in!");
+                        self.player.uuid = Some(pack.uuid);
                     }
 
                     PacketType::LoginDisconnect(pack) => {
@@ -479,6 +1697,11 @@ impl Server {
 
                     PacketType::PlayJoinGame(id) => {
                         self.join_game(id.entity_id);
+                        self.player.reset_status();
+                        self.player.set_gamemode(id.gamemode);
+                        self.sound = Some(SoundManager::new());
+                        self.dimension = id.world_name;
+                        self.world.set_view_distance(id.view_distance.0);
                         self.send_packet(encode(PacketType::PlayClientSettings(
                             PlayClientSettingsSpec {
                                 locale: self.player.locale.clone(),
@@ -496,14 +1719,139 @@ impl Server {
                         )));
                     }
 
+                    PacketType::PlayRespawn(pack) => {
+                        tracing::info!("Respawning in {}", pack.world_name);
+                        let changed_dimension = pack.world_name != self.dimension;
+
+                        // Everything we know about the old world is stale now
+                        let view_distance = self.world.get_view_distance();
+                        self.world = World::new();
+                        self.world.set_view_distance(view_distance);
+                        self.entities.clear();
+                        self.passengers.clear();
+                        self.player.reset_status();
+                        self.player.set_gamemode(pack.gamemode);
+                        self.digging = None;
+                        self.predicted_blocks.clear();
+                        self.container = None;
+                        if self.input_state == InputState::ContainerOpen {
+                            self.input_state = InputState::Playing;
+                        }
+                        self.camera_target = None;
+                        self.reset_toggles();
+                        // The server sends the weather again for the new world
+                        self.weather = Weather::new();
+                        self.dimension = pack.world_name;
+                        if changed_dimension {
+                            self.boss_bars.clear();
+                        }
+
+                        // Some servers wait for the client to confirm before finishing the
+                        // transfer, unless we already asked to respawn from the death screen
+                        if changed_dimension && !self.awaiting_respawn {
+                            self.send_packet(encode(PacketType::PlayClientStatus(
+                                PlayClientStatusSpec {
+                                    action: ClientStatusAction::PerformRespawn,
+                                },
+                            )));
+                        }
+                    }
+
+                    PacketType::PlayTitle(pack) => match pack.action {
+                        TitleActionSpec::SetTitle(text) => {
+                            self.titles.set_title(text);
+                        }
+                        TitleActionSpec::SetSubtitle(text) => {
+                            self.titles.set_subtitle(text);
+                        }
+                        TitleActionSpec::SetActionBar(text) => {
+                            self.titles.set_action_bar(text);
+                        }
+                        TitleActionSpec::SetTimesAndDisplay(times) => {
+                            self.titles
+                                .set_times(times.fade_in, times.stay, times.fade_out);
+                        }
+                        TitleActionSpec::Hide => self.titles.hide(),
+                        TitleActionSpec::Reset => self.titles.reset(),
+                    },
+
+                    // Updates for bars we don't know about are ignored
+                    PacketType::PlayBossBar(pack) => match pack.action {
+                        BossBarAction::Add(add) => {
+                            self.boss_bar_count += 1;
+                            self.boss_bars.insert(
+                                pack.uuid,
+                                BossBar {
+                                    title: add.title,
+                                    health: add.health,
+                                    colour: add.color,
+                                    division: add.division,
+                                    order: self.boss_bar_count,
+                                },
+                            );
+                        }
+                        BossBarAction::Remove => {
+                            self.boss_bars.remove(&pack.uuid);
+                        }
+                        BossBarAction::UpdateHealth(health) => {
+                            if let Some(bar) = self.boss_bars.get_mut(&pack.uuid) {
+                                bar.health = health.health;
+                            }
+                        }
+                        BossBarAction::UpdateTitle(update) => {
+                            if let Some(bar) = self.boss_bars.get_mut(&pack.uuid) {
+                                // Updated titles are left as raw JSON by mcproto-rs
+                                match serde_json::from_str::<ChatComponent>(&update.title) {
+                                    Result::Ok(title) => bar.title = title,
+                                    Err(e) => tracing::warn!("Invalid boss bar title: {}", e),
+                                }
+                            }
+                        }
+                        BossBarAction::UpdateStyle(style) => {
+                            if let Some(bar) = self.boss_bars.get_mut(&pack.uuid) {
+                                bar.colour = style.color;
+                                bar.division = style.dividers;
+                            }
+                        }
+                        BossBarAction::UpdateFlags(_) => {}
+                    },
+
+                    PacketType::PlayNamedSoundEffect(pack) => {
+                        self.play_sound(
+                            &SoundEvent {
+                                name: pack.sound_name,
+                                category: sound_category(&pack.sound_category),
+                                position: sound_position(&pack.position),
+                                volume: pack.volume,
+                                pitch: pack.pitch,
+                            },
+                            settings,
+                        );
+                    }
+
+                    PacketType::PlaySoundEffect(pack) => {
+                        match sound_events().get(&(pack.sound_id.0 as u32)) {
+                            Some(name) => self.play_sound(
+                                &SoundEvent {
+                                    name: name.clone(),
+                                    category: sound_category(&pack.sound_category),
+                                    position: sound_position(&pack.position),
+                                    volume: pack.volume,
+                                    pitch: pack.pitch,
+                                },
+                                settings,
+                            ),
+                            None => tracing::debug!("Unknown sound id {}", pack.sound_id.0),
+                        }
+                    }
+
                     PacketType::PlaySpawnPlayer(pack) => {
                         self.entities.insert(
                             pack.entity_id.0,
                             Entity::new_with_values(
                                 pack.entity_id.0,
                                 pack.uuid,
-                                // PLAYER_INDEX as u32,
-                                0,
+                                PLAYER_INDEX as u32,
                                 0,
                                 pack.location.position.x,
                                 pack.location.position.y,
@@ -563,6 +1911,40 @@ impl Server {
                     PacketType::PlayDestroyEntities(pack) => {
                         for eid in pack.entity_ids.iter() {
                             self.entities.remove(&eid.0);
+                            self.passengers.remove_entity(eid.0);
+                        }
+                    }
+
+                    PacketType::PlaySetPassengers(pack) => {
+                        let was_riding = self.passengers.get_vehicle(self.player.id);
+                        self.passengers.set_passengers(
+                            pack.entity_id.0,
+                            pack.passenger_entitiy_ids
+                                .iter()
+                                .map(|passenger| passenger.0)
+                                .collect(),
+                        );
+                        let riding = self.passengers.get_vehicle(self.player.id);
+                        if riding != was_riding {
+                            match riding {
+                                Some(vehicle) => tracing::info!("Riding entity {}", vehicle),
+                                None => tracing::info!("Dismounted"),
+                            }
+                            self.player.physics.velocity = DVec3::ZERO;
+                            self.player.physics.flying = false;
+                        }
+                    }
+
+                    // The server overruling where we've steered our vehicle
+                    PacketType::PlayServerVehicleMove(pack) => {
+                        let vehicle = self.passengers.get_vehicle(self.player.id);
+                        if let Some(vehicle) = vehicle.and_then(|id| self.entities.get_mut(&id)) {
+                            let position = &pack.location.position;
+                            vehicle.pos = DVec3::new(position.x, position.y, position.z);
+                            vehicle.last_pos = vehicle.pos;
+                            vehicle
+                                .ori
+                                .set_yaw(f64::from(pack.location.rotation.yaw) / 360.0);
                         }
                     }
 
@@ -644,51 +2026,187 @@ impl Server {
                     PacketType::PlayServerPlayerPositionAndLook(pack) => {
                         tracing::debug!("Player position updated!");
 
-                        self.player.set_position(DVec3::new(
-                            pack.location.position.x,
-                            pack.location.position.y,
-                            pack.location.position.z,
-                        ));
-                        self.player.get_orientation_mut().set(
-                            f64::from(pack.location.rotation.yaw),
-                            f64::from(pack.location.rotation.pitch),
+                        let orientation = self.player.get_orientation();
+                        let teleport = PendingTeleport::resolve(
+                            pack.teleport_id.0,
+                            &pack.location,
+                            pack.flags,
+                            *self.player.get_position(),
+                            orientation.get_yaw(),
+                            orientation.get_pitch(),
                         );
 
-                        self.send_packet(encode(PacketType::PlayTeleportConfirm(
-                            PlayTeleportConfirmSpec {
-                                teleport_id: pack.teleport_id,
-                            },
-                        )));
+                        self.player.set_position(teleport.position);
+                        self.player.physics.velocity = DVec3::ZERO;
+                        self.player
+                            .get_orientation_mut()
+                            .set(f64::from(teleport.yaw), f64::from(teleport.pitch));
 
-                        let x = self.player.get_position().x;
-                        let y = self.player.get_position().y;
-                        let z = self.player.get_position().z;
-                        self.send_packet(encode(PacketType::PlayClientPlayerPositionAndRotation(
-                            PlayClientPlayerPositionAndRotationSpec {
-                                on_ground: (true),
-                                feet_location: EntityLocation {
-                                    position: types::Vec3 { x, y, z },
-                                    rotation: pack.location.rotation,
-                                },
-                            },
-                        )));
+                        // Confirmed along with any others once this batch of packets is handled
+                        self.movement.queue_teleport(teleport);
+
+                        // The server teleports us to the spawn point once we've respawned
+                        if self.awaiting_respawn {
+                            self.awaiting_respawn = false;
+                            if self.input_state == InputState::Dead {
+                                self.input_state = InputState::Playing;
+                            }
+                            self.death_message = None;
+                        }
                     }
 
                     PacketType::PlayServerChatMessage(chat) => {
-                        self.chat.add_message(chat, self.world_time);
+                        let text = chat.message.to_traditional().unwrap_or_default();
+                        if self.is_ignored(&chat, &text, settings) {
+                            self.chat.suppress_message();
+                            return;
+                        }
+                        self.chat_log.log_received(&text);
+
+                        // Our own messages come back to us, they shouldn't count as mentions
+                        let own = self.player.uuid == Some(chat.sender);
+                        let mention = !own && self.is_mention(&text, settings);
+                        if mention && settings.mention_ping {
+                            self.ping_mention(ctx, settings);
+                        }
+                        self.chat.add_message(chat, self.world_time, mention);
                     }
 
                     PacketType::PlayChunkData(cd) => {
-                        self.world.insert_chunk(Chunk::new(&cd.data));
+                        if let Err(e) = self.world.load_chunk(&cd.data) {
+                            tracing::error!(
+                                "Failed to load chunk {} {}: {e}",
+                                cd.data.position.x,
+                                cd.data.position.z
+                            );
+                        }
                     }
 
                     PacketType::PlayUnloadChunk(pack) => {
                         self.world
-                            .get_chunks_mut()
-                            .remove(&IVec2::new(pack.position.x, pack.position.z));
+                            .unload_chunk(&IVec2::new(pack.position.x, pack.position.z));
+                    }
+
+                    PacketType::PlayUpdateLight(pack) => self.world.handle_update_light(&pack),
+
+                    PacketType::PlayOpenWindow(pack) => match u8::try_from(pack.id.0) {
+                        Result::Ok(id) => {
+                            // Opening a window replaces whichever one was open before. Some of
+                            // mcproto-rs's window type names are off for 1.16, but the ids are
+                            // the ones the server sent
+                            self.container = Some(Container::new(id, pack.kind.id().0, pack.title));
+                            if self.input_state != InputState::Dead {
+                                self.input_state = InputState::ContainerOpen;
+                            }
+                        }
+                        Err(_) => {
+                            tracing::warn!("Server opened invalid window {}", pack.id.0)
+                        }
+                    },
+
+                    PacketType::PlayServerCloseWindow(pack) => {
+                        if self
+                            .container
+                            .as_ref()
+                            .is_some_and(|container| container.get_id() == pack.window_id)
+                        {
+                            self.container = None;
+                            if self.input_state == InputState::ContainerOpen {
+                                self.input_state = InputState::Playing;
+                            }
+                        }
+                    }
+
+                    PacketType::PlayWindowItems(pack) => {
+                        // Window 0 is always the player's inventory
+                        if pack.window_id == 0 {
+                            self.player.inventory.set_all(&pack.slots);
+                        } else if let Some(container) = self
+                            .container
+                            .as_mut()
+                            .filter(|container| container.get_id() == pack.window_id)
+                        {
+                            container.set_all(&pack.slots);
+                        }
+                    }
+
+                    PacketType::PlayCamera(pack) => {
+                        let id = pack.camera_id.0;
+                        self.camera_target = (id != self.player.id).then_some(id);
+                    }
+
+                    PacketType::PlayServerWindowConfirmation(pack) => {
+                        // The server rejected a click, and won't accept any more until we
+                        // acknowledge it
+                        if !pack.accepted {
+                            self.send_packet(encode(PacketType::PlayClientWindowConfirmation(
+                                PlayClientWindowConfirmationSpec {
+                                    window_id: pack.window_id as i8,
+                                    action_num: pack.action_number,
+                                    accepted: true,
+                                },
+                            )));
+                        }
+                    }
+
+                    PacketType::PlaySetSlot(pack) => {
+                        let item = ItemStack::from_slot(&pack.slot_data);
+                        // The window id is signed on the wire, and 0.2.0 names the slot `slow`
+                        match (pack.window_id as i8, pack.slow) {
+                            (0, slot) if slot >= 0 => {
+                                self.player.inventory.set_slot(slot as usize, item);
+                            }
+                            // Window -2 uses the inventory's internal numbering, where the
+                            // hotbar comes first
+                            (-2, slot) if slot >= 0 => {
+                                let slot = slot as usize;
+                                let slot = if slot < HOTBAR_SIZE {
+                                    HOTBAR_START + slot
+                                } else {
+                                    slot
+                                };
+                                self.player.inventory.set_slot(slot, item);
+                            }
+                            (window, slot) if slot >= 0 => {
+                                if let Some(container) =
+                                    self.container.as_mut().filter(|container| {
+                                        i32::from(container.get_id()) == i32::from(window)
+                                    })
+                                {
+                                    container.set_slot(slot as usize, item);
+                                }
+                            }
+                            // Window -1 slot -1 is the item carried by the cursor
+                            (-1, -1) => self.player.inventory.set_cursor(item),
+                            _ => {}
+                        }
+                    }
+
+                    PacketType::PlayServerHeldItemChange(pack) => {
+                        if pack.slot >= 0 {
+                            self.player.inventory.set_selected_slot(pack.slot as usize);
+                        }
+                    }
+
+                    PacketType::PlayUpdateViewPosition(pack) => {
+                        self.world
+                            .set_view_center(IVec2::new(pack.chunk.x.0, pack.chunk.z.0));
+                    }
+
+                    PacketType::PlayUpdateViewDistance(pack) => {
+                        self.world.set_view_distance(pack.view_distance.0);
                     }
 
                     PacketType::PlayBlockChange(pack) => {
+                        // The server has the final say on blocks we predicted, so the world is
+                        // rolled back to its state if they don't match
+                        let location = &pack.location;
+                        let position = WorldCoords::new(location.x, location.y.into(), location.z);
+                        if let Some(predicted) = self.predicted_blocks.remove(&position) {
+                            if i32::from(predicted) != pack.block_id.0 {
+                                tracing::debug!("Server rejected placing a block at {position}");
+                            }
+                        }
                         self.world.handle_block_change(pack);
                     }
 
@@ -701,6 +2219,7 @@ impl Server {
                         match pack.actions {
                             PlayerInfoActionList::Add(players) => {
                                 for player in players.iter() {
+                                    self.update_own_ping(player.uuid, player.action.ping_ms.0);
                                     self.players.insert(
                                         player.uuid,
                                         RemotePlayer {
@@ -729,6 +2248,7 @@ impl Server {
                             PlayerInfoActionList::UpdateLatency(players) => {
                                 let players: Vec<PlayerInfoAction<VarInt>> = From::from(players);
                                 for player in players {
+                                    self.update_own_ping(player.uuid, player.action.0);
                                     if let Some(p) = self.players.get_mut(&player.uuid) {
                                         p.ping = player.action.into();
                                     }
@@ -753,11 +2273,57 @@ impl Server {
                         }
                     }
 
+                    PacketType::PlayTeams(pack) => self.teams.handle_teams(pack),
+
+                    PacketType::PlayDeclareCommands(pack) => {
+                        self.commands = CommandTree::from_packet(&pack);
+                    }
+
+                    PacketType::PlayStatistics(pack) => {
+                        self.statistics = Some(Statistics::from_packet(&pack));
+                    }
+
+                    PacketType::PlayerPlayerListHeaderAndFooter(pack) => {
+                        self.player_list_header = Some(pack.header);
+                        self.player_list_footer = Some(pack.footer);
+                    }
+
+                    PacketType::PlayEntityEquipment(pack) => {
+                        if let Some(ent) = self.entities.get_mut(&pack.entity_id.0) {
+                            for entry in pack.equipment.iter() {
+                                ent.equipment.set(&entry.slot, &entry.item);
+                            }
+                        }
+                    }
+
+                    PacketType::PlayEntityMetadata(pack) => {
+                        if let Some(ent) = self.entities.get_mut(&pack.entity_id.0) {
+                            ent.metadata.update(&pack.metadata);
+                        }
+                    }
+
+                    PacketType::PlayEntityProperties(pack) => {
+                        // Only our own armor and speed are used, not other entities' attributes
+                        if pack.entity_id.0 == self.player.id {
+                            if let Some(armor) = pack
+                                .properties
+                                .iter()
+                                .find(|property| property.key == "minecraft:generic.armor")
+                            {
+                                self.player.armor = Some(attribute_value(armor));
+                            }
+                            if let Some(speed) = pack
+                                .properties
+                                .iter()
+                                .find(|property| property.key == "minecraft:generic.movement_speed")
+                            {
+                                self.player.movement_speed = Some(attribute_value(speed));
+                            }
+                        }
+                    }
+
                     // Currently ignoring these packets
-                    PacketType::PlayEntityMetadata(_)
-                    | PacketType::PlayEntityProperties(_)
-                    | PacketType::PlayEntityStatus(_)
-                    | PacketType::PlayEntityAnimation(_) => {}
+                    PacketType::PlayEntityStatus(_) | PacketType::PlayEntityAnimation(_) => {}
 
                     // Packets that have been forwarded but not handled properly
                     _ => {
@@ -766,6 +2332,15 @@ impl Server {
                 }
             }
 
+            // The network thread stops after losing the connection
+            Error(e) => {
+                if self.connection == ConnectionState::Connected {
+                    self.connection = ConnectionState::ServerDisconnected(format!(
+                        "Lost connection to the server: {e}"
+                    ));
+                }
+            }
+
             // What do with these messages ay??
             _ => {
                 tracing::debug!("Unhandled message: {:?}", comm);
@@ -773,3 +2348,68 @@ impl Server {
         }
     }
 }
+
+/// Sound positions are sent as fixed-point numbers with 3 fractional bits
+fn sound_position(position: &types::Vec3<types::FixedInt>) -> DVec3 {
+    DVec3::new(
+        position.x.into_float(3),
+        position.y.into_float(3),
+        position.z.into_float(3),
+    )
+}
+
+fn sound_category(category: &PacketSoundCategory) -> SoundCategory {
+    match category {
+        PacketSoundCategory::Music | PacketSoundCategory::Records => SoundCategory::Music,
+        PacketSoundCategory::Block => SoundCategory::Blocks,
+        PacketSoundCategory::Hostile => SoundCategory::Hostile,
+        _ => SoundCategory::Other,
+    }
+}
+
+fn int_position(position: WorldCoords) -> IntPosition {
+    IntPosition {
+        x: position.x,
+        y: position.y as i16,
+        z: position.z,
+    }
+}
+
+fn digging_face(face: BlockFace) -> DiggingFace {
+    match face {
+        BlockFace::Down => DiggingFace::Bottom,
+        BlockFace::Up => DiggingFace::Top,
+        BlockFace::North => DiggingFace::North,
+        BlockFace::South => DiggingFace::South,
+        BlockFace::West => DiggingFace::West,
+        BlockFace::East => DiggingFace::East,
+    }
+}
+
+/// An attribute's value once its modifiers are applied, in the same order as vanilla: additions,
+/// then percentages of the base, then multipliers
+fn attribute_value(property: &EntityPropertySpec) -> f64 {
+    let mut base = property.value;
+    for modifier in property.modifiers.iter() {
+        if matches!(
+            modifier.operation,
+            EntityPropertyModifierOperation::AddSubtractAmount
+        ) {
+            base += modifier.amount;
+        }
+    }
+
+    let mut value = base;
+    for modifier in property.modifiers.iter() {
+        match modifier.operation {
+            EntityPropertyModifierOperation::AddSubtractAmount => {}
+            EntityPropertyModifierOperation::AddSubtractAmountPercentOfCurrent => {
+                value += base * modifier.amount;
+            }
+            EntityPropertyModifierOperation::MultiplyByAmountPercent => {
+                value *= 1.0 + modifier.amount;
+            }
+        }
+    }
+    value
+}
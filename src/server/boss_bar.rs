@@ -0,0 +1,43 @@
+use egui::Color32;
+use mcproto_rs::{
+    types::Chat as ChatComponent,
+    v1_16_3::{BossBarColor, BossBarDivision},
+};
+
+/// A boss bar the server has added, shown at the top of the screen until it's removed
+pub struct BossBar {
+    pub title: ChatComponent,
+    /// Fraction of the bar that's filled, from 0 to 1
+    pub health: f32,
+    pub colour: BossBarColor,
+    pub division: BossBarDivision,
+    /// Bars are shown in the order they were added
+    pub order: u64,
+}
+
+impl BossBar {
+    #[must_use]
+    pub fn get_colour(&self) -> Color32 {
+        match self.colour {
+            BossBarColor::Pink => Color32::from_rgb(236, 0, 184),
+            BossBarColor::Blue => Color32::from_rgb(0, 183, 236),
+            BossBarColor::Red => Color32::from_rgb(236, 53, 0),
+            BossBarColor::Green => Color32::from_rgb(29, 236, 0),
+            BossBarColor::Yellow => Color32::from_rgb(233, 236, 0),
+            BossBarColor::Purple => Color32::from_rgb(123, 0, 236),
+            BossBarColor::White => Color32::from_rgb(236, 236, 236),
+        }
+    }
+
+    /// Number of segments the bar is split into
+    #[must_use]
+    pub fn get_segments(&self) -> u32 {
+        match self.division {
+            BossBarDivision::NoDivision => 1,
+            BossBarDivision::SixNotches => 6,
+            BossBarDivision::TenNotches => 10,
+            BossBarDivision::TwelveNotches => 12,
+            BossBarDivision::TwentyNotches => 20,
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use mcproto_rs::v1_16_3::{
+    CommandNode, CommandParserSpec, PlayDeclareCommandsSpec, StringParserMode,
+};
+
+/// What an argument accepts, as far as we can check it without the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentParser {
+    Bool,
+    Integer,
+    Float,
+    /// A single word
+    Word,
+    /// A single word, or several in quotes
+    Phrase,
+    /// Everything left in the command
+    Greedy,
+}
+
+impl ArgumentParser {
+    fn from_spec(spec: &CommandParserSpec) -> Self {
+        match spec {
+            CommandParserSpec::Bool => Self::Bool,
+            CommandParserSpec::Integer(_) => Self::Integer,
+            CommandParserSpec::Float(_) | CommandParserSpec::Double(_) => Self::Float,
+            CommandParserSpec::StringParser(StringParserMode::SingleWord) => Self::Word,
+            // Misspelt in mcproto-rs
+            CommandParserSpec::StringParser(StringParserMode::QuotablePharse) => Self::Phrase,
+            CommandParserSpec::StringParser(StringParserMode::GreedyPhrase) => Self::Greedy,
+            // Anything else might span several words or be a parser we don't know about, so
+            // accept whatever is left rather than flag valid commands as wrong
+            _ => Self::Greedy,
+        }
+    }
+
+    /// How many bytes from the start of `input` this argument takes up, if it fits at all
+    fn consume(self, input: &str) -> Option<usize> {
+        let word = input.split(' ').next().unwrap_or_default();
+        let valid = match self {
+            Self::Bool => word == "true" || word == "false",
+            Self::Integer => word.parse::<i32>().is_ok(),
+            Self::Float => word.parse::<f64>().is_ok(),
+            Self::Word => !word.is_empty(),
+            Self::Phrase => {
+                if let Some(quoted) = input.strip_prefix('"') {
+                    // An unfinished quote is still being typed so takes the rest
+                    return Some(quoted.find('"').map_or(input.len(), |end| end + 2));
+                }
+                !word.is_empty()
+            }
+            Self::Greedy => return Some(input.len()),
+        };
+        valid.then_some(word.len())
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::Integer => "integer",
+            Self::Float => "number",
+            Self::Word => "word",
+            Self::Phrase => "phrase",
+            Self::Greedy => "text",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    Root,
+    Literal(String),
+    Argument {
+        name: String,
+        parser: ArgumentParser,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub children: Vec<usize>,
+    /// Node to carry on from after this one, e.g. `execute run` goes back to the root
+    pub redirect: Option<usize>,
+}
+
+/// What the chat input looks like as a command
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandCheck {
+    /// Byte offset into the input where it stops being a command we know, None if all of it is
+    pub error_at: Option<usize>,
+    /// The rest of the literal currently being typed, which tab fills in
+    pub completion: Option<String>,
+    /// Name and type of the argument that comes next
+    pub expected: Option<String>,
+}
+
+/// The commands the server has told us about, as a graph of literals and arguments from the
+/// root node
+#[derive(Debug, Default)]
+pub struct CommandTree {
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl CommandTree {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn from_packet(pack: &PlayDeclareCommandsSpec) -> Self {
+        let count = pack.nodes.len();
+        // Drop any links to nodes that don't exist so we never need to check again
+        let index = |i: i32| usize::try_from(i).ok().filter(|i| *i < count);
+
+        let nodes = pack
+            .nodes
+            .iter()
+            .map(|node| Node {
+                kind: match &node.node {
+                    CommandNode::Root => NodeKind::Root,
+                    CommandNode::Literal(literal) => NodeKind::Literal(literal.name.clone()),
+                    CommandNode::Argument(argument) => NodeKind::Argument {
+                        name: argument.name.clone(),
+                        parser: ArgumentParser::from_spec(&argument.parser),
+                    },
+                },
+                children: node
+                    .children_indices
+                    .iter()
+                    .filter_map(|i| index(i.0))
+                    .collect(),
+                redirect: node.redirect_node.as_ref().and_then(|i| index(i.0)),
+            })
+            .collect();
+
+        match index(pack.root_index.0) {
+            Some(root) => Self { nodes, root },
+            None => {
+                tracing::warn!("Commands root {} is out of range", pack.root_index.0);
+                Self::new()
+            }
+        }
+    }
+
+    /// The nodes that can follow `node`
+    fn children(&self, node: usize) -> impl Iterator<Item = &Node> {
+        let node = &self.nodes[node];
+        let source = node.redirect.map_or(node, |redirect| &self.nodes[redirect]);
+        source.children.iter().map(|child| &self.nodes[*child])
+    }
+
+    /// The child of `node` that the start of `input` is, and how many bytes of it that takes.
+    /// Literals are tried before arguments.
+    fn match_child(&self, node: usize, input: &str) -> Option<(usize, usize)> {
+        let source = self.nodes[node].redirect.unwrap_or(node);
+        let word = input.split(' ').next().unwrap_or_default();
+        let children = &self.nodes[source].children;
+
+        children
+            .iter()
+            .find_map(|child| match &self.nodes[*child].kind {
+                NodeKind::Literal(name) if name == word => Some((*child, word.len())),
+                _ => None,
+            })
+            .or_else(|| {
+                children
+                    .iter()
+                    .find_map(|child| match &self.nodes[*child].kind {
+                        NodeKind::Argument { parser, .. } => {
+                            parser.consume(input).map(|len| (*child, len))
+                        }
+                        _ => None,
+                    })
+            })
+    }
+
+    /// Follow `input` through the tree for as long as it matches whole words, returning the
+    /// node reached and the byte offset of whatever is left
+    fn walk(&self, input: &str) -> (usize, usize) {
+        let mut node = self.root;
+        let mut pos = 1;
+        loop {
+            let rest = &input[pos..];
+            // The last word might still be being typed
+            if !rest.contains(' ') {
+                return (node, pos);
+            }
+            match self.match_child(node, rest) {
+                Some((child, len)) if rest[len..].starts_with(' ') => {
+                    node = child;
+                    pos += len + 1;
+                }
+                _ => return (node, pos),
+            }
+        }
+    }
+
+    /// Literals following everything but the last word of `input` that start with the last word
+    #[must_use]
+    pub fn completions<'a>(&'a self, input: &str) -> Vec<&'a str> {
+        if self.nodes.is_empty() || !input.starts_with('/') {
+            return Vec::new();
+        }
+
+        let (node, pos) = self.walk(input);
+        let partial = &input[pos..];
+        if partial.contains(' ') {
+            return Vec::new();
+        }
+
+        let mut literals: Vec<&str> = self
+            .children(node)
+            .filter_map(|child| match &child.kind {
+                NodeKind::Literal(name) if name.starts_with(partial) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        literals.sort_unstable();
+        literals
+    }
+
+    /// Check how much of a command the server would understand, and what could come next
+    #[must_use]
+    pub fn check(&self, input: &str) -> CommandCheck {
+        // Nothing to check against until the server sends its commands
+        if self.nodes.is_empty() || !input.starts_with('/') {
+            return CommandCheck::default();
+        }
+
+        let (node, pos) = self.walk(input);
+        let rest = &input[pos..];
+
+        if rest.is_empty() {
+            let expected = self.children(node).find_map(|child| match &child.kind {
+                NodeKind::Argument { name, parser } => {
+                    Some(format!("<{name}: {}>", parser.describe()))
+                }
+                _ => None,
+            });
+            return CommandCheck {
+                expected,
+                ..Default::default()
+            };
+        }
+
+        if self
+            .match_child(node, rest)
+            .is_some_and(|(_, len)| len == rest.len())
+        {
+            return CommandCheck::default();
+        }
+
+        let completion = self
+            .completions(input)
+            .first()
+            .map(|literal| literal[rest.len()..].to_owned());
+        CommandCheck {
+            error_at: completion.is_none().then_some(pos),
+            completion,
+            expected: None,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use mcproto_rs::types::{Chat as ChatComponent, Slot};
+
+use crate::player::inventory::ItemStack;
+
+/// Number of slots in the player's inventory and hotbar that come after a container's own slots
+const PLAYER_SLOTS: usize = 36;
+
+/// A window the server has opened, e.g. a chest, which lasts until either side closes it
+#[derive(Debug)]
+pub struct Container {
+    id: u8,
+    /// Window type id from the `minecraft:menu` registry
+    kind: i32,
+    title: ChatComponent,
+    /// Including the player's inventory at the end, like the server sends them
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Container {
+    #[must_use]
+    pub fn new(id: u8, kind: i32, title: ChatComponent) -> Self {
+        Self {
+            id,
+            kind,
+            title,
+            slots: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn get_id(&self) -> u8 {
+        self.id
+    }
+
+    #[must_use]
+    pub fn get_title(&self) -> &ChatComponent {
+        &self.title
+    }
+
+    /// Replace the contents of every slot, as in a Window Items packet
+    pub fn set_all(&mut self, slots: &[Slot]) {
+        self.slots = slots.iter().map(ItemStack::from_slot).collect();
+    }
+
+    /// Set the contents of a slot, growing the window if the items haven't arrived yet
+    pub fn set_slot(&mut self, slot: usize, item: Option<ItemStack>) {
+        if slot >= self.slots.len() {
+            self.slots.resize(slot + 1, None);
+        }
+        self.slots[slot] = item;
+    }
+
+    /// The container's own slots, leaving out the player's inventory
+    #[must_use]
+    pub fn get_slots(&self) -> &[Option<ItemStack>] {
+        let len = container_size(self.kind)
+            .unwrap_or_else(|| self.slots.len().saturating_sub(PLAYER_SLOTS))
+            .min(self.slots.len());
+        &self.slots[..len]
+    }
+}
+
+/// How many slots a type of window has before the player's inventory
+/// (https://wiki.vg/Inventory#Windows)
+fn container_size(kind: i32) -> Option<usize> {
+    Some(match kind {
+        // generic_9x1 to generic_9x6
+        0..=5 => 9 * (kind as usize + 1),
+        // generic_3x3
+        6 => 9,
+        // beacon and lectern
+        8 | 16 => 1,
+        // enchantment and stonecutter
+        12 | 23 => 2,
+        // anvil, blast_furnace, furnace, grindstone, merchant, smithing, smoker and cartography
+        7 | 9 | 13 | 14 | 18 | 20 | 21 | 22 => 3,
+        // loom
+        17 => 4,
+        // brewing_stand and hopper
+        10 | 15 => 5,
+        // crafting
+        11 => 10,
+        // shulker_box
+        19 => 27,
+        _ => return None,
+    })
+}
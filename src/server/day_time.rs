@@ -0,0 +1,70 @@
+/// How many ticks the day advances each second
+const TICKS_PER_SECOND: f64 = 20.0;
+/// Ticks in a full day
+const TICKS_PER_DAY: f64 = 24_000.0;
+/// How far off the server's time we can be before jumping straight to it rather than easing
+const MAX_DRIFT: f64 = 40.0;
+/// How much of the remaining correction is eased in each second
+const CORRECTION_RATE: f64 = 2.0;
+
+/// The time of day, ticking along between the updates the server sends about once a second so
+/// the sky glides rather than steps
+#[derive(Debug, Default)]
+pub struct DayTime {
+    /// The last time the server sent
+    server_time: i64,
+    /// Our estimate of the current time, in ticks
+    time: f64,
+    /// How far the estimate still has to move to catch up with the server
+    correction: f64,
+    /// If the server has stopped the daylight cycle
+    frozen: bool,
+}
+
+impl DayTime {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the time from the server. It sends a negative time when the daylight cycle is off
+    pub fn set(&mut self, time_of_day: i64) {
+        self.frozen = time_of_day < 0;
+        self.server_time = time_of_day.abs();
+
+        let error = self.server_time as f64 - self.time;
+        if self.frozen || error.abs() > MAX_DRIFT {
+            self.time = self.server_time as f64;
+            self.correction = 0.0;
+        } else {
+            self.correction = error;
+        }
+    }
+
+    /// Move the time along by `delta` seconds
+    pub fn update(&mut self, delta: f64) {
+        if self.frozen {
+            return;
+        }
+
+        let step = self.correction * (delta * CORRECTION_RATE).min(1.0);
+        self.correction -= step;
+        self.time += delta * TICKS_PER_SECOND + step;
+
+        // If the server stops sending time without telling us, e.g. while it's lagging, don't
+        // keep running off on our own
+        self.time = self.time.min(self.server_time as f64 + MAX_DRIFT);
+    }
+
+    /// The last time the server sent, in ticks
+    #[must_use]
+    pub const fn get_ticks(&self) -> i64 {
+        self.server_time
+    }
+
+    /// How far through the day it is, from 0.0 at sunrise to 1.0
+    #[must_use]
+    pub fn get_fraction(&self) -> f64 {
+        self.time.rem_euclid(TICKS_PER_DAY) / TICKS_PER_DAY
+    }
+}
@@ -0,0 +1,53 @@
+use crate::world::{raycast::BlockFace, WorldCoords};
+
+/// How far from their eyes the player can reach blocks
+pub const REACH: f64 = 5.0;
+
+/// Seconds it takes to break any block outside of creative, until block hardness is known
+const BREAK_TIME: f64 = 1.0;
+/// Seconds between arm swings while breaking a block or holding left click
+pub const SWING_PERIOD: f64 = 0.25;
+
+/// A block the player is part way through breaking
+pub struct Digging {
+    pub position: WorldCoords,
+    pub face: BlockFace,
+
+    elapsed: f64,
+    since_swing: f64,
+}
+
+impl Digging {
+    #[must_use]
+    pub fn new(position: WorldCoords, face: BlockFace) -> Self {
+        Self {
+            position,
+            face,
+
+            elapsed: 0.0,
+            since_swing: 0.0,
+        }
+    }
+
+    /// Keep breaking for another `delta` seconds, returns if the arm should swing again
+    pub fn update(&mut self, delta: f64) -> bool {
+        self.elapsed += delta;
+        self.since_swing += delta;
+        if self.since_swing >= SWING_PERIOD {
+            self.since_swing -= SWING_PERIOD;
+            return true;
+        }
+        false
+    }
+
+    /// How close the block is to breaking, from 0 to 1
+    #[must_use]
+    pub fn get_progress(&self) -> f64 {
+        (self.elapsed / BREAK_TIME).min(1.0)
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= BREAK_TIME
+    }
+}
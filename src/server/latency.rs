@@ -0,0 +1,45 @@
+use std::collections::VecDeque;
+
+/// Number of samples kept for the rolling average
+const LATENCY_SAMPLES: usize = 10;
+
+/// Keeps a rolling average of recent latency samples so the displayed ping doesn't jump around.
+/// The samples are the keep-alive round trips the server measures and reports in the player list,
+/// as the client only answers keep-alives and can't time them itself
+pub struct LatencyTracker {
+    samples: VecDeque<u32>,
+}
+
+impl LatencyTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(LATENCY_SAMPLES),
+        }
+    }
+
+    /// Add a new sample in milliseconds, dropping the oldest sample if there are too many
+    pub fn add_sample(&mut self, ping: u32) {
+        if self.samples.len() >= LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ping);
+    }
+
+    /// The average of the recent samples, or None if there haven't been any yet
+    #[must_use]
+    pub fn average(&self) -> Option<u32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let total: u64 = self.samples.iter().map(|&s| u64::from(s)).sum();
+        Some((total / self.samples.len() as u64) as u32)
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
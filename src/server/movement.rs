@@ -0,0 +1,386 @@
+use std::collections::VecDeque;
+
+use glam::DVec3;
+use mcproto_rs::{types::EntityLocation, v1_16_3::PositionAndLookFlags};
+
+/// Moving less than this far isn't worth telling the server about, the same as vanilla
+const MIN_MOVEMENT: f64 = 2e-4;
+/// Moving further than this since the last update sends the position straight away, without
+/// waiting for the next tick
+const MAX_UNSENT_MOVEMENT: f64 = 1.0;
+/// Ticks between letting the server know we're still here when nothing has changed
+const HEARTBEAT_TICKS: u32 = 20;
+
+/// Which movement packet to send the server, the smallest one that covers what changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementUpdate {
+    Nothing,
+    /// Just the on ground flag
+    Heartbeat,
+    Position,
+    Rotation,
+    PositionAndRotation,
+}
+
+/// A teleport from the server that we've applied but not yet confirmed
+#[derive(Debug, Clone, Copy)]
+pub struct PendingTeleport {
+    pub id: i32,
+    /// Where the teleport put the player, with any relative parts already added on
+    pub position: DVec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl PendingTeleport {
+    /// Work out where a teleport puts the player, where each part flagged as relative is an
+    /// offset from where they are rather than absolute
+    #[must_use]
+    pub fn resolve(
+        id: i32,
+        location: &EntityLocation<f64, f32>,
+        flags: PositionAndLookFlags,
+        position: DVec3,
+        yaw: f64,
+        pitch: f64,
+    ) -> Self {
+        let relative = |is_relative: bool, value: f64, current: f64| {
+            if is_relative {
+                current + value
+            } else {
+                value
+            }
+        };
+
+        Self {
+            id,
+            position: DVec3::new(
+                relative(flags.is_x_rel(), location.position.x, position.x),
+                relative(flags.is_y_rel(), location.position.y, position.y),
+                relative(flags.is_z_rel(), location.position.z, position.z),
+            ),
+            yaw: relative(
+                flags.is_y_rotation_rel(),
+                f64::from(location.rotation.yaw),
+                yaw,
+            ) as f32,
+            pitch: relative(
+                flags.is_x_rotation_rel(),
+                f64::from(location.rotation.pitch),
+                pitch,
+            ) as f32,
+        }
+    }
+}
+
+/// Remembers what we last told the server about where the player is and which way they're
+/// facing, to work out what needs sending next
+#[derive(Debug, Default)]
+pub struct MovementTracker {
+    position: DVec3,
+    yaw: f32,
+    pitch: f32,
+    on_ground: bool,
+    /// Ticks since anything was sent
+    idle_ticks: u32,
+    /// Teleports applied but not yet confirmed, oldest first
+    teleports: VecDeque<PendingTeleport>,
+}
+
+impl MovementTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decide what to send for this tick, assuming it will be sent
+    pub fn update(
+        &mut self,
+        position: DVec3,
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool,
+    ) -> MovementUpdate {
+        let moved = position.distance_squared(self.position) > MIN_MOVEMENT * MIN_MOVEMENT;
+        let rotated = yaw != self.yaw || pitch != self.pitch;
+        self.idle_ticks += 1;
+
+        let update = match (moved, rotated) {
+            (true, true) => MovementUpdate::PositionAndRotation,
+            (true, false) => MovementUpdate::Position,
+            (false, true) => MovementUpdate::Rotation,
+            (false, false) if on_ground != self.on_ground || self.idle_ticks >= HEARTBEAT_TICKS => {
+                MovementUpdate::Heartbeat
+            }
+            (false, false) => MovementUpdate::Nothing,
+        };
+
+        if moved {
+            self.position = position;
+        }
+        if rotated {
+            self.yaw = yaw;
+            self.pitch = pitch;
+        }
+        self.on_ground = on_ground;
+        if update != MovementUpdate::Nothing {
+            self.idle_ticks = 0;
+        }
+
+        update
+    }
+
+    /// Record a full update that was sent outside of `update`, e.g. confirming a teleport
+    pub fn record(&mut self, position: DVec3, yaw: f32, pitch: f32, on_ground: bool) {
+        self.position = position;
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self.on_ground = on_ground;
+        self.idle_ticks = 0;
+    }
+
+    /// Remember a teleport to confirm later, after any that came before it
+    pub fn queue_teleport(&mut self, teleport: PendingTeleport) {
+        self.teleports.push_back(teleport);
+    }
+
+    /// The oldest teleport still to be confirmed, recording it as sent as the server expects to
+    /// hear back where it put us along with the confirmation
+    pub fn confirm_teleport(&mut self) -> Option<PendingTeleport> {
+        let teleport = self.teleports.pop_front()?;
+        self.record(teleport.position, teleport.yaw, teleport.pitch, false);
+        Some(teleport)
+    }
+
+    /// If the player has moved far enough that the server should hear about it right away
+    #[must_use]
+    pub fn moved_far(&self, position: DVec3) -> bool {
+        position.distance_squared(self.position) > MAX_UNSENT_MOVEMENT * MAX_UNSENT_MOVEMENT
+    }
+}
+
+/// Unit vectors pointing forwards and to the right of a yaw in degrees, along the ground. A yaw of
+/// 0 faces south (+Z), turning towards west (-X)
+#[must_use]
+pub fn walk_directions(yaw: f64) -> (DVec3, DVec3) {
+    let yaw = yaw.to_radians();
+    let forward = DVec3::new(-yaw.sin(), 0.0, yaw.cos());
+    let right = forward.cross(DVec3::Y).normalize();
+    (forward, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use mcproto_rs::types::{EntityRotation, Vec3};
+
+    use super::*;
+
+    const START: DVec3 = DVec3::new(0.5, 64.0, 0.5);
+
+    /// A tracker that has already sent the player standing at `START` facing south
+    fn tracker() -> MovementTracker {
+        let mut tracker = MovementTracker::new();
+        tracker.record(START, 0.0, 0.0, true);
+        tracker
+    }
+
+    #[test]
+    fn sends_only_what_changed() {
+        let mut tracker = tracker();
+        let moved = START + DVec3::X;
+
+        assert_eq!(
+            tracker.update(START, 0.0, 0.0, true),
+            MovementUpdate::Nothing
+        );
+        assert_eq!(
+            tracker.update(moved, 0.0, 0.0, true),
+            MovementUpdate::Position
+        );
+        assert_eq!(
+            tracker.update(moved, 90.0, 0.0, true),
+            MovementUpdate::Rotation
+        );
+        assert_eq!(
+            tracker.update(moved, 90.0, 45.0, true),
+            MovementUpdate::Rotation
+        );
+        assert_eq!(
+            tracker.update(START, 180.0, 0.0, true),
+            MovementUpdate::PositionAndRotation
+        );
+        assert_eq!(
+            tracker.update(START, 180.0, 0.0, true),
+            MovementUpdate::Nothing
+        );
+    }
+
+    #[test]
+    fn tiny_movements_are_ignored() {
+        let mut tracker = tracker();
+        let nudged = START + DVec3::splat(MIN_MOVEMENT / 2.0);
+
+        assert_eq!(
+            tracker.update(nudged, 0.0, 0.0, true),
+            MovementUpdate::Nothing
+        );
+    }
+
+    #[test]
+    fn ground_only_changes_send_heartbeat() {
+        let mut tracker = tracker();
+
+        assert_eq!(
+            tracker.update(START, 0.0, 0.0, false),
+            MovementUpdate::Heartbeat
+        );
+        assert_eq!(
+            tracker.update(START, 0.0, 0.0, false),
+            MovementUpdate::Nothing
+        );
+        assert_eq!(
+            tracker.update(START, 0.0, 0.0, true),
+            MovementUpdate::Heartbeat
+        );
+    }
+
+    #[test]
+    fn heartbeat_every_20_ticks() {
+        let mut tracker = tracker();
+
+        for _ in 1..HEARTBEAT_TICKS {
+            assert_eq!(
+                tracker.update(START, 0.0, 0.0, true),
+                MovementUpdate::Nothing
+            );
+        }
+        assert_eq!(
+            tracker.update(START, 0.0, 0.0, true),
+            MovementUpdate::Heartbeat
+        );
+
+        // Anything sent restarts the count
+        for _ in 1..HEARTBEAT_TICKS {
+            assert_eq!(
+                tracker.update(START, 0.0, 0.0, true),
+                MovementUpdate::Nothing
+            );
+        }
+        assert_eq!(
+            tracker.update(START, 10.0, 0.0, true),
+            MovementUpdate::Rotation
+        );
+        assert_eq!(
+            tracker.update(START, 10.0, 0.0, true),
+            MovementUpdate::Nothing
+        );
+    }
+
+    #[test]
+    fn moved_far_since_last_sent() {
+        let mut tracker = tracker();
+
+        assert!(!tracker.moved_far(START + DVec3::new(0.5, 0.0, 0.5)));
+        assert!(tracker.moved_far(START + DVec3::new(0.0, -1.5, 0.0)));
+
+        // Only what's been sent counts
+        tracker.update(START + DVec3::X * 0.8, 0.0, 0.0, true);
+        assert!(tracker.moved_far(START + DVec3::X * 1.9));
+        assert!(!tracker.moved_far(START + DVec3::X * 1.7));
+    }
+
+    fn assert_close(a: DVec3, b: DVec3) {
+        assert!(a.abs_diff_eq(b, 1e-9), "{a} != {b}");
+    }
+
+    #[test]
+    fn walking_directions_follow_yaw() {
+        let cases = [
+            (0.0, DVec3::Z, DVec3::NEG_X),
+            (90.0, DVec3::NEG_X, DVec3::NEG_Z),
+            (180.0, DVec3::NEG_Z, DVec3::X),
+            (-90.0, DVec3::X, DVec3::Z),
+            (270.0, DVec3::X, DVec3::Z),
+        ];
+        for (yaw, forward, right) in cases {
+            let (f, r) = walk_directions(yaw);
+            assert_close(f, forward);
+            assert_close(r, right);
+        }
+    }
+
+    #[test]
+    fn walking_directions_are_square_and_level() {
+        for yaw in [0.0, 17.5, 45.0, 133.0, 200.0, -60.0, 719.0] {
+            let (forward, right) = walk_directions(yaw);
+            assert!((forward.length() - 1.0).abs() < 1e-9);
+            assert!((right.length() - 1.0).abs() < 1e-9);
+            assert!(forward.dot(right).abs() < 1e-9);
+            assert_eq!(forward.y, 0.0);
+            assert_eq!(right.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn relative_teleport_parts_are_offsets() {
+        let location = EntityLocation {
+            position: Vec3 {
+                x: 10.0,
+                y: 2.0,
+                z: -4.0,
+            },
+            rotation: EntityRotation {
+                yaw: 90.0,
+                pitch: 15.0,
+            },
+        };
+
+        // Absolute teleports ignore where we are
+        let teleport =
+            PendingTeleport::resolve(1, &location, PositionAndLookFlags(0), START, 45.0, 30.0);
+        assert_eq!(teleport.id, 1);
+        assert_eq!(teleport.position, DVec3::new(10.0, 2.0, -4.0));
+        assert_eq!((teleport.yaw, teleport.pitch), (90.0, 15.0));
+
+        // Only the flagged parts are added on
+        let mut flags = PositionAndLookFlags(0);
+        flags.set_y_rel(true);
+        flags.set_y_rotation_rel(true);
+        let teleport = PendingTeleport::resolve(2, &location, flags, START, 45.0, 30.0);
+        assert_eq!(teleport.position, DVec3::new(10.0, 66.0, -4.0));
+        assert_eq!((teleport.yaw, teleport.pitch), (135.0, 15.0));
+
+        // Everything relative, like a server nudging the player along
+        let teleport =
+            PendingTeleport::resolve(3, &location, PositionAndLookFlags(0x1F), START, 45.0, 30.0);
+        assert_eq!(teleport.position, DVec3::new(10.5, 66.0, -3.5));
+        assert_eq!((teleport.yaw, teleport.pitch), (135.0, 45.0));
+    }
+
+    #[test]
+    fn teleports_confirmed_in_order() {
+        let mut tracker = tracker();
+        for id in 1..=3 {
+            tracker.queue_teleport(PendingTeleport {
+                id,
+                position: DVec3::new(f64::from(id) * 100.0, 70.0, 0.0),
+                yaw: id as f32,
+                pitch: 0.0,
+            });
+        }
+
+        let confirmed: Vec<i32> = std::iter::from_fn(|| tracker.confirm_teleport())
+            .map(|teleport| teleport.id)
+            .collect();
+        assert_eq!(confirmed, [1, 2, 3]);
+        assert!(tracker.confirm_teleport().is_none());
+
+        // The last teleport is what the server now thinks, landing in the air
+        let last = DVec3::new(300.0, 70.0, 0.0);
+        assert!(!tracker.moved_far(last));
+        assert_eq!(
+            tracker.update(last, 3.0, 0.0, false),
+            MovementUpdate::Nothing
+        );
+    }
+}
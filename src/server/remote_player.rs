@@ -1,5 +1,7 @@
 use mcproto_rs::{uuid::UUID4, v1_16_3::GameMode};
 
+use super::teams::Teams;
+
 pub struct RemotePlayer {
     pub uuid: UUID4,
     pub name: String,
@@ -7,3 +9,14 @@ pub struct RemotePlayer {
     pub ping: i32,
     pub display_name: Option<String>,
 }
+
+impl RemotePlayer {
+    /// The name to show for this player, in the legacy `§` coded format. A display name from the
+    /// server replaces the name entirely, otherwise it's decorated by the player's team.
+    #[must_use]
+    pub fn get_display_name(&self, teams: &Teams) -> String {
+        self.display_name
+            .clone()
+            .unwrap_or_else(|| teams.decorate(&self.name))
+    }
+}
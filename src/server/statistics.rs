@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use mcproto_rs::v1_16_3::{PlayStatisticsSpec, StatisticCategory};
+
+use crate::resources::{block_types, custom_stats, entities, format_name, items, stat_types};
+
+/// Ticks in an hour
+const TICKS_PER_HOUR: i32 = 20 * 60 * 60;
+/// Ticks in a minute
+const TICKS_PER_MINUTE: i32 = 20 * 60;
+
+/// A single statistic, ready to be shown
+#[derive(Debug, Clone)]
+pub struct Statistic {
+    pub name: String,
+    pub value: String,
+}
+
+/// The player's statistics from the last time they were requested, grouped by category
+#[derive(Debug, Default)]
+pub struct Statistics {
+    categories: BTreeMap<String, Vec<Statistic>>,
+}
+
+impl Statistics {
+    #[must_use]
+    pub fn from_packet(pack: &PlayStatisticsSpec) -> Self {
+        let mut categories: BTreeMap<String, Vec<Statistic>> = BTreeMap::new();
+
+        for entry in pack.entries.iter() {
+            let category_id = entry.kind.id().0 as u32;
+            let statistic_id = match &entry.kind {
+                StatisticCategory::Mined(id)
+                | StatisticCategory::Crafted(id)
+                | StatisticCategory::Used(id)
+                | StatisticCategory::Broken(id)
+                | StatisticCategory::PickedUp(id)
+                | StatisticCategory::Dropped(id)
+                | StatisticCategory::Killed(id)
+                | StatisticCategory::KilledBy(id) => id.0 as u32,
+                StatisticCategory::Custom(stat) => stat.id().0 as u32,
+            };
+            let value = entry.value.0;
+
+            let category = stat_types()
+                .get(&category_id)
+                .map_or_else(|| format!("Unknown ({category_id})"), |c| format_name(c));
+            let (name, value) = match category.as_str() {
+                "Custom" => match custom_stats().get(&statistic_id) {
+                    Some(stat) => (format_name(stat), format_custom(stat, value)),
+                    None => (format!("Unknown ({statistic_id})"), value.to_string()),
+                },
+                _ => (stat_subject(&category, statistic_id), value.to_string()),
+            };
+
+            categories
+                .entry(category)
+                .or_default()
+                .push(Statistic { name, value });
+        }
+
+        for stats in categories.values_mut() {
+            stats.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        Self { categories }
+    }
+
+    /// Each category's name with its statistics sorted by name
+    pub fn categories(&self) -> impl Iterator<Item = (&String, &Vec<Statistic>)> {
+        self.categories.iter()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+}
+
+/// The block, item or entity a statistic outside the custom category counts
+fn stat_subject(category: &str, id: u32) -> String {
+    let name = match category {
+        "Mined" => block_types().get(&id).map(|b| format_name(b)),
+        "Killed" | "Killed By" => entities().get(&id).map(|e| e.name.clone()),
+        // Crafted, used, broken, picked up and dropped
+        _ => items().get(&id).map(|i| i.name.clone()),
+    };
+    name.unwrap_or_else(|| format!("Unknown ({id})"))
+}
+
+/// Custom statistics are counted in ticks for times and centimetres for distances
+fn format_custom(stat: &str, value: i32) -> String {
+    if stat.ends_with("_one_cm") {
+        format!("{:.2} km", f64::from(value) / 100_000.0)
+    } else if stat.contains("time") || stat.ends_with("play_one_minute") {
+        format!(
+            "{}:{:02}",
+            value / TICKS_PER_HOUR,
+            value % TICKS_PER_HOUR / TICKS_PER_MINUTE
+        )
+    } else {
+        value.to_string()
+    }
+}
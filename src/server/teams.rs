@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use mcproto_rs::{
+    types::VarInt,
+    v1_16_3::{PlayTeamsSpec, TeamAction, TeamMember},
+};
+
+/// How a team decorates the names of its members
+#[derive(Debug, Default)]
+pub struct Team {
+    /// In the legacy `§` coded format
+    pub prefix: String,
+    /// In the legacy `§` coded format
+    pub suffix: String,
+    /// Legacy colour code for members' names, None to leave them uncoloured
+    pub colour: Option<char>,
+}
+
+/// The scoreboard teams the server has told us about and who is in them
+#[derive(Debug, Default)]
+pub struct Teams {
+    teams: HashMap<String, Team>,
+    /// Team name for each entry, which is a player's name or an entity's UUID
+    members: HashMap<String, String>,
+}
+
+impl Teams {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle_teams(&mut self, pack: PlayTeamsSpec) {
+        let name = pack.team_name;
+        match pack.action {
+            TeamAction::Create(spec) => {
+                let team = Team {
+                    prefix: spec.prefix.to_traditional().unwrap_or_default(),
+                    suffix: spec.suffix.to_traditional().unwrap_or_default(),
+                    colour: team_colour(spec.color),
+                };
+                self.create_team(name, team, spec.entities.iter().map(member_entry));
+            }
+            TeamAction::UpdateInfo(spec) => {
+                let team = Team {
+                    prefix: spec.prefix.to_traditional().unwrap_or_default(),
+                    suffix: spec.suffix.to_traditional().unwrap_or_default(),
+                    colour: team_colour(spec.color),
+                };
+                self.update_team(&name, team);
+            }
+            TeamAction::Remove => self.remove_team(&name),
+            TeamAction::AddPlayers(spec) => {
+                self.add_members(&name, spec.entities.iter().map(member_entry));
+            }
+            TeamAction::RemovePlayers(spec) => {
+                self.remove_members(&name, spec.entities.iter().map(member_entry));
+            }
+        }
+    }
+
+    fn create_team(
+        &mut self,
+        name: String,
+        team: Team,
+        entries: impl Iterator<Item = impl Into<String>>,
+    ) {
+        self.add_members(&name, entries);
+        self.teams.insert(name, team);
+    }
+
+    /// Replace how a team decorates its members, keeping who's in it
+    fn update_team(&mut self, name: &str, team: Team) {
+        match self.teams.get_mut(name) {
+            Some(existing) => *existing = team,
+            None => tracing::warn!("Tried to update unknown team {}", name),
+        }
+    }
+
+    /// Drop a team, and with it every entry's membership so none keep its colours
+    fn remove_team(&mut self, name: &str) {
+        self.teams.remove(name);
+        self.members.retain(|_, team| team != name);
+    }
+
+    /// Entries can only be in one team, so joining a team leaves the last one
+    fn add_members(&mut self, team: &str, entries: impl Iterator<Item = impl Into<String>>) {
+        for entry in entries {
+            self.members.insert(entry.into(), team.to_owned());
+        }
+    }
+
+    /// Only removes entries that haven't already moved on to another team
+    fn remove_members(&mut self, team: &str, entries: impl Iterator<Item = impl Into<String>>) {
+        for entry in entries {
+            let entry = entry.into();
+            if self.members.get(&entry).is_some_and(|t| t == team) {
+                self.members.remove(&entry);
+            }
+        }
+    }
+
+    /// The team an entry, e.g. a player's name, is in
+    #[must_use]
+    pub fn get_team(&self, entry: &str) -> Option<&Team> {
+        self.members
+            .get(entry)
+            .and_then(|team| self.teams.get(team))
+    }
+
+    /// An entry's name with its team's prefix, suffix and colour, in the legacy `§` coded format
+    #[must_use]
+    pub fn decorate(&self, entry: &str) -> String {
+        match self.get_team(entry) {
+            Some(team) => {
+                let colour = team.colour.map(|c| format!("§{c}")).unwrap_or_default();
+                format!("{}{colour}{entry}§r{}", team.prefix, team.suffix)
+            }
+            None => entry.to_owned(),
+        }
+    }
+}
+
+/// Players are listed by their name and other entities by their UUID
+fn member_entry(member: &TeamMember) -> String {
+    match member {
+        TeamMember::Player(name) => name.clone(),
+        TeamMember::Entity(uuid) => uuid.to_string(),
+    }
+}
+
+/// Teams are coloured with a formatting code id, only 0-15 are colours
+fn team_colour(colour: VarInt) -> Option<char> {
+    u32::try_from(colour.0)
+        .ok()
+        .filter(|c| *c < 16)
+        .and_then(|c| char::from_digit(c, 16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(prefix: &str, colour: Option<char>) -> Team {
+        Team {
+            prefix: prefix.to_owned(),
+            suffix: String::new(),
+            colour,
+        }
+    }
+
+    fn entries(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn create_add_update_remove() {
+        let mut teams = Teams::new();
+
+        teams.create_team(
+            "red".into(),
+            team("[R] ", Some('c')),
+            entries(&["Alex"]).iter(),
+        );
+        assert_eq!(teams.decorate("Alex"), "[R] §cAlex§r");
+        assert_eq!(teams.decorate("Steve"), "Steve");
+
+        teams.add_members("red", entries(&["Steve"]).iter());
+        assert_eq!(teams.decorate("Steve"), "[R] §cSteve§r");
+
+        teams.update_team("red", team("[R] ", Some('9')));
+        assert_eq!(teams.decorate("Alex"), "[R] §9Alex§r");
+        assert_eq!(teams.decorate("Steve"), "[R] §9Steve§r");
+
+        teams.remove_team("red");
+        assert!(teams.get_team("Alex").is_none());
+        assert_eq!(teams.decorate("Alex"), "Alex");
+        assert_eq!(teams.decorate("Steve"), "Steve");
+    }
+
+    #[test]
+    fn switching_teams() {
+        let mut teams = Teams::new();
+        teams.create_team("red".into(), team("", Some('c')), entries(&["Alex"]).iter());
+        teams.create_team("blue".into(), team("", Some('9')), entries(&[]).iter());
+
+        teams.add_members("blue", entries(&["Alex"]).iter());
+        assert_eq!(teams.decorate("Alex"), "§9Alex§r");
+
+        // Being removed from the team they already left changes nothing
+        teams.remove_members("red", entries(&["Alex"]).iter());
+        assert_eq!(teams.decorate("Alex"), "§9Alex§r");
+        // And removing the old team doesn't take them out of the new one
+        teams.remove_team("red");
+        assert_eq!(teams.decorate("Alex"), "§9Alex§r");
+
+        teams.remove_members("blue", entries(&["Alex"]).iter());
+        assert_eq!(teams.decorate("Alex"), "Alex");
+    }
+
+    #[test]
+    fn updating_unknown_team_does_nothing() {
+        let mut teams = Teams::new();
+        teams.update_team("ghost", team("[G] ", None));
+        teams.add_members("ghost", entries(&["Alex"]).iter());
+
+        assert!(teams.get_team("Alex").is_none());
+        assert_eq!(teams.decorate("Alex"), "Alex");
+    }
+
+    #[test]
+    fn only_legacy_colours_are_kept() {
+        assert_eq!(team_colour(VarInt(0)), Some('0'));
+        assert_eq!(team_colour(VarInt(12)), Some('c'));
+        assert_eq!(team_colour(VarInt(15)), Some('f'));
+        // Formatting codes and reset aren't colours
+        assert_eq!(team_colour(VarInt(16)), None);
+        assert_eq!(team_colour(VarInt(21)), None);
+        assert_eq!(team_colour(VarInt(-1)), None);
+    }
+}
@@ -0,0 +1,143 @@
+use mcproto_rs::types::Chat as ChatComponent;
+
+/// Length of a game tick in seconds, title timings are sent in ticks
+const TICK: f64 = 0.05;
+
+const DEFAULT_FADE_IN: i32 = 10;
+const DEFAULT_STAY: i32 = 70;
+const DEFAULT_FADE_OUT: i32 = 20;
+
+/// How long action bar text stays on screen, the last second of which it fades out
+const ACTION_BAR_TIME: f64 = 3.0;
+const ACTION_BAR_FADE: f64 = 1.0;
+
+/// The title, subtitle and action bar text currently being shown
+pub struct Titles {
+    title: Option<ChatComponent>,
+    subtitle: Option<ChatComponent>,
+    /// Seconds since the title was shown
+    title_time: f64,
+
+    fade_in: f64,
+    stay: f64,
+    fade_out: f64,
+
+    action_bar: Option<ChatComponent>,
+    /// Seconds since the action bar text was shown
+    action_bar_time: f64,
+}
+
+impl Titles {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut titles = Self {
+            title: None,
+            subtitle: None,
+            title_time: 0.0,
+
+            fade_in: 0.0,
+            stay: 0.0,
+            fade_out: 0.0,
+
+            action_bar: None,
+            action_bar_time: 0.0,
+        };
+        titles.reset();
+        titles
+    }
+
+    /// Show a new title, restarting the fade in
+    pub fn set_title(&mut self, text: ChatComponent) {
+        self.title = Some(text);
+        self.title_time = 0.0;
+    }
+
+    /// Set the subtitle, which is shown underneath the title while it's visible
+    pub fn set_subtitle(&mut self, text: ChatComponent) {
+        self.subtitle = Some(text);
+    }
+
+    pub fn set_action_bar(&mut self, text: ChatComponent) {
+        self.action_bar = Some(text);
+        self.action_bar_time = 0.0;
+    }
+
+    /// Set how long titles fade in, stay and fade out for, in ticks
+    pub fn set_times(&mut self, fade_in: i32, stay: i32, fade_out: i32) {
+        self.fade_in = f64::from(fade_in.max(0)) * TICK;
+        self.stay = f64::from(stay.max(0)) * TICK;
+        self.fade_out = f64::from(fade_out.max(0)) * TICK;
+    }
+
+    /// Remove the title and subtitle from the screen
+    pub fn hide(&mut self) {
+        self.title = None;
+        self.subtitle = None;
+        self.title_time = 0.0;
+    }
+
+    /// Remove the title and subtitle and go back to the default timings
+    pub fn reset(&mut self) {
+        self.hide();
+        self.set_times(DEFAULT_FADE_IN, DEFAULT_STAY, DEFAULT_FADE_OUT);
+    }
+
+    pub fn update(&mut self, delta: f64) {
+        if self.title.is_some() {
+            self.title_time += delta;
+            if self.title_time >= self.fade_in + self.stay + self.fade_out {
+                self.hide();
+            }
+        }
+
+        if self.action_bar.is_some() {
+            self.action_bar_time += delta;
+            if self.action_bar_time >= ACTION_BAR_TIME {
+                self.action_bar = None;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn get_title(&self) -> Option<&ChatComponent> {
+        self.title.as_ref()
+    }
+
+    #[must_use]
+    pub fn get_subtitle(&self) -> Option<&ChatComponent> {
+        self.title.as_ref().and(self.subtitle.as_ref())
+    }
+
+    #[must_use]
+    pub fn get_action_bar(&self) -> Option<&ChatComponent> {
+        self.action_bar.as_ref()
+    }
+
+    /// Opacity of the title and subtitle from 0 to 1
+    #[must_use]
+    pub fn get_title_alpha(&self) -> f32 {
+        let t = self.title_time;
+        let alpha = if t < self.fade_in {
+            t / self.fade_in
+        } else if t < self.fade_in + self.stay {
+            1.0
+        } else if self.fade_out > 0.0 {
+            1.0 - (t - self.fade_in - self.stay) / self.fade_out
+        } else {
+            0.0
+        };
+        alpha.clamp(0.0, 1.0) as f32
+    }
+
+    /// Opacity of the action bar text from 0 to 1
+    #[must_use]
+    pub fn get_action_bar_alpha(&self) -> f32 {
+        ((ACTION_BAR_TIME - self.action_bar_time) / ACTION_BAR_FADE).clamp(0.0, 1.0) as f32
+    }
+}
+
+impl Default for Titles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
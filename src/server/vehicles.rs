@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::entities::Entity;
+
+/// Blocks per second a vehicle we're steering moves at
+pub const VEHICLE_SPEED: f64 = 6.0;
+/// How far below the top of their vehicle a player sits, the same as vanilla
+const PLAYER_RIDING_OFFSET: f64 = -0.35;
+
+/// Which entities are riding which
+#[derive(Debug, Default)]
+pub struct Passengers {
+    /// The vehicle each riding entity is on
+    vehicles: HashMap<i32, i32>,
+    /// Passengers of each vehicle in order, the first one steers it
+    passengers: HashMap<i32, Vec<i32>>,
+}
+
+impl Passengers {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace everyone riding `vehicle`, as in a Set Passengers packet
+    pub fn set_passengers(&mut self, vehicle: i32, passengers: Vec<i32>) {
+        if let Some(old) = self.passengers.remove(&vehicle) {
+            for passenger in old {
+                self.vehicles.remove(&passenger);
+            }
+        }
+        if passengers.is_empty() {
+            return;
+        }
+
+        for passenger in &passengers {
+            // Entities can only ride one thing at a time
+            if let Some(previous) = self.vehicles.insert(*passenger, vehicle) {
+                if let Some(others) = self.passengers.get_mut(&previous) {
+                    others.retain(|other| other != passenger);
+                }
+            }
+        }
+        self.passengers.insert(vehicle, passengers);
+    }
+
+    /// Forget an entity that has gone, both as a vehicle and as a passenger
+    pub fn remove_entity(&mut self, id: i32) {
+        self.set_passengers(id, Vec::new());
+        if let Some(vehicle) = self.vehicles.remove(&id) {
+            if let Some(passengers) = self.passengers.get_mut(&vehicle) {
+                passengers.retain(|passenger| *passenger != id);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vehicles.clear();
+        self.passengers.clear();
+    }
+
+    /// The vehicle an entity is riding, if any
+    #[must_use]
+    pub fn get_vehicle(&self, passenger: i32) -> Option<i32> {
+        self.vehicles.get(&passenger).copied()
+    }
+
+    #[must_use]
+    pub fn get_passengers(&self, vehicle: i32) -> &[i32] {
+        self.passengers.get(&vehicle).map_or(&[], Vec::as_slice)
+    }
+
+    /// If an entity is the one steering the vehicle it's riding
+    #[must_use]
+    pub fn is_controlling(&self, passenger: i32) -> bool {
+        self.get_vehicle(passenger)
+            .is_some_and(|vehicle| self.get_passengers(vehicle).first() == Some(&passenger))
+    }
+}
+
+/// Steering input for the vehicle the player is riding, collected fresh each frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Steering {
+    /// Positive to the left
+    pub sideways: f32,
+    pub forward: f32,
+    pub jump: bool,
+    pub unmount: bool,
+}
+
+/// Where a player riding `vehicle` sits, relative to the vehicle's position
+#[must_use]
+pub fn seat_height(vehicle: &Entity) -> f64 {
+    let height = vehicle
+        .get_type()
+        .map_or(0.0, |kind| f64::from(kind.height));
+    height * 0.75 + PLAYER_RIDING_OFFSET
+}
+
+/// Minecarts follow their rails, so only the server moves them
+#[must_use]
+pub fn is_steerable(vehicle: &Entity) -> bool {
+    vehicle
+        .get_type()
+        .is_some_and(|kind| !kind.name.contains("minecart"))
+}
@@ -0,0 +1,32 @@
+use glam::DVec3;
+
+/// How much of the sky's colour rain and thunder wash out at full strength
+const OVERCAST: f64 = 0.75;
+
+/// The rain and thunder the server has told us about
+#[derive(Debug, Default)]
+pub struct Weather {
+    pub raining: bool,
+    /// How heavily it's raining, from 0 to 1. Servers fade this in and out rather than jumping
+    pub rain_level: f32,
+    /// How stormy it is, from 0 to 1
+    pub thunder_level: f32,
+}
+
+impl Weather {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Darken and desaturate a sky colour for the current weather, the same way vanilla does
+    #[must_use]
+    pub fn apply_to_sky(&self, colour: DVec3) -> DVec3 {
+        let rain = f64::from(self.rain_level.clamp(0.0, 1.0)) * OVERCAST;
+        let thunder = f64::from(self.thunder_level.clamp(0.0, 1.0)) * OVERCAST;
+
+        let grey = DVec3::splat(colour.dot(DVec3::new(0.3, 0.59, 0.11)));
+        let colour = colour * (1.0 - rain) + grey * 0.6 * rain;
+        colour * (1.0 - thunder) + grey * 0.2 * thunder
+    }
+}
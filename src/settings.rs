@@ -1,29 +1,71 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use directories_next::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
 use thiserror::Error;
+use winit::keyboard::KeyCode;
+
+use crate::chat::DEFAULT_MAX_HISTORY;
+
+/// The current layout of the config file. Bump this and add a migration to `MIGRATIONS` whenever
+/// a field is renamed, moved or changes meaning.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upgrades a config file from the version at its index to the next one. Config files written
+/// before versioning are version 0.
+const MIGRATIONS: [fn(&mut Value); CONFIG_VERSION as usize] = [migrate_unversioned];
 
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct Settings {
+    pub version: u32,
+
     pub direct_connection: String,
     pub show_fps: bool,
+    pub show_hud: bool,
     pub vsync: bool,
+    /// How much bigger the gui is drawn than the window's scale
+    pub ui_scale: f32,
 
     pub window_pos: Option<[i32; 2]>,
     pub window_size: [u32; 2],
 
     pub mouse_sensitivity: f64,
+    /// Sneak and sprint stay on after pressing their key once, rather than needing it held
+    pub toggle_sneak: bool,
+    pub toggle_sprint: bool,
     pub fov: f64,
+    pub keybinds: KeyBindings,
 
     pub online_play: bool,
     pub name: String,
     pub saved_servers: Vec<SavedServer>,
+    /// Write the chat from each server to a log file in the config directory
+    pub log_chat: bool,
+    /// How many received chat messages are kept to scroll back through
+    pub chat_history: usize,
+    /// Show when each message arrived in the open chat
+    pub chat_timestamps: bool,
+    /// Highlight messages with the player's name or one of `mention_keywords` in them
+    pub highlight_mentions: bool,
+    /// Play a sound and ask for the window to be noticed when mentioned
+    pub mention_ping: bool,
+    pub mention_keywords: Vec<String>,
+    /// Players whose chat messages are hidden
+    pub ignored_players: Vec<String>,
 
     pub day_colour: [f32; 3],
     pub fog_near: f32,
     pub fog_far: f32,
+
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub block_volume: f32,
+    pub hostile_volume: f32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
@@ -33,6 +75,123 @@ pub struct SavedServer {
     pub name: String,
 }
 
+/// Things the player can do that can be bound to a key
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Jump,
+    Sneak,
+    Sprint,
+    OpenChat,
+    OpenCommand,
+    TogglePerspective,
+    PlayerList,
+    Pause,
+    ToggleNoclip,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Forward,
+        Action::Back,
+        Action::Left,
+        Action::Right,
+        Action::Jump,
+        Action::Sneak,
+        Action::Sprint,
+        Action::OpenChat,
+        Action::OpenCommand,
+        Action::TogglePerspective,
+        Action::PlayerList,
+        Action::Pause,
+        Action::ToggleNoclip,
+    ];
+
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            Action::Forward => KeyCode::KeyW,
+            Action::Back => KeyCode::KeyS,
+            Action::Left => KeyCode::KeyA,
+            Action::Right => KeyCode::KeyD,
+            Action::Jump => KeyCode::Space,
+            Action::Sneak => KeyCode::ShiftLeft,
+            Action::Sprint => KeyCode::ControlLeft,
+            Action::OpenChat => KeyCode::KeyT,
+            Action::OpenCommand => KeyCode::Slash,
+            Action::TogglePerspective => KeyCode::F5,
+            Action::PlayerList => KeyCode::Tab,
+            Action::Pause => KeyCode::Escape,
+            Action::ToggleNoclip => KeyCode::F4,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Forward => "Forward",
+            Action::Back => "Back",
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Jump => "Jump",
+            Action::Sneak => "Sneak",
+            Action::Sprint => "Sprint",
+            Action::OpenChat => "Open chat",
+            Action::OpenCommand => "Open command",
+            Action::TogglePerspective => "Toggle perspective",
+            Action::PlayerList => "Player list",
+            Action::Pause => "Pause",
+            Action::ToggleNoclip => "Toggle noclip",
+        }
+    }
+}
+
+/// The key each action is bound to. Saved as a map from action to key, where actions missing
+/// from the config use their default key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct KeyBindings {
+    keys: HashMap<Action, KeyCode>,
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> KeyCode {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    pub fn set(&mut self, action: Action, key: KeyCode) {
+        self.keys.insert(action, key);
+    }
+
+    /// Put every action back on its default key
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// If another action is bound to the same key as `action`
+    pub fn has_conflict(&self, action: Action) -> bool {
+        let key = self.get(action);
+        Action::ALL
+            .iter()
+            .any(|other| *other != action && self.get(*other) == key)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("IO error: {0}")]
@@ -46,39 +205,122 @@ pub enum Error {
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
+
             direct_connection: String::new(),
             show_fps: true,
+            show_hud: true,
             vsync: true,
+            ui_scale: 1.0,
 
             window_pos: None,
             window_size: [1200, 700],
 
             mouse_sensitivity: 1.0,
+            toggle_sneak: false,
+            toggle_sprint: false,
             fov: 90.0,
+            keybinds: KeyBindings::default(),
 
             online_play: false,
 
             name: String::from("Bash"),
             saved_servers: Vec::new(),
+            log_chat: false,
+            chat_history: DEFAULT_MAX_HISTORY,
+            chat_timestamps: false,
+            highlight_mentions: true,
+            mention_ping: true,
+            mention_keywords: Vec::new(),
+            ignored_players: Vec::new(),
 
             day_colour: [0.3, 0.6, 0.9],
             fog_near: 5.0,
             fog_far: 320.0,
+
+            master_volume: 1.0,
+            music_volume: 1.0,
+            block_volume: 1.0,
+            hostile_volume: 1.0,
         }
     }
 }
 
 impl Settings {
+    /// Load settings from a file, migrating them from older versions of the config and writing
+    /// the upgraded file back if needed. If the file is corrupted the backup of the last config
+    /// that loaded is used instead.
     pub fn load_from<P: AsRef<Path>>(file: P) -> Result<Settings, Error> {
+        let file = file.as_ref();
+        let backup = with_suffix(file, ".bak");
         let contents = std::fs::read_to_string(file)?;
-        let settings = serde_yaml::from_str(&contents)?;
+
+        let (settings, rewrite) = match Self::parse(&contents) {
+            Ok((settings, migrated)) => {
+                // Keep a copy of the last config that loaded to recover from
+                if let Err(e) = std::fs::write(&backup, &contents) {
+                    tracing::warn!("Couldn't back up config: {e}");
+                }
+                (settings, migrated)
+            }
+            Err(e) => {
+                tracing::error!("Couldn't parse config ({e}), trying the backup");
+                let contents = std::fs::read_to_string(&backup).map_err(|_| e)?;
+                let (settings, _) = Self::parse(&contents)?;
+                tracing::info!("Recovered config from backup");
+                (settings, true)
+            }
+        };
+
+        if rewrite {
+            if let Err(e) = settings.save_to(file) {
+                tracing::warn!("Couldn't save upgraded config: {e}");
+            }
+        }
 
         Ok(settings)
     }
 
+    /// Parse a config file's contents, returning the settings and whether they had to be migrated
+    /// from an older version
+    fn parse(contents: &str) -> Result<(Settings, bool), Error> {
+        let mut value: Value = serde_yaml::from_str(contents)?;
+
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .map_or(0, |v| u32::try_from(v).unwrap_or(u32::MAX));
+        if version > CONFIG_VERSION {
+            tracing::warn!(
+                "Config is from a newer version ({version}, expected {CONFIG_VERSION}), some \
+                 settings may be lost"
+            );
+        }
+
+        for migration in MIGRATIONS.iter().skip(version as usize) {
+            migration(&mut value);
+        }
+        if let Value::Mapping(map) = &mut value {
+            map.insert("version".into(), CONFIG_VERSION.into());
+        }
+        let settings: Settings = serde_yaml::from_value(value)?;
+
+        if version < CONFIG_VERSION {
+            tracing::info!("Migrated config from version {version} to {CONFIG_VERSION}");
+        }
+
+        Ok((settings, version < CONFIG_VERSION))
+    }
+
+    /// Write the settings to a temporary file first and then move it over the real one, so a
+    /// crash part way through can't leave a half written config behind
     pub fn save_to<P: AsRef<Path>>(&self, file: P) -> Result<(), Error> {
+        let file = file.as_ref();
+        let temp = with_suffix(file, ".tmp");
+
         let contents = serde_yaml::to_string(self)?;
-        std::fs::write(file, contents)?;
+        std::fs::write(&temp, contents)?;
+        std::fs::rename(&temp, file)?;
 
         Ok(())
     }
@@ -92,8 +334,23 @@ impl Settings {
         let path = locate_config_directory()?.join("config.yaml");
         self.save_to(path)
     }
+
+    /// The key bound to an action, falling back to the default if it's missing from the config
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keybinds.get(action)
+    }
 }
 
+/// `path` with `suffix` added to the end of its file name, e.g. `config.yaml.bak`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Version 1 is the layout from before versioning, so there's nothing to change
+fn migrate_unversioned(_config: &mut Value) {}
+
 pub fn locate_config_directory() -> Result<PathBuf, Error> {
     let dirs = ProjectDirs::from("mink-raft", "bash", "mink-raft").ok_or(Error::NoValidHome)?;
     let dir = dirs.config_dir();
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use glam::DVec3;
+use rodio::{Decoder, OutputStream, Sink};
+
+use crate::settings::Settings;
+
+/// Directory sound files are loaded from, named after the sound event e.g. `block.stone.break.ogg`
+pub const SOUNDS_DIR: &str = "assets/sounds/";
+/// Extensions tried, in order, when loading a sound file
+const SOUND_EXTENSIONS: [&str; 2] = ["ogg", "wav"];
+
+/// Common sound events and the files they're found in under `SOUNDS_DIR`, laid out like the
+/// game's own sounds. Events not listed here are loaded from a file named after the event.
+const SOUND_FILES: [(&str, &str); 15] = [
+    ("block.chest.close", "random/chestclosed"),
+    ("block.chest.open", "random/chestopen"),
+    ("block.note_block.basedrum", "note/bd"),
+    ("block.note_block.bass", "note/bass"),
+    ("block.note_block.harp", "note/harp"),
+    ("block.note_block.hat", "note/hat"),
+    ("block.note_block.pling", "note/pling"),
+    ("block.note_block.snare", "note/snare"),
+    ("entity.arrow.hit", "random/bowhit1"),
+    ("entity.arrow.shoot", "random/bow"),
+    ("entity.experience_orb.pickup", "random/orb"),
+    ("entity.generic.explode", "random/explode1"),
+    ("entity.item.pickup", "random/pop"),
+    ("entity.player.levelup", "random/levelup"),
+    ("ui.button.click", "random/click"),
+];
+
+/// Distance in blocks a sound of volume 1.0 can be heard from
+const BASE_SOUND_RANGE: f64 = 16.0;
+
+/// The volume slider a sound is controlled by, on top of the master volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCategory {
+    Music,
+    Blocks,
+    Hostile,
+    Other,
+}
+
+impl SoundCategory {
+    #[must_use]
+    pub fn volume(self, settings: &Settings) -> f32 {
+        match self {
+            Self::Music => settings.music_volume,
+            Self::Blocks => settings.block_volume,
+            Self::Hostile => settings.hostile_volume,
+            Self::Other => 1.0,
+        }
+    }
+}
+
+/// A sound played somewhere in the world
+pub struct SoundEvent {
+    /// Name of the sound event, e.g. `minecraft:block.stone.break`
+    pub name: String,
+    pub category: SoundCategory,
+    pub position: DVec3,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+enum SoundCommand {
+    Play {
+        name: String,
+        volume: f32,
+        pitch: f32,
+    },
+    Shutdown,
+}
+
+/// Plays sounds on a dedicated audio thread, which is stopped when this is dropped
+pub struct SoundManager {
+    send: Sender<SoundCommand>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SoundManager {
+    #[must_use]
+    pub fn new() -> Self {
+        let (send, recv) = mpsc::channel();
+
+        let thread = std::thread::Builder::new()
+            .name(String::from("Audio"))
+            .spawn(move || run_audio_thread(&recv))
+            .map_err(|e| tracing::error!("Couldn't start audio thread: {e}"))
+            .ok();
+
+        Self { send, thread }
+    }
+
+    /// Play a sound heard by a listener at `listener`, fading out with distance like vanilla does
+    pub fn play_at(&self, sound: &SoundEvent, listener: DVec3, settings: &Settings) {
+        // Louder sounds carry further rather than getting louder
+        let range = BASE_SOUND_RANGE * f64::from(sound.volume.max(1.0));
+        let attenuation = (1.0 - sound.position.distance(listener) / range).max(0.0) as f32;
+
+        let volume = sound.volume.min(1.0)
+            * attenuation
+            * settings.master_volume
+            * sound.category.volume(settings);
+        if volume <= 0.0 {
+            return;
+        }
+
+        let name = sound.name.strip_prefix("minecraft:").unwrap_or(&sound.name);
+
+        // The audio thread only goes away if it couldn't open an output device
+        self.send
+            .send(SoundCommand::Play {
+                name: name.to_string(),
+                volume,
+                pitch: sound.pitch,
+            })
+            .ok();
+    }
+}
+
+impl Default for SoundManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SoundManager {
+    fn drop(&mut self) {
+        self.send.send(SoundCommand::Shutdown).ok();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                tracing::error!("Audio thread panicked");
+            }
+        }
+    }
+}
+
+fn run_audio_thread(recv: &Receiver<SoundCommand>) {
+    // The output stream has to stay alive for as long as sounds are playing
+    let (_stream, handle) = match OutputStream::try_default() {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::error!("Couldn't open audio output, sound is disabled: {e}");
+            return;
+        }
+    };
+
+    // Files are read the first time each sound is played, missing and broken ones are remembered
+    // as None so they're only reported once
+    let mut sounds: HashMap<String, Option<Arc<[u8]>>> = HashMap::new();
+    let mut sinks: Vec<Sink> = Vec::new();
+
+    while let Ok(SoundCommand::Play {
+        name,
+        volume,
+        pitch,
+    }) = recv.recv()
+    {
+        sinks.retain(|sink| !sink.empty());
+
+        let cached = sounds
+            .entry(name)
+            .or_insert_with_key(|name| load_sound(name));
+        let Some(data) = cached else {
+            continue;
+        };
+
+        let source = match Decoder::new(Cursor::new(data.clone())) {
+            Ok(source) => source,
+            Err(e) => {
+                tracing::error!("Couldn't decode sound: {e}");
+                *cached = None;
+                continue;
+            }
+        };
+        let sink = match Sink::try_new(&handle) {
+            Ok(sink) => sink,
+            Err(e) => {
+                tracing::error!("Couldn't create audio sink: {e}");
+                continue;
+            }
+        };
+        sink.set_volume(volume);
+        sink.set_speed(pitch);
+        sink.append(source);
+        sinks.push(sink);
+    }
+
+    tracing::debug!("Audio thread shutting down");
+}
+
+/// Read the file for a sound event. Plenty of sounds won't have files, so they're skipped quietly.
+fn load_sound(name: &str) -> Option<Arc<[u8]>> {
+    let file = SOUND_FILES
+        .iter()
+        .find(|(event, _)| *event == name)
+        .map_or(name, |(_, file)| *file);
+
+    let data = SOUND_EXTENSIONS
+        .iter()
+        .find_map(|ext| std::fs::read(format!("{SOUNDS_DIR}{file}.{ext}")).ok());
+    if data.is_none() {
+        tracing::debug!("No sound file for {name}");
+    }
+    data.map(Arc::from)
+}
@@ -1,14 +1,29 @@
-use std::{collections::HashMap, convert::TryInto};
+use std::{cmp::Reverse, collections::HashMap};
 
 use glam::{DVec3, IVec2, IVec3, Vec3Swizzles};
-use mcproto_rs::v1_16_3::{PlayBlockChangeSpec, PlayMultiBlockChangeSpec};
-
-use crate::resources::{blocks, BlockState};
-
-use self::chunks::{block_pos_to_index, BlockIndex, Chunk, ChunkSection};
-
+use mcproto_rs::v1_16_3::{
+    ChunkData, PlayBlockChangeSpec, PlayMultiBlockChangeSpec, PlayUpdateLightSpec,
+};
+
+use crate::{
+    renderer::BlockVertex,
+    resources::{biomes::PLAINS_ID, blocks, BlockState},
+};
+
+use self::{
+    border::WorldBorder,
+    chunks::{BlockIndex, Chunk, ChunkError, ChunkSection, MAX_SECTION, MIN_SECTION},
+    light::{ChunkLight, MAX_LIGHT},
+    mesher::{MeshJob, Mesher},
+};
+
+pub mod border;
 pub mod chunk_builder;
 pub mod chunks;
+pub mod coords;
+pub mod light;
+pub mod mesher;
+pub mod raycast;
 
 /// Block position in the world
 pub type WorldCoords = IVec3;
@@ -21,6 +36,47 @@ pub type ChunkLocation = IVec2;
 /// Section position in world (SectionLocation(1,1,1) == WorldCoords(16,16,16))
 pub type SectionLocation = IVec3;
 
+/// The server sends chunks one further than the view distance it tells us about
+const VIEW_DISTANCE_MARGIN: i32 = 1;
+
+/// The kind of dimension the player is in, which decides how the sky and fog look
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+    End,
+}
+
+impl Dimension {
+    /// Get the kind of dimension from its identifier, e.g. `minecraft:the_nether`. Unknown
+    /// dimensions from custom worlds are treated like the overworld
+    pub fn from_identifier(identifier: &str) -> Dimension {
+        match identifier {
+            "minecraft:the_nether" => Dimension::Nether,
+            "minecraft:the_end" => Dimension::End,
+            _ => Dimension::Overworld,
+        }
+    }
+
+    /// A fixed sky colour for dimensions without a day/night cycle
+    pub fn fixed_sky_colour(&self) -> Option<DVec3> {
+        match self {
+            Dimension::Overworld => None,
+            Dimension::Nether => Some(DVec3::new(0.2, 0.03, 0.03)),
+            Dimension::End => Some(DVec3::new(0.04, 0.03, 0.06)),
+        }
+    }
+
+    /// How far the fog reaches compared to the overworld
+    pub fn fog_scale(&self) -> f32 {
+        match self {
+            Dimension::Overworld => 1.0,
+            Dimension::Nether => 0.25,
+            Dimension::End => 0.75,
+        }
+    }
+}
+
 trait Directional {
     fn north() -> Self;
     fn south() -> Self;
@@ -30,9 +86,17 @@ trait Directional {
 
 pub struct World {
     chunks: HashMap<IVec2, Chunk>,
+    /// Kept apart from the chunks as some servers send light before the chunk it's for
+    light: HashMap<ChunkLocation, ChunkLight>,
     chunks_to_generate: Vec<ChunkLocation>,
     sections_to_generate: Vec<SectionLocation>,
-    // builder: ChunkBuilder,
+    mesher: Mesher,
+
+    /// Unknown until the server first tells us
+    view_center: Option<ChunkLocation>,
+    view_distance: i32,
+
+    border: WorldBorder,
 }
 
 impl World {
@@ -40,12 +104,150 @@ impl World {
     pub fn new() -> Self {
         Self {
             chunks: HashMap::new(),
+            light: HashMap::new(),
             chunks_to_generate: Vec::new(),
             sections_to_generate: Vec::new(),
-            // builder: ChunkBuilder::new(),
+            mesher: Mesher::new(),
+
+            view_center: None,
+            view_distance: 8,
+
+            border: WorldBorder::new(),
         }
     }
 
+    pub fn border(&self) -> &WorldBorder {
+        &self.border
+    }
+
+    pub fn border_mut(&mut self) -> &mut WorldBorder {
+        &mut self.border
+    }
+
+    pub fn get_view_center(&self) -> Option<&ChunkLocation> {
+        self.view_center.as_ref()
+    }
+
+    pub fn get_view_distance(&self) -> i32 {
+        self.view_distance
+    }
+
+    /// Move the centre of the area the server keeps loaded, dropping chunks that are now too far
+    /// away
+    pub fn set_view_center(&mut self, center: ChunkLocation) {
+        self.view_center = Some(center);
+        self.trim_to_view(center, self.view_distance + VIEW_DISTANCE_MARGIN);
+    }
+
+    /// Change the radius of the area the server keeps loaded, dropping chunks that are now too far
+    /// away
+    pub fn set_view_distance(&mut self, distance: i32) {
+        self.view_distance = distance;
+        if let Some(center) = self.view_center {
+            self.trim_to_view(center, distance + VIEW_DISTANCE_MARGIN);
+        }
+    }
+
+    /// Returns true if the chunk is within `distance` chunks of `center` on both axes
+    fn is_in_view(location: &ChunkLocation, center: ChunkLocation, distance: i32) -> bool {
+        let offset = (*location - center).abs();
+        offset.x <= distance && offset.y <= distance
+    }
+
+    /// Drop any chunks further than `distance` chunks away from `center`, for servers that don't
+    /// explicitly unload chunks when they leave the player's view
+    pub fn trim_to_view(&mut self, center: ChunkLocation, distance: i32) {
+        let before = self.chunks.len();
+        self.chunks
+            .retain(|loc, _| Self::is_in_view(loc, center, distance));
+        self.light
+            .retain(|loc, _| Self::is_in_view(loc, center, distance));
+        self.chunks_to_generate
+            .retain(|loc| Self::is_in_view(loc, center, distance));
+        self.sections_to_generate
+            .retain(|loc| Self::is_in_view(&loc.xz(), center, distance));
+        self.mesher
+            .retain(|loc| Self::is_in_view(&loc.xz(), center, distance));
+
+        let dropped = before - self.chunks.len();
+        if dropped > 0 {
+            tracing::debug!("Dropped {dropped} chunks outside of view");
+        }
+    }
+
+    /// Returns the number of loaded chunks inside the current view distance
+    pub fn count_chunks_in_view(&self) -> usize {
+        let Some(center) = self.view_center else {
+            return self.chunks.len();
+        };
+        let distance = self.view_distance + VIEW_DISTANCE_MARGIN;
+        self.chunks
+            .keys()
+            .filter(|loc| Self::is_in_view(loc, center, distance))
+            .count()
+    }
+
+    /// Load chunk data from the server. Full chunks replace whatever was there, others only update
+    /// the sections they contain in a chunk that's already loaded
+    ///
+    /// # Errors
+    /// If the chunk data is malformed
+    pub fn load_chunk(&mut self, data: &ChunkData) -> Result<(), ChunkError> {
+        if data.biomes.is_some() {
+            self.insert_chunk(Chunk::new(data)?);
+            return Ok(());
+        }
+
+        let location = ChunkLocation::new(data.position.x, data.position.z);
+        match self.get_chunk_mut(&location) {
+            Some(chunk) => {
+                chunk.merge(data)?;
+                self.queue_chunk_mesh(location);
+            }
+            None => tracing::warn!("Got partial chunk data for unloaded chunk {location}"),
+        }
+        Ok(())
+    }
+
+    /// Drop a chunk and its light, along with any meshes of it still being generated
+    pub fn unload_chunk(&mut self, location: &ChunkLocation) {
+        self.chunks.remove(location);
+        self.light.remove(location);
+        self.mesher.retain(|loc| loc.xz() != *location);
+    }
+
+    /// Store light sent by the server, whether or not its chunk has been loaded yet
+    pub fn handle_update_light(&mut self, pack: &PlayUpdateLightSpec) {
+        let location = ChunkLocation::new(pack.chunk.x.0, pack.chunk.z.0);
+        if let Some(center) = self.view_center {
+            if !Self::is_in_view(&location, center, self.view_distance + VIEW_DISTANCE_MARGIN) {
+                tracing::debug!("Ignoring light for chunk {location} outside of view");
+                return;
+            }
+        }
+
+        self.light
+            .entry(location)
+            .or_default()
+            .update(&pack.update.skylight_data, &pack.update.blocklight_data);
+    }
+
+    /// Light from the sky at a block. Everywhere without light data is fully lit
+    pub fn get_sky_light(&self, coords: WorldCoords) -> u8 {
+        self.light
+            .get(&coords::chunk_location(&coords))
+            .map_or(MAX_LIGHT, |l| {
+                l.get_sky_light(&coords::world_to_chunk(&coords))
+            })
+    }
+
+    /// Light from blocks like torches at a block
+    pub fn get_block_light(&self, coords: WorldCoords) -> u8 {
+        self.light
+            .get(&coords::chunk_location(&coords))
+            .map_or(0, |l| l.get_block_light(&coords::world_to_chunk(&coords)))
+    }
+
     pub fn insert_chunk(&mut self, chunk: Chunk) {
         let chunk_coords = *chunk.get_coords();
         self.chunks.insert(chunk_coords, chunk);
@@ -75,138 +277,98 @@ impl World {
         chunk.is_some() && north.is_some() && east.is_some() && south.is_some() && west.is_some()
     }
 
-    /*
-    fn generate_section_mesh(&mut self, loc: &SectionLocation, dis: &Display, threaded: bool) {
-        let chunk = self.get_section(loc);
-        // Discard chunk sections that are empty
-        if chunk.is_none() {
-            return;
-        }
-        let chunk = chunk.unwrap();
-
-        // Can unwrap because we checked above that they are all valid
-        let north = self.get_section(&SectionLocation::new(loc.x, loc.y, loc.z - 1));
-        let east = self.get_section(&SectionLocation::new(loc.x + 1, loc.y, loc.z));
-        let south = self.get_section(&SectionLocation::new(loc.x, loc.y, loc.z + 1));
-        let west = self.get_section(&SectionLocation::new(loc.x - 1, loc.y, loc.z));
-        let above = if loc.y >= MAX_SECTION {
-            None
-        } else {
-            self.get_section(&SectionLocation::new(loc.x, loc.y + 1, loc.z))
-        };
-        let below = if loc.y <= MIN_SECTION {
-            None
-        } else {
-            self.get_section(&SectionLocation::new(loc.x, loc.y - 1, loc.z))
-        };
+    /// Copy a chunk section and its neighbours to be meshed on another thread, or None if the
+    /// section is empty
+    fn mesh_job(&self, loc: &SectionLocation) -> Option<MeshJob> {
+        let neighbour = |offset: IVec3| self.get_section(&(*loc + offset)).cloned();
 
-        self.builder.generate_chunk_section(
-            chunk,
-            loc.clone(),
-            above,
-            below,
-            north,
-            east,
-            south,
-            west,
-            threaded,
-        );
+        Some(MeshJob {
+            location: *loc,
+            section: self.get_section(loc)?.clone(),
+            above: if loc.y < MAX_SECTION {
+                neighbour(IVec3::Y)
+            } else {
+                None
+            },
+            below: if loc.y > MIN_SECTION {
+                neighbour(-IVec3::Y)
+            } else {
+                None
+            },
+            north: neighbour(IVec3::north()),
+            east: neighbour(IVec3::east()),
+            south: neighbour(IVec3::south()),
+            west: neighbour(IVec3::west()),
+        })
     }
 
-    pub fn generate_meshes(&mut self, dis: &Display, threaded: bool) {
+    /// Starts meshing any queued chunks and chunk sections that have all their neighbouring chunks
+    /// loaded, nearest to `center` first, and returns the world space vertices of each section
+    /// that has finished meshing since the last call.
+    pub fn generate_meshes(
+        &mut self,
+        center: SectionLocation,
+    ) -> Vec<(SectionLocation, Vec<BlockVertex>)> {
         // Chunks
-        let mut temp = Vec::new();
-        std::mem::swap(&mut self.chunks_to_generate, &mut temp);
-        let ready_chunks: Vec<_> = temp
-            .drain_filter(|loc| self.are_chunk_neighbours_loaded(loc))
-            .collect();
-        std::mem::swap(&mut self.chunks_to_generate, &mut temp);
-
-        for loc in ready_chunks {
-            for y in MIN_SECTION..=MAX_SECTION {
-                self.generate_section_mesh(&SectionLocation::new(loc.x, y, loc.y), dis, threaded);
-                self.builder.generate_chunk(
-                    self.get_chunk(&loc).unwrap(),
-                    self.get_chunk(&(loc + IVec2::north())).unwrap(),
-                    self.get_chunk(&(loc + IVec2::east())).unwrap(),
-                    self.get_chunk(&(loc + IVec2::south())).unwrap(),
-                    self.get_chunk(&(loc + IVec2::west())).unwrap(),
-                    threaded,
-                )
+        let mut ready_sections = Vec::new();
+        let mut chunks = std::mem::take(&mut self.chunks_to_generate);
+        chunks.retain(|loc| {
+            // Discard chunks that were unloaded while waiting
+            if !self.is_chunk_loaded(loc) {
+                return false;
             }
-        }
-
-        // Chunk sections
-        let mut temp = Vec::new();
-        std::mem::swap(&mut temp, &mut self.sections_to_generate);
-        temp.retain(|loc| {
             // Retain chunks that don't have all their neighbouring chunks
-            if !self.are_chunk_neighbours_loaded(&ChunkLocation::new(loc.x, loc.z)) {
+            if !self.are_chunk_neighbours_loaded(loc) {
                 return true;
             }
 
-            // Discard chunk sections that are empty
-            if self.get_section(loc).is_none() {
-                return false;
+            for y in MIN_SECTION..=MAX_SECTION {
+                ready_sections.push(SectionLocation::new(loc.x, y, loc.y));
             }
-
-            let sect = self.get_section(loc).unwrap();
-            let above = if loc.y < MAX_SECTION {
-                self.get_section(&(*loc + IVec3::Y))
-            } else {
-                None
-            };
-            let below = if loc.y > MIN_SECTION {
-                self.get_section(&(*loc - IVec3::Y))
-            } else {
-                None
-            };
-            let north = self.get_section(&(*loc + IVec3::north()));
-            let east = self.get_section(&(*loc + IVec3::east()));
-            let south = self.get_section(&(*loc + IVec3::south()));
-            let west = self.get_section(&(*loc + IVec3::west()));
-
-            self.builder.generate_chunk_section(
-                sect,
-                loc.clone(),
-                above,
-                below,
-                north,
-                east,
-                south,
-                west,
-                threaded,
-            );
-
             false
         });
-        std::mem::swap(&mut temp, &mut self.sections_to_generate);
-
-        // Load ready meshes
-        let incoming = self.builder.get_incoming_meshes();
-        let mut new_meshes = Vec::new();
-        let mut i = 0;
-        loop {
-            match incoming.try_recv() {
-                Ok(a) => new_meshes.push(a),
-                Err(TryRecvError::Empty) => break,
-                Err(e) => panic!("Lost chunk builder thread: {}", e),
-            }
+        self.chunks_to_generate = chunks;
 
-            // Limit to 16 chunk sections per frame otherwise it can still tank frames from
-            // uploading entire new chunks to the GPU at once
-            if i >= 16 {
-                break;
-            }
-            i += 1;
+        for loc in ready_sections {
+            self.queue_chunk_section_mesh(loc);
         }
 
-        for (loc, verts) in new_meshes {
-            self.get_chunk_mut(&loc.xz())
-                .map(|c| c.load_mesh(dis, verts, loc.y));
+        // Chunk sections, sorted so the nearest are popped first
+        let mut meshes = Vec::new();
+        let mut waiting = Vec::new();
+        let mut sections = std::mem::take(&mut self.sections_to_generate);
+        sections.sort_unstable_by_key(|loc| Reverse((*loc - center).length_squared()));
+        while let Some(loc) = sections.pop() {
+            // Discard sections that were unloaded while waiting
+            if !self.is_chunk_loaded(&loc.xz()) {
+                continue;
+            }
+            // Leave the rest until the workers catch up, by which time the player may have moved
+            if !self.mesher.has_capacity() || !self.are_chunk_neighbours_loaded(&loc.xz()) {
+                waiting.push(loc);
+                continue;
+            }
+
+            match self.mesh_job(&loc) {
+                Some(job) => self.mesher.queue(job),
+                // Nothing to mesh, but this still replaces any old mesh
+                None => {
+                    self.mesher.retain(|l| *l != loc);
+                    meshes.push((loc, Vec::new()));
+                }
+            }
         }
+        self.sections_to_generate = waiting;
+
+        // Meshes of chunks unloaded since they were queued are thrown away
+        meshes.extend(
+            self.mesher
+                .finished()
+                .into_iter()
+                .filter(|(loc, _)| self.is_chunk_loaded(&loc.xz())),
+        );
+        meshes
     }
-    */
 
     pub fn get_chunks(&self) -> &HashMap<IVec2, Chunk> {
         &self.chunks
@@ -268,6 +430,20 @@ impl World {
         self.chunks.get(location).is_some()
     }
 
+    /// Get the block state id at a position in the world, or None if its chunk isn't loaded.
+    /// Above and below the world is air
+    pub fn get_block(&self, coords: WorldCoords) -> Option<u32> {
+        let local = coords::world_to_chunk(&coords);
+        self.get_chunk(&coords::chunk_location(&coords))
+            .map(|c| c.get_block(local.x, local.y, local.z))
+    }
+
+    /// The biome id at a position in the world, plains if its chunk isn't loaded
+    pub fn get_biome(&self, coords: WorldCoords) -> u32 {
+        self.get_chunk(&coords::chunk_location(&coords))
+            .map_or(PLAINS_ID, |c| c.get_biome(&coords::world_to_chunk(&coords)))
+    }
+
     pub fn block_at(&self, coords: &WorldCoords) -> Option<&BlockState> {
         self.chunks
             .get(&Chunk::chunk_containing(coords))
@@ -282,74 +458,31 @@ impl World {
         }
 
         let coords = IVec3::new(pack.location.x, pack.location.y.into(), pack.location.z);
-        let section_loc = ChunkSection::section_containing(&coords);
-        let mut sections_to_regenerate = Vec::new();
-
-        if let Some(chunk) = self.get_chunk_containing_mut(&coords) {
-            if !chunk.is_section_present(section_loc.y) {
-                chunk.put_section(ChunkSection {
-                    y: ChunkSection::section_containing_height(section_loc.y),
-                    blocks: [0; 4096],
-                });
-            }
+        self.set_block(&coords, pack.block_id.0 as BlockIndex);
+    }
 
-            let section = chunk
-                .get_section_mut(section_loc.y)
-                .expect("Couldn't get chunk section that was just emplaced in the Chunk");
-            let local_coords = ChunkSection::map_from_world_coords(&coords);
-
-            section.blocks[block_pos_to_index(&local_coords)] = pack.block_id.0 as BlockIndex;
-            sections_to_regenerate.push(section_loc);
-
-            // Regenerate neighbouring chunks if necessary
-            if local_coords.x == 0 {
-                sections_to_regenerate.push(IVec3::new(
-                    section_loc.x - 1,
-                    section_loc.y,
-                    section_loc.z,
-                ));
-            }
-            if local_coords.x == 15 {
-                sections_to_regenerate.push(IVec3::new(
-                    section_loc.x + 1,
-                    section_loc.y,
-                    section_loc.z,
-                ));
-            }
-            if local_coords.y == 0 {
-                sections_to_regenerate.push(IVec3::new(
-                    section_loc.x,
-                    section_loc.y - 1,
-                    section_loc.z,
-                ));
-            }
-            if local_coords.y == 15 {
-                sections_to_regenerate.push(IVec3::new(
-                    section_loc.x,
-                    section_loc.y + 1,
-                    section_loc.z,
-                ));
-            }
-            if local_coords.z == 0 {
-                sections_to_regenerate.push(IVec3::new(
-                    section_loc.x,
-                    section_loc.y,
-                    section_loc.z - 1,
-                ));
-            }
-            if local_coords.z == 15 {
-                sections_to_regenerate.push(IVec3::new(
-                    section_loc.x,
-                    section_loc.y,
-                    section_loc.z + 1,
-                ));
-            }
-        } else {
+    /// Change a single block and remesh the sections that can see it
+    pub fn set_block(&mut self, coords: &WorldCoords, block: BlockIndex) {
+        let Some(chunk) = self.get_chunk_containing_mut(coords) else {
             tracing::warn!("Block change in unloaded chunk");
+            return;
+        };
+        if !chunk.set_block(&coords::world_to_chunk(coords), block) {
+            tracing::warn!("Block change outside of the world at {coords}");
+            return;
         }
 
-        for coords in sections_to_regenerate {
-            self.queue_chunk_section_mesh(coords);
+        // Blocks on the edge of a section can be seen from the neighbouring section too
+        let section_loc = coords::section_location(coords);
+        let local_coords = coords::world_to_section(coords);
+        self.queue_chunk_section_mesh(section_loc);
+        for axis in IVec3::AXES {
+            let along = local_coords.dot(axis);
+            if along == 0 {
+                self.queue_chunk_section_mesh(section_loc - axis);
+            } else if along == coords::SECTION_SIZE - 1 {
+                self.queue_chunk_section_mesh(section_loc + axis);
+            }
         }
     }
 
@@ -361,22 +494,14 @@ impl World {
             return;
         }
 
-        // Create new chunk section if this one was empty
-        if !self.is_section_present(&loc) {
-            self.get_chunk_mut(&loc.xz())
-                .unwrap()
-                .put_section(ChunkSection {
-                    y: loc.y,
-                    blocks: [0; 4096],
-                })
-        }
-
-        self.queue_chunk_section_mesh(loc);
         for change in pack.blocks.iter() {
-            if change.block_id >= blocks().len().try_into().unwrap() {
-                tracing::error!("Got block change with invalid block ID");
-                continue;
-            }
+            let block = match BlockIndex::try_from(change.block_id) {
+                Ok(block) if usize::from(block) < blocks().len() => block,
+                _ => {
+                    tracing::error!("Got block change with invalid block ID");
+                    continue;
+                }
+            };
 
             // For some reason the y and z coordinates are the wrong way around??
             let local_pos = IVec3::new(
@@ -385,29 +510,7 @@ impl World {
                 change.rel_position.y.into(),
             );
 
-            self.get_section_mut(&loc).unwrap().blocks[block_pos_to_index(&local_pos)] =
-                change.block_id.try_into().unwrap();
-
-            // Update adjacent chunk sections
-            self.queue_chunk_section_mesh(loc);
-            if local_pos.x == 0 {
-                self.queue_chunk_section_mesh(IVec3::new(loc.x - 1, loc.y, loc.z));
-            }
-            if local_pos.x == 15 {
-                self.queue_chunk_section_mesh(IVec3::new(loc.x + 1, loc.y, loc.z));
-            }
-            if local_pos.y == 0 {
-                self.queue_chunk_section_mesh(IVec3::new(loc.x, loc.y - 1, loc.z));
-            }
-            if local_pos.y == 15 {
-                self.queue_chunk_section_mesh(IVec3::new(loc.x, loc.y + 1, loc.z));
-            }
-            if local_pos.z == 0 {
-                self.queue_chunk_section_mesh(IVec3::new(loc.x, loc.y, loc.z - 1));
-            }
-            if local_pos.z == 15 {
-                self.queue_chunk_section_mesh(IVec3::new(loc.x, loc.y, loc.z + 1));
-            }
+            self.set_block(&coords::section_to_world(&loc, &local_pos), block);
         }
     }
 }
@@ -427,6 +530,18 @@ pub fn block_coords(pos: &DVec3) -> IVec3 {
     )
 }
 
+/// The first state of the block with the given name, for building worlds in tests
+#[cfg(test)]
+pub fn test_block(name: &str) -> BlockIndex {
+    let id = blocks()
+        .values()
+        .filter(|b| b.name == name)
+        .map(|b| b.id)
+        .min()
+        .unwrap_or_else(|| panic!("No block called {name}"));
+    BlockIndex::try_from(id).unwrap()
+}
+
 impl Directional for IVec2 {
     fn north() -> Self {
         IVec2::new(0, -1)
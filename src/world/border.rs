@@ -0,0 +1,148 @@
+use glam::{DVec2, DVec3, Vec3Swizzles};
+
+/// How wide the border is until the server says otherwise, the same as vanilla
+const DEFAULT_DIAMETER: f64 = 59_999_968.0;
+const DEFAULT_WARNING_BLOCKS: f64 = 5.0;
+const DEFAULT_WARNING_TIME: f64 = 15.0;
+
+/// The square edge of the world the player can't go past, which can grow or shrink over time
+#[derive(Debug, Clone)]
+pub struct WorldBorder {
+    centre: DVec2,
+    diameter: f64,
+
+    /// Diameter the border is moving towards, and where it started
+    target_diameter: f64,
+    start_diameter: f64,
+    /// Seconds the border has spent moving, and how long it will take in total
+    lerp_elapsed: f64,
+    lerp_duration: f64,
+
+    /// The player is warned when they're this many blocks from the border
+    warning_blocks: f64,
+    /// The player is warned when a shrinking border will reach them within this many seconds
+    warning_time: f64,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            centre: DVec2::ZERO,
+            diameter: DEFAULT_DIAMETER,
+
+            target_diameter: DEFAULT_DIAMETER,
+            start_diameter: DEFAULT_DIAMETER,
+            lerp_elapsed: 0.0,
+            lerp_duration: 0.0,
+
+            warning_blocks: DEFAULT_WARNING_BLOCKS,
+            warning_time: DEFAULT_WARNING_TIME,
+        }
+    }
+}
+
+impl WorldBorder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the border along towards its target size
+    pub fn update(&mut self, delta: f64) {
+        if self.lerp_elapsed >= self.lerp_duration {
+            return;
+        }
+
+        self.lerp_elapsed = (self.lerp_elapsed + delta).min(self.lerp_duration);
+        let progress = self.lerp_elapsed / self.lerp_duration;
+        self.diameter =
+            self.start_diameter + (self.target_diameter - self.start_diameter) * progress;
+    }
+
+    pub fn set_diameter(&mut self, diameter: f64) {
+        self.lerp_to(diameter, diameter, 0.0);
+    }
+
+    /// Start moving the border from one diameter to another over `duration` seconds
+    pub fn lerp_to(&mut self, from: f64, to: f64, duration: f64) {
+        self.diameter = if duration > 0.0 { from } else { to };
+        self.start_diameter = from;
+        self.target_diameter = to;
+        self.lerp_elapsed = 0.0;
+        self.lerp_duration = duration.max(0.0);
+    }
+
+    pub fn set_centre(&mut self, x: f64, z: f64) {
+        self.centre = DVec2::new(x, z);
+    }
+
+    pub fn set_warning_blocks(&mut self, blocks: f64) {
+        self.warning_blocks = blocks;
+    }
+
+    pub fn set_warning_time(&mut self, seconds: f64) {
+        self.warning_time = seconds;
+    }
+
+    #[must_use]
+    pub fn get_centre(&self) -> DVec2 {
+        self.centre
+    }
+
+    #[must_use]
+    pub fn get_diameter(&self) -> f64 {
+        self.diameter
+    }
+
+    /// The corners of the border on the xz plane
+    #[must_use]
+    pub fn get_min(&self) -> DVec2 {
+        self.centre - self.diameter / 2.0
+    }
+
+    #[must_use]
+    pub fn get_max(&self) -> DVec2 {
+        self.centre + self.diameter / 2.0
+    }
+
+    /// How far a position is inside the border to the nearest edge, negative if it's outside
+    #[must_use]
+    pub fn distance_to(&self, position: &DVec3) -> f64 {
+        let position = position.xz();
+        let inside_min = position - self.get_min();
+        let inside_max = self.get_max() - position;
+        inside_min.min(inside_max).min_element()
+    }
+
+    /// How close to the border the player has to be to be warned about it, further away while
+    /// it's closing in
+    #[must_use]
+    pub fn get_warning_distance(&self) -> f64 {
+        let remaining = self.lerp_duration - self.lerp_elapsed;
+        if remaining <= 0.0 || self.target_diameter >= self.diameter {
+            return self.warning_blocks;
+        }
+
+        // Blocks each edge moves per second, half the change in diameter
+        let speed = (self.diameter - self.target_diameter) / 2.0 / remaining;
+        let closing = (speed * self.warning_time).min((self.diameter - self.target_diameter) / 2.0);
+        self.warning_blocks.max(closing)
+    }
+
+    /// Keep a box of width `width` centred on `position` inside the border
+    #[must_use]
+    pub fn clamp(&self, position: DVec3, width: f64) -> DVec3 {
+        let half = width / 2.0;
+        let min = self.get_min() + half;
+        let max = self.get_max() - half;
+        // A border narrower than the box can't contain it, so keep it in the middle
+        if min.x > max.x || min.y > max.y {
+            return DVec3::new(self.centre.x, position.y, self.centre.y);
+        }
+        DVec3::new(
+            position.x.clamp(min.x, max.x),
+            position.y,
+            position.z.clamp(min.y, max.y),
+        )
+    }
+}
@@ -1,245 +1,390 @@
-/*
-use std::sync::{
-    mpsc::{channel, Receiver, Sender},
-    RwLockReadGuard,
-};
+use std::collections::HashMap;
 
-use glam::IVec3;
-use threadpool::ThreadPool;
+use glam::{IVec3, Vec2, Vec3};
 
 use crate::{
     renderer::BlockVertex,
-    resources::{block_models::BlockModel, BLOCKS, BLOCK_MODELS_PARSED},
-};
-
-use super::{
-    chunks::{
-        block_index_to_pos, block_pos_to_index, Chunk, ChunkSection, WrappedChunkSection,
-        MAX_SECTION, MIN_SECTION,
+    resources::{
+        block_model,
+        block_models::{push_face, BlockModel, FACE_DIRECTIONS},
+        blocks,
     },
-    SectionLocation,
 };
 
-pub struct ChunkBuilder {
-    incoming: Receiver<(SectionLocation, Vec<BlockVertex>)>,
-    outgoing: Sender<(SectionLocation, Vec<BlockVertex>)>,
-    pool: ThreadPool,
+use super::chunks::{block_index_to_pos, block_pos_to_index, BlockIndex, ChunkSection};
+
+/// Models that look like full cubes but can be seen through, so mustn't hide neighbouring faces
+const CULL_EXCEPTIONS: [&str; 4] = ["glass", "leaves", "water", "spawner"];
+/// The axis each face in `FACE_DIRECTIONS` points along
+const FACE_AXES: [usize; 6] = [1, 1, 2, 0, 2, 0];
+/// The axes a face's texture u and v run along, for each face in `FACE_DIRECTIONS`
+const FACE_UV_AXES: [(usize, usize); 6] = [(0, 2), (0, 2), (0, 1), (2, 1), (0, 1), (2, 1)];
+
+/// The texture of each visible full cube face in a section facing one direction, by block index
+type FaceMask = [Option<f32>; 4096];
+
+/// The chunk sections surrounding the one being meshed, used to cull faces on its edges
+pub struct SectionNeighbours<'a> {
+    pub above: Option<&'a ChunkSection>,
+    pub below: Option<&'a ChunkSection>,
+    pub north: Option<&'a ChunkSection>,
+    pub east: Option<&'a ChunkSection>,
+    pub south: Option<&'a ChunkSection>,
+    pub west: Option<&'a ChunkSection>,
 }
 
-impl ChunkBuilder {
-    pub fn new() -> ChunkBuilder {
-        let (send, recv) = channel();
-
-        let mut threads: usize = std::thread::available_parallelism().unwrap().into();
-        threads = threads.saturating_sub(4);
-        threads = threads.max(1);
-
-        ChunkBuilder {
-            incoming: recv,
-            outgoing: send,
-            pool: threadpool::Builder::new()
-                .num_threads(threads)
-                .thread_name("ChunkBuilderPool".to_string())
-                .build(),
+/// Generates the vertices for a chunk section, relative to the section's origin. Faces of full
+/// cubes are merged with matching neighbours into larger quads, everything else is meshed face by
+/// face.
+pub fn generate_mesh(section: &ChunkSection, neighbours: &SectionNeighbours) -> Vec<BlockVertex> {
+    let mut verts: Vec<BlockVertex> = Vec::new();
+    let mut models: HashMap<BlockIndex, Option<&'static BlockModel>> = HashMap::new();
+    let mut cubes: HashMap<BlockIndex, Option<[f32; 6]>> = HashMap::new();
+    let mut cube_faces: Vec<FaceMask> = vec![[None; 4096]; FACE_DIRECTIONS.len()];
+
+    for (i, b) in section.blocks.iter().enumerate() {
+        // Air
+        if *b == 0 {
+            continue;
+        }
+        let Some(model) = load_model(&mut models, *b) else {
+            continue;
+        };
+
+        let pos = block_index_to_pos(i);
+        let culled = culled_sides(section, neighbours, &mut models, pos);
+
+        // Cube faces are only collected here, they're meshed once every block is known
+        if let Some(textures) = cubes.entry(*b).or_insert_with(|| model.cube_textures()) {
+            for (faces, (texture, culled)) in cube_faces.iter_mut().zip(textures.iter().zip(culled))
+            {
+                if !culled {
+                    faces[i] = Some(*texture);
+                }
+            }
+            continue;
+        }
+
+        for mut vert in model.generate_mesh(|cullface| is_culled(&culled, cullface)) {
+            vert.position[0] += pos.x as f32;
+            vert.position[1] += pos.y as f32;
+            vert.position[2] += pos.z as f32;
+            verts.push(vert);
         }
     }
 
-    pub fn get_incoming_meshes(&self) -> &Receiver<(SectionLocation, Vec<BlockVertex>)> {
-        &self.incoming
+    for (direction, faces) in cube_faces.iter_mut().enumerate() {
+        merge_faces(&mut verts, direction, faces);
     }
 
-    pub fn generate_chunk(
-        &self,
-        chunk: &Chunk,
-        north: &Chunk,
-        east: &Chunk,
-        south: &Chunk,
-        west: &Chunk,
-        threaded: bool,
-    ) {
-        for sec in chunk.get_sections() {
-            if sec.is_none() {
-                continue;
+    verts
+}
+
+/// Mesh the faces of full cubes facing one direction, greedily growing each quad along one axis
+/// then the other while the faces next to it have the same texture. Textures repeat across the
+/// merged quads, so they look the same as if each face was drawn on its own.
+fn merge_faces(verts: &mut Vec<BlockVertex>, direction: usize, faces: &mut FaceMask) {
+    let normal = FACE_AXES[direction];
+    let (across, along) = ((normal + 1) % 3, (normal + 2) % 3);
+    let (u, v) = FACE_UV_AXES[direction];
+
+    let index = |slice: i32, a: i32, b: i32| {
+        let mut pos = IVec3::ZERO;
+        pos[normal] = slice;
+        pos[across] = a;
+        pos[along] = b;
+        block_pos_to_index(&pos)
+    };
+
+    for slice in 0..16 {
+        for b in 0..16 {
+            let mut a = 0;
+            while a < 16 {
+                let Some(texture) = faces[index(slice, a, b)] else {
+                    a += 1;
+                    continue;
+                };
+
+                let mut width = 1;
+                while a + width < 16 && faces[index(slice, a + width, b)] == Some(texture) {
+                    width += 1;
+                }
+                let mut height = 1;
+                while b + height < 16
+                    && (a..a + width).all(|i| faces[index(slice, i, b + height)] == Some(texture))
+                {
+                    height += 1;
+                }
+                for j in b..b + height {
+                    for i in a..a + width {
+                        faces[index(slice, i, j)] = None;
+                    }
+                }
+
+                let mut from = Vec3::ZERO;
+                from[normal] = slice as f32;
+                from[across] = a as f32;
+                from[along] = b as f32;
+                let mut size = Vec3::ONE;
+                size[across] = width as f32;
+                size[along] = height as f32;
+
+                let uv = (Vec2::ZERO, Vec2::new(size[u], size[v]));
+                push_face(
+                    verts,
+                    FACE_DIRECTIONS[direction],
+                    from,
+                    from + size,
+                    uv,
+                    texture,
+                );
+
+                a += width;
             }
-            let sec = sec.unwrap();
-            let sec_read = sec.read().unwrap();
-            let loc = IVec3::new(chunk.get_coords().x, sec_read.y, chunk.get_coords().y);
-
-            let above = if loc.y < MAX_SECTION {
-                chunk.get_section(loc.y + 1)
-            } else {
-                None
-            };
-            let below = if loc.y > MIN_SECTION {
-                chunk.get_section(loc.y - 1)
-            } else {
-                None
-            };
-            let north = north.get_section(loc.y);
-            let east = east.get_section(loc.y);
-            let south = south.get_section(loc.y);
-            let west = west.get_section(loc.y);
-
-            self.generate_chunk_section(
-                sec.clone(),
-                loc,
-                above,
-                below,
-                north,
-                east,
-                south,
-                west,
-                threaded,
-            );
         }
     }
+}
 
-    pub fn generate_chunk_section(
-        &self,
-        sect: WrappedChunkSection,
-        loc: SectionLocation,
-        above: Option<WrappedChunkSection>,
-        below: Option<WrappedChunkSection>,
-        north: Option<WrappedChunkSection>,
-        east: Option<WrappedChunkSection>,
-        south: Option<WrappedChunkSection>,
-        west: Option<WrappedChunkSection>,
-        threaded: bool,
-    ) {
-        let outgoing = self.outgoing.clone();
-
-        let run = move || {
-            let above = above.as_ref();
-            let below = below.as_ref();
-            let north = north.as_ref();
-            let south = south.as_ref();
-            let east = east.as_ref();
-            let west = west.as_ref();
-            outgoing
-                .send((
-                    loc,
-                    Self::generate_mesh(
-                        sect.read().unwrap(),
-                        above.map(|s| s.read().unwrap()),
-                        below.map(|s| s.read().unwrap()),
-                        north.map(|s| s.read().unwrap()),
-                        east.map(|s| s.read().unwrap()),
-                        south.map(|s| s.read().unwrap()),
-                        west.map(|s| s.read().unwrap()),
-                    ),
-                ))
-                .ok();
+/// Works out which sides of the block at `pos` are hidden by the blocks next to them, in the
+/// order of `FACE_DIRECTIONS`. Blocks on the edge look into the neighbouring sections.
+fn culled_sides(
+    section: &ChunkSection,
+    neighbours: &SectionNeighbours,
+    models: &mut HashMap<BlockIndex, Option<&'static BlockModel>>,
+    pos: IVec3,
+) -> [bool; 6] {
+    let mut neighbour = |offset: IVec3, on_edge: bool, other: Option<&ChunkSection>| {
+        let ni = block_pos_to_index(&(pos + offset));
+        let block = if on_edge {
+            other.map(|cs| cs.blocks[ni]).unwrap_or(0)
+        } else {
+            section.blocks[ni]
         };
+        culls_neighbour(models, block)
+    };
 
-        if threaded {
-            self.pool.execute(move || run());
-        } else {
-            run();
-        }
+    [
+        neighbour(IVec3::Y, pos.y == 15, neighbours.above),
+        neighbour(IVec3::NEG_Y, pos.y == 0, neighbours.below),
+        neighbour(IVec3::NEG_Z, pos.z == 0, neighbours.north),
+        neighbour(IVec3::X, pos.x == 15, neighbours.east),
+        neighbour(IVec3::Z, pos.z == 15, neighbours.south),
+        neighbour(IVec3::NEG_X, pos.x == 0, neighbours.west),
+    ]
+}
+
+/// Returns true if a model face with the given `cullface` is hidden, given the culled sides of
+/// its block
+fn is_culled(culled: &[bool; 6], cullface: &str) -> bool {
+    match cullface {
+        "up" => culled[0],
+        "down" => culled[1],
+        "north" => culled[2],
+        "east" => culled[3],
+        "south" => culled[4],
+        "west" => culled[5],
+        _ => false,
     }
+}
 
-    pub fn generate_mesh(
-        section: RwLockReadGuard<ChunkSection>,
-        above: Option<RwLockReadGuard<ChunkSection>>,
-        below: Option<RwLockReadGuard<ChunkSection>>,
-        north: Option<RwLockReadGuard<ChunkSection>>,
-        east: Option<RwLockReadGuard<ChunkSection>>,
-        south: Option<RwLockReadGuard<ChunkSection>>,
-        west: Option<RwLockReadGuard<ChunkSection>>,
-    ) -> Vec<BlockVertex> {
-        let mut verts: Vec<BlockVertex> = Vec::new();
+/// Get the model for a block state, looking it up the first time it's seen in this section
+fn load_model(
+    models: &mut HashMap<BlockIndex, Option<&'static BlockModel>>,
+    block: BlockIndex,
+) -> Option<&'static BlockModel> {
+    *models.entry(block).or_insert_with(|| {
+        let key = blocks().get(&block.into())?.models.as_ref()?.first()?;
+        let model = block_model(key);
+        if model.is_none() {
+            tracing::debug!("Couldn't find model {}", key);
+        }
+        model
+    })
+}
 
-        let value = section.blocks;
-        for (i, b) in value.iter().enumerate() {
-            let block = BLOCKS.get(&((*b).into()));
-            if block.is_none() {
-                continue;
-            }
-            let block = block.unwrap();
+/// Returns true if the given block hides the faces of blocks next to it
+fn culls_neighbour(
+    models: &mut HashMap<BlockIndex, Option<&'static BlockModel>>,
+    block: BlockIndex,
+) -> bool {
+    if block == 0 {
+        return false;
+    }
 
-            if block.models.is_none() {
-                continue;
-            }
-            if block.models.as_ref().unwrap().len() == 0 {
-                continue;
-            }
+    let Some(model) = blocks()
+        .get(&block.into())
+        .and_then(|b| b.models.as_ref())
+        .and_then(|m| m.first())
+    else {
+        return false;
+    };
+    if CULL_EXCEPTIONS
+        .iter()
+        .any(|exception| model.contains(exception))
+    {
+        return false;
+    }
 
-            let model_key = block.models.as_ref().unwrap().get(0).unwrap();
-            let model: Option<&BlockModel> = BLOCK_MODELS_PARSED.get(model_key);
-            if model.is_none() {
-                log::error!(
-                    "Couldn't find model {}",
-                    block.models.as_ref().unwrap().get(0).unwrap()
-                );
-                continue;
-            }
-            let model = model.unwrap();
+    load_model(models, block).is_some_and(BlockModel::culls_against)
+}
 
-            let pos = block_index_to_pos(i);
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
 
-            // Top Face
-            let n = pos + IVec3::new(0, 1, 0);
-            let ni = block_pos_to_index(&n);
-            let b_above = if pos.y == 15 {
-                above.as_ref().map(|cs| cs.blocks[ni]).unwrap_or(0)
-            } else {
-                section.blocks[ni]
-            };
+    use crate::world::test_block;
 
-            // Bottom Face
-            let n = pos + IVec3::new(0, -1, 0);
-            let ni = block_pos_to_index(&n);
-            let b_below = if pos.y == 0 {
-                below.as_ref().map(|cs| cs.blocks[ni]).unwrap_or(0)
-            } else {
-                section.blocks[ni]
-            };
+    use super::*;
 
-            // North Face
-            let n = pos + IVec3::new(0, 0, -1);
-            let ni = block_pos_to_index(&n);
-            let b_north = if pos.z == 0 {
-                north.as_ref().map(|cs| cs.blocks[ni]).unwrap_or(0)
-            } else {
-                section.blocks[ni]
-            };
+    const NO_NEIGHBOURS: SectionNeighbours = SectionNeighbours {
+        above: None,
+        below: None,
+        north: None,
+        east: None,
+        south: None,
+        west: None,
+    };
+    /// Each face is drawn as two triangles
+    const VERTS_PER_FACE: usize = 6;
 
-            // South Face
-            let n = pos + IVec3::new(0, 0, 1);
-            let ni = block_pos_to_index(&n);
-            let b_south = if pos.z == 15 {
-                south.as_ref().map(|cs| cs.blocks[ni]).unwrap_or(0)
-            } else {
-                section.blocks[ni]
-            };
+    /// A section with `block` wherever `filled` says so
+    fn section(block: BlockIndex, filled: impl Fn(IVec3) -> bool) -> ChunkSection {
+        let mut blocks = [0; 4096];
+        for (i, b) in blocks.iter_mut().enumerate() {
+            if filled(block_index_to_pos(i)) {
+                *b = block;
+            }
+        }
+        ChunkSection { y: 0, blocks }
+    }
 
-            // East Face
-            let n = pos + IVec3::new(1, 0, 0);
-            let ni = block_pos_to_index(&n);
-            let b_east = if pos.x == 15 {
-                east.as_ref().map(|cs| cs.blocks[ni]).unwrap_or(0)
-            } else {
-                section.blocks[ni]
-            };
+    /// Meshes every block face by face from its model without merging anything, which is what
+    /// `generate_mesh` is measured against
+    fn generate_mesh_unmerged(
+        section: &ChunkSection,
+        neighbours: &SectionNeighbours,
+    ) -> Vec<BlockVertex> {
+        let mut verts = Vec::new();
+        let mut models = HashMap::new();
 
-            // West Face
-            let n = pos + IVec3::new(-1, 0, 0);
-            let ni = block_pos_to_index(&n);
-            let b_west = if pos.x == 0 {
-                west.as_ref().map(|cs| cs.blocks[ni]).unwrap_or(0)
-            } else {
-                section.blocks[ni]
+        for (i, b) in section.blocks.iter().enumerate() {
+            if *b == 0 {
+                continue;
+            }
+            let Some(model) = load_model(&mut models, *b) else {
+                continue;
             };
 
-            for mut vert in model.generate_mesh(b_above, b_below, b_north, b_east, b_south, b_west)
-            {
+            let pos = block_index_to_pos(i);
+            let culled = culled_sides(section, neighbours, &mut models, pos);
+
+            for mut vert in model.generate_mesh(|cullface| is_culled(&culled, cullface)) {
                 vert.position[0] += pos.x as f32;
                 vert.position[1] += pos.y as f32;
                 vert.position[2] += pos.z as f32;
                 verts.push(vert);
             }
         }
+
         verts
     }
+
+    #[test]
+    fn solid_section_is_one_quad_per_side() {
+        let solid = section(test_block("Stone"), |_| true);
+
+        let verts = generate_mesh(&solid, &NO_NEIGHBOURS);
+        assert_eq!(verts.len(), 6 * VERTS_PER_FACE);
+    }
+
+    #[test]
+    fn floor_with_a_hole_is_merged_around_it() {
+        let hole = IVec3::new(8, 0, 8);
+        let floor = section(test_block("Stone"), |pos| pos.y == 0 && pos != hole);
+
+        // The top and bottom are split into four quads around the hole, the outer sides are one
+        // each, and each block next to the hole shows one face into it
+        let verts = generate_mesh(&floor, &NO_NEIGHBOURS);
+        assert_eq!(verts.len(), (4 + 4 + 4 + 4) * VERTS_PER_FACE);
+
+        // Face by face that's 255 faces on the top and bottom, 64 around the outside and 4 inside
+        let unmerged = generate_mesh_unmerged(&floor, &NO_NEIGHBOURS);
+        assert_eq!(unmerged.len(), (255 + 255 + 64 + 4) * VERTS_PER_FACE);
+    }
+
+    /// Superflat sections from -1 to 1 on each side, with pillars of stone sticking out of them
+    /// here and there so not every face can be merged
+    fn superflat_region() -> HashMap<(i32, i32), ChunkSection> {
+        let (bedrock, dirt, grass, stone) = (
+            test_block("Bedrock"),
+            test_block("Dirt"),
+            test_block("Grass Block"),
+            test_block("Stone"),
+        );
+
+        let mut region = HashMap::new();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let mut section = section(0, |_| false);
+                for (i, b) in section.blocks.iter_mut().enumerate() {
+                    let pos = block_index_to_pos(i);
+                    let pillar = (pos.x * 7 + pos.z * 13 + x * 5 + z * 3).rem_euclid(23) == 0;
+                    *b = match pos.y {
+                        0 => bedrock,
+                        1 | 2 => dirt,
+                        3 => grass,
+                        4..=7 if pillar => stone,
+                        _ => 0,
+                    };
+                }
+                region.insert((x, z), section);
+            }
+        }
+        region
+    }
+
+    /// Mesh the middle of a region `runs` times, returning the vertex count and the average time
+    fn mesh_middle(
+        region: &HashMap<(i32, i32), ChunkSection>,
+        mesher: fn(&ChunkSection, &SectionNeighbours) -> Vec<BlockVertex>,
+        runs: u32,
+    ) -> (usize, Duration) {
+        let neighbours = SectionNeighbours {
+            above: None,
+            below: None,
+            north: region.get(&(0, -1)),
+            east: region.get(&(1, 0)),
+            south: region.get(&(0, 1)),
+            west: region.get(&(-1, 0)),
+        };
+
+        let start = Instant::now();
+        let mut count = 0;
+        for _ in 0..runs {
+            count = mesher(&region[&(0, 0)], &neighbours).len();
+        }
+        (count, start.elapsed() / runs)
+    }
+
+    /// Compares the triangle counts and times of meshing a superflat world with and without
+    /// merging faces. Run it with `cargo test --release -- --ignored --nocapture meshing_benchmark`
+    #[test]
+    #[ignore]
+    fn meshing_benchmark() {
+        const RUNS: u32 = 200;
+        let region = superflat_region();
+
+        let (face_count, face_time) = mesh_middle(&region, generate_mesh_unmerged, RUNS);
+        let (merged_count, merged_time) = mesh_middle(&region, generate_mesh, RUNS);
+        println!(
+            "Face by face: {} triangles in {face_time:?}",
+            face_count / 3
+        );
+        println!(
+            "Merged:       {} triangles in {merged_time:?}",
+            merged_count / 3
+        );
+
+        assert!(merged_count * 4 < face_count);
+    }
 }
-*/
@@ -1,27 +1,52 @@
-use std::{
-    convert::TryInto,
-    io::{Cursor, Read},
-};
+use std::io::{self, Cursor, Read};
 
 use glam::{IVec2, IVec3};
 use mcproto_rs::{nbt, v1_16_3::ChunkData};
+use thiserror::Error;
 
 use crate::{
     network::read_varint,
-    resources::{blocks, BlockState},
+    resources::{biomes::PLAINS_ID, blocks, BlockState},
 };
 
-use super::{ChunkCoords, ChunkLocation, SectionCoords, SectionLocation, WorldCoords};
+use super::{coords, ChunkCoords, ChunkLocation, SectionCoords, SectionLocation, WorldCoords};
 
 // Base 2 Log of number of state ids in the game
 const MAX_BITS_PER_BLOCK: u32 = 15;
+/// Sections using more bits per block than this use global state ids rather than a palette
+const MAX_PALETTE_BITS: u32 = 8;
+/// Bits used for each height in a heightmap
+const HEIGHTMAP_BITS: usize = 9;
+/// Biomes are stored for 4x4x4 cells, so 4 * 4 * 64 of them
+pub const BIOMES_PER_CHUNK: usize = 1024;
 pub const SECTIONS_PER_CHUNK: usize = 16;
 pub const MAX_SECTION: i32 = 15;
 pub const MIN_SECTION: i32 = 0;
 pub type BlockIndex = u16;
 pub type ChunkArray = [BlockIndex; 16 * 16 * 16];
 
-#[derive(Debug)]
+/// Problems with the chunk data the server sent
+#[derive(Error, Debug)]
+pub enum ChunkError {
+    #[error("Chunk data ended early: {0}")]
+    Truncated(#[from] io::Error),
+    #[error("Expected a full chunk with biomes")]
+    NotFullChunk,
+    #[error("Expected {BIOMES_PER_CHUNK} biomes but got {0}")]
+    BiomeCount(usize),
+    #[error("Invalid palette length {0}")]
+    PaletteLength(i32),
+    #[error("Palette index {index} out of range for palette of {len}")]
+    PaletteIndex { index: u64, len: usize },
+    #[error("Invalid block state id {0}")]
+    BlockId(i32),
+    #[error("Expected {expected} longs of block data but got {got}")]
+    DataLength { expected: usize, got: i32 },
+    #[error("Section {0}: {1}")]
+    Section(usize, Box<ChunkError>),
+}
+
+#[derive(Debug, Clone)]
 pub struct ChunkSection {
     pub y: i32,
     pub blocks: ChunkArray,
@@ -34,7 +59,7 @@ impl ChunkSection {
 
     /// Convert block coordinates from within a chunk to the chunk section
     pub fn map_from_chunk_coords(coords: &ChunkCoords) -> SectionCoords {
-        IVec3::new(coords.x, coords.y.rem_euclid(16), coords.z)
+        coords::chunk_to_section(coords)
     }
 
     /// Convert block coordinsate from within this chunk section to the entire chunk
@@ -43,7 +68,7 @@ impl ChunkSection {
     }
 
     pub fn map_from_world_coords(coords: &WorldCoords) -> SectionCoords {
-        Self::map_from_chunk_coords(&Chunk::map_from_world_coords(coords))
+        coords::world_to_section(coords)
     }
 
     /// Get the block at the provided SectionCoords within this chunk section
@@ -53,15 +78,11 @@ impl ChunkSection {
 
     /// Get the chunk section index of the section containing the provided y level
     pub fn section_containing_height(y: i32) -> i32 {
-        y / 16
+        coords::section_of_height(y)
     }
 
     pub fn section_containing(coords: &WorldCoords) -> SectionLocation {
-        IVec3::new(
-            coords.x.div_euclid(16),
-            coords.y.div_euclid(16),
-            coords.z.div_euclid(16),
-        )
+        coords::section_location(coords)
     }
 }
 
@@ -69,34 +90,126 @@ pub struct Chunk {
     pos: ChunkLocation,
     heightmap: [u16; 256],
     sections: Box<[Option<ChunkSection>; SECTIONS_PER_CHUNK]>,
+    /// Biome ids for each 4x4x4 cell of the chunk
+    biomes: Box<[i32; BIOMES_PER_CHUNK]>,
 }
 
 impl Chunk {
-    pub fn new(data: &ChunkData) -> Chunk {
+    /// Decode a full chunk sent by the server
+    ///
+    /// # Errors
+    /// If the chunk isn't a full chunk or its data is malformed
+    pub fn new(data: &ChunkData) -> Result<Chunk, ChunkError> {
         tracing::debug!("Processing chunk data");
 
-        Chunk {
+        let biomes = data.biomes.as_ref().ok_or(ChunkError::NotFullChunk)?;
+        if biomes.len() != BIOMES_PER_CHUNK {
+            return Err(ChunkError::BiomeCount(biomes.len()));
+        }
+        let mut biome_ids = Box::new([0; BIOMES_PER_CHUNK]);
+        for (id, biome) in biome_ids.iter_mut().zip(biomes.iter()) {
+            *id = biome.0;
+        }
+
+        Ok(Chunk {
             pos: IVec2::new(data.position.x, data.position.z),
 
             heightmap: process_heightmap(data),
-            sections: process_sections(data),
+            sections: process_sections(data)?,
+            biomes: biome_ids,
+        })
+    }
+
+    /// A chunk with nothing in it yet, all air in plains
+    pub fn empty(pos: ChunkLocation) -> Chunk {
+        Chunk {
+            pos,
+            heightmap: [0; 256],
+            sections: Box::new([const { None }; SECTIONS_PER_CHUNK]),
+            biomes: Box::new([PLAINS_ID as i32; BIOMES_PER_CHUNK]),
+        }
+    }
+
+    /// Apply a chunk that isn't a full chunk on top of this one. Only the sections it contains
+    /// are replaced, the rest are kept as they were
+    ///
+    /// # Errors
+    /// If the chunk data is malformed, in which case this chunk is left unchanged
+    pub fn merge(&mut self, data: &ChunkData) -> Result<(), ChunkError> {
+        self.merge_sections(&data.data, data.primary_bit_mask.0)?;
+        self.heightmap = process_heightmap(data);
+        Ok(())
+    }
+
+    /// Replace the sections in `mask` with ones read from `data`, leaving the chunk unchanged if
+    /// the data is malformed
+    fn merge_sections(&mut self, data: &[u8], mask: i32) -> Result<(), ChunkError> {
+        let mut sections = read_sections(data, mask)?;
+        for (i, section) in sections.iter_mut().enumerate() {
+            if is_section_in_mask(mask, i) {
+                self.sections[i] = section.take();
+            }
         }
+        Ok(())
+    }
+
+    /// Get the block state id at coordinates within this chunk. Anything in a missing section or
+    /// outside of the chunk is air
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> u32 {
+        if !(0..16).contains(&x) || !(0..16).contains(&z) || y < 0 {
+            return 0;
+        }
+        self.get_section_containing(y)
+            .map(|s| u32::from(s.blocks[block_pos_to_index(&IVec3::new(x, y, z))]))
+            .unwrap_or(0)
+    }
+
+    /// Change the block at coordinates within this chunk, adding the section if it was empty.
+    /// Returns false if the coordinates are above or below the world
+    pub fn set_block(&mut self, coords: &ChunkCoords, block: BlockIndex) -> bool {
+        let y = coords::section_of_height(coords.y);
+        let Some(index) = coords::section_index(y) else {
+            return false;
+        };
+
+        let section = self.sections[index].get_or_insert_with(|| ChunkSection::new(y, [0; 4096]));
+        section.blocks[block_pos_to_index(coords)] = block;
+        true
+    }
+
+    /// The biome ids of each 4x4x4 cell of this chunk
+    pub fn get_biomes(&self) -> &[i32; BIOMES_PER_CHUNK] {
+        &self.biomes
+    }
+
+    /// The biome id at coordinates within this chunk. Biomes are only stored for each 4x4x4
+    /// cell, and heights outside the world use the nearest cell
+    pub fn get_biome(&self, coords: &ChunkCoords) -> u32 {
+        let x = coords.x.clamp(0, 15) / 4;
+        let y = coords.y.clamp(0, 255) / 4;
+        let z = coords.z.clamp(0, 15) / 4;
+        let id = self.biomes[(y * 16 + z * 4 + x) as usize];
+        u32::try_from(id).unwrap_or(PLAINS_ID)
     }
 
     /// Returns true if there are any non-air blocks within a section of the chunk
     pub fn is_section_present(&self, y: i32) -> bool {
-        self.sections.get(section_to_index(y)).is_some()
+        self.get_section(y).is_some()
     }
 
     pub fn is_section_containing_present(&self, y: i32) -> bool {
-        self.sections
-            .get(section_to_index(ChunkSection::section_containing_height(y)))
-            .is_some()
+        self.get_section_containing(y).is_some()
     }
 
+    /// Add or replace a section, ignoring any above or below the world
     pub fn put_section(&mut self, section: ChunkSection) {
-        let index = section_to_index(section.y);
-        *self.sections.get_mut(index).unwrap() = Some(section);
+        match coords::section_index(section.y) {
+            Some(index) => self.sections[index] = Some(section),
+            None => tracing::warn!(
+                "Tried to add chunk section outside the world: {}",
+                section.y
+            ),
+        }
     }
 
     pub fn get_sections(&self) -> &[Option<ChunkSection>; SECTIONS_PER_CHUNK] {
@@ -105,16 +218,11 @@ impl Chunk {
 
     /// Returns an option containing a reference to the request section of this chunk
     pub fn get_section(&self, y: i32) -> Option<&ChunkSection> {
-        self.sections
-            .get(section_to_index(y))
-            .unwrap_or(&None)
-            .as_ref()
+        self.sections[coords::section_index(y)?].as_ref()
     }
     /// Returns an option containing a reference to the request section of this chunk
     pub fn get_section_mut(&mut self, y: i32) -> Option<&mut ChunkSection> {
-        self.sections
-            .get_mut(section_to_index(y))
-            .map(|s| s.as_mut())?
+        self.sections[coords::section_index(y)?].as_mut()
     }
 
     pub fn get_section_containing(&self, y: i32) -> Option<&ChunkSection> {
@@ -127,22 +235,18 @@ impl Chunk {
 
     /// Converts a coordinates of a block from the world to the coordinates within the chunk
     pub fn map_from_world_coords(coords: &WorldCoords) -> ChunkCoords {
-        IVec3::new(coords.x.rem_euclid(16), coords.y, coords.z.rem_euclid(16))
+        coords::world_to_chunk(coords)
     }
 
     /// Converts a coordinates of a block within this chunk to a position in the world
     pub fn map_to_world_coords(&self, coords: &ChunkCoords) -> WorldCoords {
         assert!(coords.x >= 0 && coords.x < 16);
         assert!(coords.z >= 0 && coords.z < 16);
-        IVec3::new(
-            self.pos.x * 16 + coords.x,
-            coords.y,
-            self.pos.y * 16 + coords.z,
-        )
+        coords::chunk_to_world(&self.pos, coords)
     }
 
     pub fn chunk_containing(coords: &WorldCoords) -> ChunkLocation {
-        IVec2::new(coords.x.div_euclid(16), coords.z.div_euclid(16))
+        coords::chunk_location(coords)
     }
 
     pub fn block_at(&self, coords: &ChunkCoords) -> Option<&'static BlockState> {
@@ -161,132 +265,146 @@ impl Chunk {
 fn process_heightmap(data: &ChunkData) -> [u16; 256] {
     let mut map = [0u16; 256];
 
-    if let nbt::Tag::Compound(heightmaps) = &data.heightmaps.root.payload {
-        if heightmaps.len() != 2 {
-            tracing::error!(
-                "Got unexpected number of heightmap compound elements, expected 2 got {}",
-                heightmaps.len()
-            );
-            return map;
-        }
-
-        for heightmap in heightmaps {
-            if let nbt::NamedTag {
-                name,
-                payload: nbt::Tag::LongArray(longs),
-            } = heightmap
-            {
-                if name != "MOTION_BLOCKING" {
-                    continue;
-                }
-
-                let vals_per_long: usize = 7;
-                for i in 0..256usize {
-                    let long = 1 / vals_per_long;
-                    let offset = (i % vals_per_long) * 9;
-
-                    map[i] = ((longs[long] >> offset) & 0x1ff) as u16;
-                }
-            }
-        }
-    } else {
+    let nbt::Tag::Compound(heightmaps) = &data.heightmaps.root.payload else {
         tracing::error!("Didn't get heightmap compound expected from ChunkData");
         return map;
+    };
+
+    let longs = heightmaps.iter().find_map(|heightmap| match heightmap {
+        nbt::NamedTag {
+            name,
+            payload: nbt::Tag::LongArray(longs),
+        } if name == "MOTION_BLOCKING" => Some(longs),
+        _ => None,
+    });
+    let Some(longs) = longs else {
+        tracing::debug!("Chunk data has no MOTION_BLOCKING heightmap");
+        return map;
+    };
+
+    // Each height takes 9 bits, and they don't span across longs
+    let vals_per_long: usize = 64 / HEIGHTMAP_BITS;
+    if longs.len() < map.len().div_ceil(vals_per_long) {
+        tracing::error!("Heightmap is too short, only {} longs", longs.len());
+        return map;
+    }
+    for (i, height) in map.iter_mut().enumerate() {
+        let long = i / vals_per_long;
+        let offset = (i % vals_per_long) * HEIGHTMAP_BITS;
+
+        *height = ((longs[long] >> offset) & 0x1ff) as u16;
     }
 
     map
 }
 
+/// Returns true if the primary bit mask of some chunk data contains the section at the index
+fn is_section_in_mask(mask: i32, index: usize) -> bool {
+    mask & 0b1 << index != 0
+}
+
 /// Builds a list of chunk sections from chunk data
-fn process_sections(data: &ChunkData) -> Box<[Option<ChunkSection>; SECTIONS_PER_CHUNK]> {
-    // Check bit mask for which chunk sections are present
-    let mut chunk_sections_present = [false; SECTIONS_PER_CHUNK];
-    for i in 0..SECTIONS_PER_CHUNK {
-        if data.primary_bit_mask.0 & 0b1 << i != 0 {
-            chunk_sections_present[i] = true;
-        }
-    }
+fn process_sections(
+    data: &ChunkData,
+) -> Result<Box<[Option<ChunkSection>; SECTIONS_PER_CHUNK]>, ChunkError> {
+    read_sections(&data.data, data.primary_bit_mask.0)
+}
 
+/// Reads the sections in `mask` from the data array of a chunk
+fn read_sections(
+    data: &[u8],
+    mask: i32,
+) -> Result<Box<[Option<ChunkSection>; SECTIONS_PER_CHUNK]>, ChunkError> {
     let mut sections = Box::new([const { None }; SECTIONS_PER_CHUNK]);
 
-    // Decode data array
-    let mut cur = Cursor::new(&*data.data);
-    for i in 0..SECTIONS_PER_CHUNK {
-        if !chunk_sections_present[i] {
+    // Decode data array, only the sections in the bit mask are sent
+    let mut cur = Cursor::new(data);
+    for (i, section) in sections.iter_mut().enumerate() {
+        if !is_section_in_mask(mask, i) {
             continue;
         }
 
-        let mut buf = [0u8; 2];
-        cur.read_exact(&mut buf).unwrap();
-        let block_count = i16::from_ne_bytes(buf);
-
-        let mut buf = [0u8; 1];
-        cur.read_exact(&mut buf).unwrap();
-        let mut bits_per_block = buf[0].into();
-
-        if bits_per_block <= 4 {
-            bits_per_block = 4;
-        }
-        if bits_per_block >= 9 {
-            bits_per_block = MAX_BITS_PER_BLOCK;
-        }
-
-        let palette: Option<Vec<i32>>;
-
-        // Construct palette or no palette
-        if bits_per_block < 9 {
-            let palette_len = read_varint(&mut cur).unwrap();
-            tracing::debug!("Got chunk with pallete of {} elements.", palette_len);
-            let mut palette_vec: Vec<i32> = Vec::new();
-
-            for _ in 0..palette_len as usize {
-                palette_vec.push(read_varint(&mut cur).unwrap());
-            }
-            palette = Some(palette_vec);
-        } else {
-            palette = None;
-        }
+        *section = Some(ChunkSection {
+            y: i as i32,
+            blocks: read_section(&mut cur).map_err(|e| ChunkError::Section(i, Box::new(e)))?,
+        });
+    }
+    Ok(sections)
+}
 
-        // Get long array of blocks
-        let array_len = read_varint(&mut cur).unwrap();
-        let mut array = Vec::new();
-        for _ in 0..array_len as usize {
-            let mut buf = [0u8; 8];
-            cur.read_exact(&mut buf).unwrap();
-            array.push(i64::from_be_bytes(buf));
+/// Reads the blocks of a single chunk section
+fn read_section(cur: &mut Cursor<&[u8]>) -> Result<ChunkArray, ChunkError> {
+    // Number of non-air blocks, which we don't need
+    let mut buf = [0u8; 2];
+    cur.read_exact(&mut buf)?;
+
+    let mut buf = [0u8; 1];
+    cur.read_exact(&mut buf)?;
+    let bits_per_block: u32 = match buf[0] {
+        0..=4 => 4,
+        bits @ 5..=8 => bits.into(),
+        _ => MAX_BITS_PER_BLOCK,
+    };
+
+    // Sections with few enough kinds of block index into a palette, the rest use global ids
+    let palette = if bits_per_block <= MAX_PALETTE_BITS {
+        let palette_len = read_varint(cur)?;
+        if palette_len <= 0 || palette_len > 1 << bits_per_block {
+            return Err(ChunkError::PaletteLength(palette_len));
         }
 
-        // Bit mask depending on bits per block
-        let mask = 2i64.pow(bits_per_block) - 1;
-        let blocks_per_long = 64 / bits_per_block;
-
-        let mut blocks = [0u16; 4096];
-
-        // Extract blocks
-        for j in 0..4096 {
-            let long = j / blocks_per_long;
-            let start = (j % blocks_per_long) * bits_per_block;
-
-            // Get block id / palette index from long
-            let block = (array[long as usize] >> start) & mask;
-
-            // Get block from palette
-            match &palette {
-                Some(pal) => {
-                    blocks[j as usize] = pal[block as usize] as u16;
-                }
-                None => {
-                    blocks[j as usize] = block as u16;
-                }
-            }
+        let mut palette = Vec::with_capacity(palette_len as usize);
+        for _ in 0..palette_len {
+            let id = read_varint(cur)?;
+            palette.push(BlockIndex::try_from(id).map_err(|_| ChunkError::BlockId(id))?);
         }
-
-        sections[i] = Some(ChunkSection {
-            y: i as i32,
-            blocks,
+        Some(palette)
+    } else {
+        None
+    };
+
+    // Get long array of blocks. Blocks don't span across longs, so there may be unused bits at
+    // the end of each
+    let blocks_per_long = (64 / bits_per_block) as usize;
+    let expected = 4096usize.div_ceil(blocks_per_long);
+    let array_len = read_varint(cur)?;
+    if usize::try_from(array_len) != Ok(expected) {
+        return Err(ChunkError::DataLength {
+            expected,
+            got: array_len,
         });
     }
-    sections
+    let mut array = Vec::with_capacity(expected);
+    for _ in 0..expected {
+        let mut buf = [0u8; 8];
+        cur.read_exact(&mut buf)?;
+        array.push(u64::from_be_bytes(buf));
+    }
+
+    // Bit mask depending on bits per block
+    let mask = (1u64 << bits_per_block) - 1;
+
+    let mut blocks = [0; 4096];
+    for (j, block) in blocks.iter_mut().enumerate() {
+        let long = j / blocks_per_long;
+        let start = (j % blocks_per_long) as u32 * bits_per_block;
+
+        // Get block id / palette index from long
+        let value = (array[long] >> start) & mask;
+
+        *block = match &palette {
+            Some(palette) => *palette
+                .get(value as usize)
+                .ok_or(ChunkError::PaletteIndex {
+                    index: value,
+                    len: palette.len(),
+                })?,
+            // Fits as the mask is only 15 bits
+            None => value as BlockIndex,
+        };
+    }
+
+    Ok(blocks)
 }
 
 /// Converts a block position to an index within a chunk section array
@@ -303,6 +421,216 @@ pub fn block_index_to_pos(i: usize) -> IVec3 {
     IVec3::new(x as i32, y as i32, z as i32)
 }
 
-fn section_to_index(loc: i32) -> usize {
-    loc.try_into().unwrap()
+#[cfg(test)]
+mod tests {
+    use mcproto_rs::Deserialize;
+
+    use crate::network::write_varint;
+
+    use super::*;
+
+    /// The data of a section saying it uses `bits` per block, with `palette` unless it uses global
+    /// ids. Each block is `value(index)`, packed into longs like the server does.
+    fn section_bytes(bits: u8, palette: Option<&[i32]>, value: impl Fn(usize) -> u64) -> Vec<u8> {
+        let mut bytes = vec![0x10, 0x00, bits];
+        if let Some(palette) = palette {
+            write_varint(&mut bytes, palette.len() as i32).unwrap();
+            for id in palette {
+                write_varint(&mut bytes, *id).unwrap();
+            }
+        }
+
+        // Global ids always take 15 bits, whatever the section says
+        let bits = match bits {
+            0..=4 => 4,
+            5..=8 => usize::from(bits),
+            _ => MAX_BITS_PER_BLOCK as usize,
+        };
+        let per_long = 64 / bits;
+        let mut longs = vec![0u64; 4096usize.div_ceil(per_long)];
+        for i in 0..4096 {
+            longs[i / per_long] |= value(i) << (i % per_long * bits);
+        }
+        write_varint(&mut bytes, longs.len() as i32).unwrap();
+        for long in longs {
+            bytes.extend(long.to_be_bytes());
+        }
+        bytes
+    }
+
+    fn read(bytes: &[u8]) -> Result<ChunkArray, ChunkError> {
+        read_section(&mut Cursor::new(bytes))
+    }
+
+    #[test]
+    fn single_block_palette() {
+        let blocks = read(&section_bytes(4, Some(&[1]), |_| 0)).unwrap();
+
+        assert!(blocks.iter().all(|b| *b == 1));
+    }
+
+    #[test]
+    fn indirect_palette() {
+        // Fewer than 4 bits are sent as 4
+        let blocks = read(&section_bytes(2, Some(&[0, 9]), |i| (i % 2) as u64)).unwrap();
+        assert!(blocks.iter().enumerate().all(|(i, b)| *b == [0, 9][i % 2]));
+
+        // 5 bits leave 4 unused at the end of each long
+        let palette: Vec<i32> = (0..20).map(|i| i * 100).collect();
+        let blocks = read(&section_bytes(5, Some(&palette), |i| (i % 20) as u64)).unwrap();
+        assert!(blocks
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i32::from(*b) == palette[i % 20]));
+    }
+
+    #[test]
+    fn direct_palette() {
+        for bits in [9, 15] {
+            let blocks = read(&section_bytes(bits, None, |i| i as u64 + 1000)).unwrap();
+
+            assert!(blocks
+                .iter()
+                .enumerate()
+                .all(|(i, b)| *b as usize == i + 1000));
+        }
+    }
+
+    #[test]
+    fn truncated_data() {
+        let bytes = section_bytes(4, Some(&[1, 2]), |i| (i % 2) as u64);
+
+        for end in [0, 2, 5, bytes.len() - 1] {
+            let result = read(&bytes[..end]);
+            assert!(matches!(result, Err(ChunkError::Truncated(_))), "{end}");
+        }
+    }
+
+    #[test]
+    fn malformed_data() {
+        let result = read(&section_bytes(4, Some(&[1]), |i| (i % 2) as u64));
+        assert!(matches!(
+            result,
+            Err(ChunkError::PaletteIndex { index: 1, len: 1 })
+        ));
+
+        let result = read(&section_bytes(4, Some(&[]), |_| 0));
+        assert!(matches!(result, Err(ChunkError::PaletteLength(0))));
+
+        let result = read(&section_bytes(4, Some(&[-1]), |_| 0));
+        assert!(matches!(result, Err(ChunkError::BlockId(-1))));
+
+        // The 4 bit data array with the length of the 5 bit one
+        let mut bytes = vec![0x10, 0x00, 5, 1, 0];
+        bytes.extend(&section_bytes(4, None, |_| 0)[3..]);
+        let result = read(&bytes);
+        assert!(matches!(
+            result,
+            Err(ChunkError::DataLength {
+                expected: 342,
+                got: 256
+            })
+        ));
+    }
+
+    #[test]
+    fn sections_follow_the_bit_mask() {
+        let mut bytes = section_bytes(4, Some(&[1]), |_| 0);
+        bytes.extend(section_bytes(4, Some(&[2]), |_| 0));
+
+        let sections = read_sections(&bytes, 0b1001).unwrap();
+        let present: Vec<(usize, i32)> = sections
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|s| (i, s.y)))
+            .collect();
+        assert_eq!(present, [(0, 0), (3, 3)]);
+        assert_eq!(sections[3].as_ref().map(|s| s.blocks[0]), Some(2));
+
+        let result = read_sections(&bytes, 0b1011);
+        assert!(matches!(result, Err(ChunkError::Section(3, _))));
+    }
+
+    #[test]
+    fn merge_replaces_only_sent_sections() {
+        let mut chunk = Chunk::empty(IVec2::ZERO);
+        let mut bytes = section_bytes(4, Some(&[1]), |_| 0);
+        bytes.extend(section_bytes(4, Some(&[1]), |_| 0));
+        chunk.merge_sections(&bytes, 0b11).unwrap();
+
+        chunk
+            .merge_sections(&section_bytes(4, Some(&[10]), |_| 0), 0b10)
+            .unwrap();
+        assert_eq!(chunk.get_block(0, 0, 0), 1);
+        assert_eq!(chunk.get_block(15, 16, 15), 10);
+        assert!(!chunk.is_section_present(2));
+
+        // Bad data leaves the chunk as it was
+        let bytes = section_bytes(4, Some(&[3]), |_| 0);
+        let result = chunk.merge_sections(&bytes[..bytes.len() - 8], 0b1);
+        assert!(result.is_err());
+        assert_eq!(chunk.get_block(0, 0, 0), 1);
+    }
+
+    /// Decode the body of a chunk data packet into a chunk, as `World::load_chunk` does
+    fn decode(packet: &[u8]) -> Chunk {
+        let decoded = ChunkData::mc_deserialize(packet).unwrap();
+        assert!(
+            decoded.data.is_empty(),
+            "{} bytes left over",
+            decoded.data.len()
+        );
+        Chunk::new(&decoded.value).unwrap()
+    }
+
+    #[test]
+    fn chunk_packet_with_a_single_block_palette() {
+        // One section of stone
+        let chunk = decode(include_bytes!("fixtures/chunk_single_palette.bin"));
+
+        assert_eq!(*chunk.get_coords(), IVec2::new(3, -2));
+        assert_eq!(chunk.get_block(0, 0, 0), 1);
+        assert_eq!(chunk.get_block(15, 15, 15), 1);
+        assert_eq!(chunk.get_block(0, 16, 0), 0);
+        assert_eq!(chunk.get_highest_block(IVec2::new(7, 9)), 16);
+        assert!(chunk.get_biomes().iter().all(|b| *b == 1));
+    }
+
+    #[test]
+    fn chunk_packet_with_a_palette_per_section() {
+        // A superflat floor in section 0 and a pond ringed with flowers in section 2, with 4 and
+        // 5 bit palettes
+        let chunk = decode(include_bytes!("fixtures/chunk_multiple_palettes.bin"));
+
+        assert_eq!(*chunk.get_coords(), IVec2::new(-1, 0));
+        let present: Vec<usize> = (0..SECTIONS_PER_CHUNK)
+            .filter(|y| chunk.is_section_present(*y as i32))
+            .collect();
+        assert_eq!(present, [0, 2]);
+
+        let column: Vec<u32> = (0..5).map(|y| chunk.get_block(5, y, 5)).collect();
+        assert_eq!(column, [33, 10, 10, 9, 0]);
+        assert_eq!(chunk.get_block(4, 32, 4), 34);
+        assert_eq!(chunk.get_block(0, 32, 0), 1400);
+        assert_eq!(chunk.get_block(1, 32, 2), 1405);
+        assert_eq!(chunk.get_block(0, 33, 0), 0);
+
+        assert_eq!(chunk.get_highest_block(IVec2::new(8, 8)), 33);
+        assert_eq!(chunk.get_highest_block(IVec2::new(0, 0)), 4);
+        assert_eq!(chunk.get_biomes()[..4], [1, 4, 1, 4]);
+    }
+
+    #[test]
+    fn chunk_packet_with_global_ids() {
+        // One section with 15 bit global ids, every block (i * 7919) % 17000 + 1 for its index i
+        let chunk = decode(include_bytes!("fixtures/chunk_direct.bin"));
+
+        assert_eq!(*chunk.get_coords(), IVec2::new(0, 7));
+        assert!(!chunk.is_section_present(0));
+        assert_eq!(chunk.get_block(0, 16, 0), 1);
+        assert_eq!(chunk.get_block(3, 17, 2), 9430);
+        assert_eq!(chunk.get_block(15, 31, 15), 9306);
+        assert_eq!(chunk.get_highest_block(IVec2::new(15, 15)), 32);
+        assert_eq!(chunk.get_biomes()[79..82], [79, 0, 1]);
+    }
 }
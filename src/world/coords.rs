@@ -0,0 +1,138 @@
+//! Conversions between the coordinate spaces of the world. Blocks at negative coordinates
+//! belong to the chunk and section below them, so everything here rounds towards negative
+//! infinity rather than zero.
+
+use glam::{IVec2, IVec3};
+
+use super::{
+    chunks::{MAX_SECTION, MIN_SECTION},
+    ChunkCoords, ChunkLocation, SectionCoords, SectionLocation, WorldCoords,
+};
+
+/// Blocks along each side of a chunk section
+pub const SECTION_SIZE: i32 = 16;
+
+/// The chunk containing a block
+pub fn chunk_location(coords: &WorldCoords) -> ChunkLocation {
+    IVec2::new(
+        coords.x.div_euclid(SECTION_SIZE),
+        coords.z.div_euclid(SECTION_SIZE),
+    )
+}
+
+/// The chunk section containing a block
+pub fn section_location(coords: &WorldCoords) -> SectionLocation {
+    IVec3::new(
+        coords.x.div_euclid(SECTION_SIZE),
+        section_of_height(coords.y),
+        coords.z.div_euclid(SECTION_SIZE),
+    )
+}
+
+/// The y of the chunk section containing a block at height `y`
+pub fn section_of_height(y: i32) -> i32 {
+    y.div_euclid(SECTION_SIZE)
+}
+
+/// Where a block is within its chunk, keeping its height
+pub fn world_to_chunk(coords: &WorldCoords) -> ChunkCoords {
+    IVec3::new(
+        coords.x.rem_euclid(SECTION_SIZE),
+        coords.y,
+        coords.z.rem_euclid(SECTION_SIZE),
+    )
+}
+
+/// Where a block is within its chunk section
+pub fn world_to_section(coords: &WorldCoords) -> SectionCoords {
+    coords.rem_euclid(IVec3::splat(SECTION_SIZE))
+}
+
+/// Where a block within a chunk is within its chunk section
+pub fn chunk_to_section(coords: &ChunkCoords) -> SectionCoords {
+    IVec3::new(coords.x, coords.y.rem_euclid(SECTION_SIZE), coords.z)
+}
+
+/// Where a block within a chunk is in the world
+pub fn chunk_to_world(chunk: &ChunkLocation, coords: &ChunkCoords) -> WorldCoords {
+    IVec3::new(
+        chunk.x * SECTION_SIZE + coords.x,
+        coords.y,
+        chunk.y * SECTION_SIZE + coords.z,
+    )
+}
+
+/// Where a block within a chunk section is in the world
+pub fn section_to_world(section: &SectionLocation, coords: &SectionCoords) -> WorldCoords {
+    *section * SECTION_SIZE + *coords
+}
+
+/// The index of a section in its chunk's list of sections, or None if it's above or below the
+/// world
+pub fn section_index(y: i32) -> Option<usize> {
+    if (MIN_SECTION..=MAX_SECTION).contains(&y) {
+        usize::try_from(y - MIN_SECTION).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_blocks_belong_to_the_chunk_below() {
+        let cases = [
+            (-1, -1, 15),
+            (-16, -1, 0),
+            (-17, -2, 15),
+            (0, 0, 0),
+            (15, 0, 15),
+            (16, 1, 0),
+        ];
+        for (block, chunk, within) in cases {
+            let coords = IVec3::new(block, block, block);
+
+            assert_eq!(chunk_location(&coords), IVec2::splat(chunk), "{block}");
+            assert_eq!(section_location(&coords), IVec3::splat(chunk), "{block}");
+            assert_eq!(section_of_height(block), chunk, "{block}");
+            assert_eq!(
+                world_to_chunk(&coords),
+                IVec3::new(within, block, within),
+                "{block}"
+            );
+            assert_eq!(world_to_section(&coords), IVec3::splat(within), "{block}");
+            assert_eq!(
+                chunk_to_section(&world_to_chunk(&coords)),
+                IVec3::splat(within),
+                "{block}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_back_to_the_world() {
+        for block in [-33, -17, -16, -1, 0, 1, 15, 16, 31] {
+            let coords = IVec3::new(block, block + 7, -block);
+
+            let chunk = world_to_chunk(&coords);
+            assert_eq!(chunk_to_world(&chunk_location(&coords), &chunk), coords);
+
+            let section = world_to_section(&coords);
+            assert_eq!(
+                section_to_world(&section_location(&coords), &section),
+                coords
+            );
+        }
+    }
+
+    #[test]
+    fn section_indices_are_inside_the_world() {
+        assert_eq!(section_index(-1), None);
+        assert_eq!(section_index(MIN_SECTION), Some(0));
+        assert_eq!(section_index(MAX_SECTION), Some(15));
+        assert_eq!(section_index(MAX_SECTION + 1), None);
+        assert_eq!(section_index(section_of_height(-17)), None);
+    }
+}
@@ -0,0 +1,164 @@
+use mcproto_rs::v1_16_3::LightingData;
+
+use super::{
+    chunks::{block_pos_to_index, MIN_SECTION},
+    coords, ChunkCoords,
+};
+
+/// Light is sent for every section of a chunk plus one above and one below the world
+pub const LIGHT_SECTIONS: usize = 18;
+/// Two light levels are packed into each byte
+pub const LIGHT_ARRAY_LENGTH: usize = 2048;
+/// The brightest light level
+pub const MAX_LIGHT: u8 = 15;
+
+/// The light levels of each block in a chunk section, two to a byte
+#[derive(Debug, Clone)]
+pub struct LightArray(Box<[u8; LIGHT_ARRAY_LENGTH]>);
+
+impl LightArray {
+    pub fn new(data: [u8; LIGHT_ARRAY_LENGTH]) -> LightArray {
+        LightArray(Box::new(data))
+    }
+
+    /// The light level at an index into the section, even indices are in the low half of each
+    /// byte
+    pub fn get(&self, index: usize) -> u8 {
+        let byte = self.0[index / 2];
+        if index.is_multiple_of(2) {
+            byte & 0xf
+        } else {
+            byte >> 4
+        }
+    }
+
+    pub fn set(&mut self, index: usize, level: u8) {
+        let byte = &mut self.0[index / 2];
+        let level = level.min(MAX_LIGHT);
+        if index.is_multiple_of(2) {
+            *byte = (*byte & 0xf0) | level;
+        } else {
+            *byte = (*byte & 0x0f) | (level << 4);
+        }
+    }
+}
+
+/// The sky and block light of a chunk. Sections the server hasn't sent light for are fully lit
+/// by the sky and have no block light
+#[derive(Debug, Default)]
+pub struct ChunkLight {
+    sky: [Option<LightArray>; LIGHT_SECTIONS],
+    block: [Option<LightArray>; LIGHT_SECTIONS],
+}
+
+impl ChunkLight {
+    pub fn new() -> ChunkLight {
+        ChunkLight::default()
+    }
+
+    /// Take the sections included in a light update, leaving the others as they were
+    pub fn update(&mut self, sky: &LightingData, block: &LightingData) {
+        merge(&mut self.sky, sky);
+        merge(&mut self.block, block);
+    }
+
+    /// Light from the sky at coordinates within the chunk
+    pub fn get_sky_light(&self, coords: &ChunkCoords) -> u8 {
+        level_at(&self.sky, coords).unwrap_or(MAX_LIGHT)
+    }
+
+    /// Light from blocks like torches at coordinates within the chunk
+    pub fn get_block_light(&self, coords: &ChunkCoords) -> u8 {
+        level_at(&self.block, coords).unwrap_or(0)
+    }
+}
+
+fn merge(sections: &mut [Option<LightArray>; LIGHT_SECTIONS], update: &LightingData) {
+    for (section, data) in sections.iter_mut().zip(update.data.iter()) {
+        if let Some(data) = data {
+            *section = Some(LightArray::new(*data));
+        }
+    }
+}
+
+/// The light level at coordinates within a chunk, if there's light data for its section
+fn level_at(sections: &[Option<LightArray>; LIGHT_SECTIONS], coords: &ChunkCoords) -> Option<u8> {
+    // The first section is the one below the world
+    let index = coords::section_of_height(coords.y) - MIN_SECTION + 1;
+    let section = sections.get(usize::try_from(index).ok()?)?.as_ref()?;
+
+    Some(section.get(block_pos_to_index(&coords::chunk_to_section(coords))))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec3;
+
+    use super::*;
+
+    /// A section lit to `level` everywhere
+    fn filled(level: u8) -> LightArray {
+        LightArray::new([level | level << 4; LIGHT_ARRAY_LENGTH])
+    }
+
+    #[test]
+    fn levels_are_packed_two_to_a_byte() {
+        let mut light = LightArray::new([0; LIGHT_ARRAY_LENGTH]);
+        light.set(0, 3);
+        light.set(1, 12);
+        light.set(4095, 15);
+
+        assert_eq!(light.0[0], 0xc3);
+        assert_eq!(light.0[2047], 0xf0);
+        assert_eq!((light.get(0), light.get(1), light.get(2)), (3, 12, 0));
+        assert_eq!((light.get(4094), light.get(4095)), (0, 15));
+    }
+
+    #[test]
+    fn setting_one_level_keeps_its_neighbour() {
+        let mut light = filled(9);
+        for index in [10, 11] {
+            for level in 0..=MAX_LIGHT {
+                light.set(index, level);
+
+                assert_eq!(light.get(index), level);
+                assert_eq!(light.get(index ^ 1), 9);
+                light.set(index, 9);
+            }
+        }
+    }
+
+    #[test]
+    fn levels_are_clamped() {
+        let mut light = filled(0);
+        light.set(6, 200);
+        light.set(7, 16);
+
+        assert_eq!((light.get(6), light.get(7)), (MAX_LIGHT, MAX_LIGHT));
+        assert_eq!((light.get(5), light.get(8)), (0, 0));
+    }
+
+    #[test]
+    fn sections_include_one_below_and_above_the_world() {
+        let mut light = ChunkLight::new();
+        for (index, level) in [(0, 2), (1, 3), (16, 4), (17, 5)] {
+            light.block[index] = Some(filled(level));
+        }
+        light.sky[1] = Some(filled(4));
+
+        let block = |y| light.get_block_light(&IVec3::new(3, y, 5));
+        assert_eq!((block(-16), block(-1)), (2, 2));
+        assert_eq!((block(0), block(15)), (3, 3));
+        assert_eq!((block(16), block(239)), (0, 0));
+        assert_eq!((block(240), block(255)), (4, 4));
+        assert_eq!((block(256), block(271)), (5, 5));
+
+        // Outside the light sections there's no data
+        let sky = |y| light.get_sky_light(&IVec3::new(3, y, 5));
+        assert_eq!((block(-17), block(272)), (0, 0));
+        assert_eq!(
+            (sky(-17), sky(-1), sky(0), sky(16), sky(272)),
+            (15, 15, 4, 15, 15)
+        );
+    }
+}
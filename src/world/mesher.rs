@@ -0,0 +1,194 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::renderer::BlockVertex;
+
+use super::{
+    chunk_builder::{self, SectionNeighbours},
+    chunks::ChunkSection,
+    SectionLocation,
+};
+
+/// Most threads to mesh on, leaving the rest of the cores for the main thread and networking
+const MAX_WORKERS: usize = 4;
+/// How many sections each worker can have waiting, more stay queued in the world so the nearest
+/// ones can still jump ahead as the player moves
+const JOBS_PER_WORKER: usize = 4;
+
+/// Copies of a chunk section and the sections around it, so it can be meshed off the main thread
+pub struct MeshJob {
+    pub location: SectionLocation,
+    pub section: ChunkSection,
+    pub above: Option<ChunkSection>,
+    pub below: Option<ChunkSection>,
+    pub north: Option<ChunkSection>,
+    pub east: Option<ChunkSection>,
+    pub south: Option<ChunkSection>,
+    pub west: Option<ChunkSection>,
+}
+
+impl MeshJob {
+    /// Generate the mesh of the section, in world space
+    fn build(&self) -> Vec<BlockVertex> {
+        let neighbours = SectionNeighbours {
+            above: self.above.as_ref(),
+            below: self.below.as_ref(),
+            north: self.north.as_ref(),
+            east: self.east.as_ref(),
+            south: self.south.as_ref(),
+            west: self.west.as_ref(),
+        };
+
+        let offset = (self.location * 16).as_vec3();
+        let mut verts = chunk_builder::generate_mesh(&self.section, &neighbours);
+        for vert in &mut verts {
+            vert.position[0] += offset.x;
+            vert.position[1] += offset.y;
+            vert.position[2] += offset.z;
+        }
+        verts
+    }
+}
+
+/// A finished mesh and which job it was for
+struct MeshResult {
+    location: SectionLocation,
+    version: u64,
+    verts: Vec<BlockVertex>,
+}
+
+/// Meshes chunk sections on a pool of worker threads, handing the meshes back to be uploaded on
+/// the main thread. Only the newest mesh of each section is kept, so a section that changes while
+/// it's being meshed doesn't end up showing the old blocks.
+pub struct Mesher {
+    /// Dropping this stops the workers once they finish what they're doing
+    jobs: Sender<(u64, MeshJob)>,
+    results: Receiver<MeshResult>,
+    /// Sends results back to ourselves when there are no workers to do it
+    results_sender: Sender<MeshResult>,
+    workers: usize,
+    in_flight: usize,
+    /// The latest job for each section still being meshed
+    versions: HashMap<SectionLocation, u64>,
+    next_version: u64,
+}
+
+impl Mesher {
+    pub fn new() -> Mesher {
+        let (jobs, job_receiver) = mpsc::channel::<(u64, MeshJob)>();
+        let (results_sender, results) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let wanted = thread::available_parallelism()
+            .map_or(1, |n| n.get().saturating_sub(1))
+            .clamp(1, MAX_WORKERS);
+        let mut workers = 0;
+        for i in 0..wanted {
+            let jobs = job_receiver.clone();
+            let results = results_sender.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("Mesher {i}"))
+                .spawn(move || worker(&jobs, &results));
+            match spawned {
+                Ok(_) => workers += 1,
+                Err(e) => tracing::error!("Couldn't start mesher thread: {e}"),
+            }
+        }
+        if workers == 0 {
+            tracing::warn!("No mesher threads, meshing on the main thread instead");
+        }
+
+        Mesher {
+            jobs,
+            results,
+            results_sender,
+            workers,
+            in_flight: 0,
+            versions: HashMap::new(),
+            next_version: 0,
+        }
+    }
+
+    /// Whether there's room for another job, more should wait until some have finished
+    pub fn has_capacity(&self) -> bool {
+        self.in_flight < self.workers.max(1) * JOBS_PER_WORKER
+    }
+
+    /// Start meshing a section, replacing any job for it that's still going
+    pub fn queue(&mut self, job: MeshJob) {
+        let version = self.next_version;
+        self.next_version += 1;
+        self.versions.insert(job.location, version);
+        self.in_flight += 1;
+
+        if self.workers == 0 {
+            let verts = job.build();
+            let _ = self.results_sender.send(MeshResult {
+                location: job.location,
+                version,
+                verts,
+            });
+            return;
+        }
+
+        if self.jobs.send((version, job)).is_err() {
+            tracing::error!("Mesher threads have stopped");
+            self.in_flight -= 1;
+        }
+    }
+
+    /// Forget about sections that no longer need meshing, e.g. in unloaded chunks, so their
+    /// meshes are dropped when they finish
+    pub fn retain(&mut self, mut keep: impl FnMut(&SectionLocation) -> bool) {
+        self.versions.retain(|loc, _| keep(loc));
+    }
+
+    /// Meshes that have finished since this was last called, without any that were replaced or
+    /// forgotten while meshing
+    pub fn finished(&mut self) -> Vec<(SectionLocation, Vec<BlockVertex>)> {
+        let mut meshes = Vec::new();
+        while let Ok(result) = self.results.try_recv() {
+            self.in_flight = self.in_flight.saturating_sub(1);
+            if self.versions.get(&result.location) != Some(&result.version) {
+                continue;
+            }
+            self.versions.remove(&result.location);
+            meshes.push((result.location, result.verts));
+        }
+        meshes
+    }
+}
+
+impl Default for Mesher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mesh jobs until the mesher is dropped
+fn worker(jobs: &Mutex<Receiver<(u64, MeshJob)>>, results: &Sender<MeshResult>) {
+    loop {
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Ok((version, job)) = job else {
+            return;
+        };
+
+        let result = MeshResult {
+            location: job.location,
+            version,
+            verts: job.build(),
+        };
+        if results.send(result).is_err() {
+            return;
+        }
+    }
+}
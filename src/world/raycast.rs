@@ -0,0 +1,378 @@
+use glam::{DVec3, IVec3};
+
+use crate::{
+    entities::Entity,
+    resources::{self, BlockState},
+};
+
+use super::{block_coords, chunks::Chunk, World, WorldCoords};
+
+/// A side of a block, in the order the protocol numbers them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFace {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl BlockFace {
+    /// Unit vector pointing out of the block through this face
+    pub fn normal(&self) -> IVec3 {
+        match self {
+            BlockFace::Down => IVec3::NEG_Y,
+            BlockFace::Up => IVec3::Y,
+            BlockFace::North => IVec3::NEG_Z,
+            BlockFace::South => IVec3::Z,
+            BlockFace::West => IVec3::NEG_X,
+            BlockFace::East => IVec3::X,
+        }
+    }
+
+    /// The face on an axis (0 for x, 1 for y, 2 for z) that points the positive or negative way
+    fn on_axis(axis: usize, positive: bool) -> BlockFace {
+        match (axis, positive) {
+            (0, false) => BlockFace::West,
+            (0, true) => BlockFace::East,
+            (1, false) => BlockFace::Down,
+            (1, true) => BlockFace::Up,
+            (2, false) => BlockFace::North,
+            _ => BlockFace::South,
+        }
+    }
+}
+
+/// The block a ray ran into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub position: WorldCoords,
+    /// The side of the block the ray came in through
+    pub face: BlockFace,
+    /// Where exactly the ray touched the block
+    pub point: DVec3,
+    /// How far along the ray the block was
+    pub distance: f64,
+    /// Id of the block state that was hit
+    pub block: u32,
+}
+
+/// The entity a ray ran into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityHit {
+    pub id: i32,
+    /// Where the ray touched the entity's bounding box
+    pub point: DVec3,
+    /// How far along the ray the entity was
+    pub distance: f64,
+}
+
+/// Whether a ray stops at a block, passing through anything without a collision shape like air.
+/// Fluids have none either, so they only stop it when `fluids` is set.
+fn is_targetable(block: &BlockState, fluids: bool) -> bool {
+    if block.is_fluid() {
+        fluids
+    } else {
+        !resources::collision_boxes(block).is_empty()
+    }
+}
+
+impl World {
+    /// Find the first block a ray from `origin` runs into within `max_distance` blocks, passing
+    /// through fluids. Gives up at chunks that haven't loaded yet.
+    pub fn raycast(&self, origin: DVec3, direction: DVec3, max_distance: f64) -> Option<RayHit> {
+        self.cast_ray(origin, direction, max_distance, false)
+    }
+
+    /// Like `raycast` but stopping at fluids too, e.g. for filling a bucket
+    pub fn raycast_fluids(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_distance: f64,
+    ) -> Option<RayHit> {
+        self.cast_ray(origin, direction, max_distance, true)
+    }
+
+    /// Walk through the grid one block at a time along the ray until it reaches a block
+    fn cast_ray(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_distance: f64,
+        fluids: bool,
+    ) -> Option<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == DVec3::ZERO {
+            return None;
+        }
+
+        let mut pos = block_coords(&origin);
+        let step = IVec3::new(
+            if direction.x < 0.0 { -1 } else { 1 },
+            if direction.y < 0.0 { -1 } else { 1 },
+            if direction.z < 0.0 { -1 } else { 1 },
+        );
+
+        // Distance along the ray to cross a whole block, and to reach the next block boundary,
+        // on each axis. Axes the ray doesn't move along are never crossed.
+        let mut t_delta = DVec3::splat(f64::INFINITY);
+        let mut t_max = DVec3::splat(f64::INFINITY);
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                continue;
+            }
+            t_delta[axis] = direction[axis].recip().abs();
+            let boundary = if step[axis] > 0 {
+                f64::from(pos[axis] + 1)
+            } else {
+                f64::from(pos[axis])
+            };
+            t_max[axis] = (boundary - origin[axis]) / direction[axis];
+        }
+
+        // Starting inside a block counts as entering it through the face the ray would have come
+        // in through on the axis it moves along the most
+        let abs = direction.abs();
+        let major = if abs.x >= abs.y && abs.x >= abs.z {
+            0
+        } else if abs.y >= abs.z {
+            1
+        } else {
+            2
+        };
+        let mut face = BlockFace::on_axis(major, direction[major] < 0.0);
+        let mut distance = 0.0;
+
+        loop {
+            if !self.is_chunk_loaded(&Chunk::chunk_containing(&pos)) {
+                return None;
+            }
+            if let Some(block) = self.block_at(&pos) {
+                if is_targetable(block, fluids) {
+                    return Some(RayHit {
+                        position: pos,
+                        face,
+                        point: origin + direction * distance,
+                        distance,
+                        block: block.id,
+                    });
+                }
+            }
+
+            let axis = if t_max.x <= t_max.y && t_max.x <= t_max.z {
+                0
+            } else if t_max.y <= t_max.z {
+                1
+            } else {
+                2
+            };
+            distance = t_max[axis];
+            if distance > max_distance {
+                return None;
+            }
+
+            pos[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+            // Moving into a block goes through its side facing back the way we came
+            face = BlockFace::on_axis(axis, step[axis] < 0);
+        }
+    }
+}
+
+/// Find the closest entity a ray from `origin` passes through within `max_distance` blocks
+pub fn raycast_entities<'a>(
+    entities: impl IntoIterator<Item = &'a Entity>,
+    origin: DVec3,
+    direction: DVec3,
+    max_distance: f64,
+) -> Option<EntityHit> {
+    let direction = direction.normalize_or_zero();
+    if direction == DVec3::ZERO {
+        return None;
+    }
+
+    entities
+        .into_iter()
+        .filter_map(|entity| {
+            let distance = entity.get_bounding_box()?.ray_distance(origin, direction)?;
+            Some(EntityHit {
+                id: entity.id,
+                point: origin + direction * distance,
+                distance,
+            })
+        })
+        .filter(|hit| hit.distance <= max_distance)
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec2;
+
+    use crate::{resources::entities, world::test_block};
+
+    use super::*;
+
+    const EYES: DVec3 = DVec3::new(0.5, 64.5, 0.5);
+
+    /// A world with the chunks from -1 to 1 on each axis loaded, empty apart from `placed`
+    fn world(placed: &[(WorldCoords, &str)]) -> World {
+        let mut world = World::new();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                world.insert_chunk(Chunk::empty(IVec2::new(x, z)));
+            }
+        }
+        for (coords, name) in placed {
+            world.set_block(coords, test_block(name));
+        }
+        world
+    }
+
+    fn kind(name: &str) -> &'static resources::Entity {
+        entities()
+            .values()
+            .find(|e| e.name == name)
+            .unwrap_or_else(|| panic!("No entity called {name}"))
+    }
+
+    fn entity(name: &str, id: i32, pos: DVec3) -> Entity {
+        let mut entity = Entity::new(kind(name).id);
+        entity.id = id;
+        entity.pos = pos;
+        entity
+    }
+
+    #[test]
+    fn hits_the_face_facing_the_ray() {
+        let world = world(&[(IVec3::new(5, 64, 0), "Stone")]);
+
+        let hit = world.raycast(EYES, DVec3::X, 10.0).unwrap();
+        assert_eq!(hit.position, IVec3::new(5, 64, 0));
+        assert_eq!(hit.face, BlockFace::West);
+        assert_eq!(hit.distance, 4.5);
+        assert_eq!(hit.point, DVec3::new(5.0, 64.5, 0.5));
+        assert_eq!(hit.block, u32::from(test_block("Stone")));
+
+        let hit = world
+            .raycast(DVec3::new(5.5, 70.5, 0.5), DVec3::NEG_Y, 10.0)
+            .unwrap();
+        assert_eq!((hit.face, hit.distance), (BlockFace::Up, 5.5));
+        assert_eq!(hit.point, DVec3::new(5.5, 65.0, 0.5));
+    }
+
+    #[test]
+    fn stops_at_the_max_distance() {
+        let world = world(&[(IVec3::new(5, 64, 0), "Stone")]);
+
+        assert_eq!(world.raycast(EYES, DVec3::X, 4.4), None);
+        assert!(world.raycast(EYES, DVec3::X, 4.5).is_some());
+        assert_eq!(world.raycast(EYES, DVec3::ZERO, 10.0), None);
+    }
+
+    #[test]
+    fn starting_inside_a_block() {
+        let world = world(&[(IVec3::new(0, 64, 0), "Stone")]);
+
+        let hit = world
+            .raycast(EYES, DVec3::new(0.2, -1.0, 0.3), 5.0)
+            .unwrap();
+        assert_eq!(hit.position, IVec3::new(0, 64, 0));
+        assert_eq!((hit.face, hit.distance), (BlockFace::Up, 0.0));
+        assert_eq!(hit.point, EYES);
+    }
+
+    #[test]
+    fn grazing_along_block_edges() {
+        let world = world(&[
+            (IVec3::new(3, 64, 0), "Stone"),
+            (IVec3::new(3, 64, 1), "Stone"),
+            (IVec3::new(3, 65, 0), "Stone"),
+            (IVec3::new(6, 65, 1), "Stone"),
+        ]);
+
+        let hit = world
+            .raycast(DVec3::new(0.5, 65.0, 1.0), DVec3::X, 10.0)
+            .unwrap();
+        assert_eq!(hit.position, IVec3::new(6, 65, 1));
+        assert_eq!((hit.face, hit.distance), (BlockFace::West, 5.5));
+    }
+
+    #[test]
+    fn crosses_into_loaded_chunks() {
+        let world = world(&[(IVec3::new(-3, 64, 0), "Stone")]);
+
+        let hit = world.raycast(EYES, DVec3::NEG_X, 10.0).unwrap();
+        assert_eq!(hit.position, IVec3::new(-3, 64, 0));
+        assert_eq!((hit.face, hit.distance), (BlockFace::East, 2.5));
+    }
+
+    #[test]
+    fn stops_at_unloaded_chunks() {
+        let mut world = world(&[]);
+        world.insert_chunk(Chunk::empty(IVec2::new(2, 0)));
+        world.set_block(&IVec3::new(40, 64, 0), test_block("Stone"));
+        world.unload_chunk(&IVec2::new(1, 0));
+
+        assert_eq!(world.raycast(EYES, DVec3::X, 100.0), None);
+        assert_eq!(world.raycast(EYES, DVec3::NEG_Z, f64::INFINITY), None);
+    }
+
+    #[test]
+    fn passes_through_blocks_without_collision() {
+        let world = world(&[
+            (IVec3::new(2, 64, 0), "Poppy"),
+            (IVec3::new(4, 64, 0), "Stone"),
+        ]);
+
+        let hit = world.raycast(EYES, DVec3::X, 10.0).unwrap();
+        assert_eq!(hit.position, IVec3::new(4, 64, 0));
+    }
+
+    #[test]
+    fn fluids_only_stop_fluid_rays() {
+        let world = world(&[
+            (IVec3::new(2, 64, 0), "Water"),
+            (IVec3::new(4, 64, 0), "Stone"),
+        ]);
+
+        let hit = world.raycast(EYES, DVec3::X, 10.0).unwrap();
+        assert_eq!((hit.position, hit.distance), (IVec3::new(4, 64, 0), 3.5));
+
+        let hit = world.raycast_fluids(EYES, DVec3::X, 10.0).unwrap();
+        assert_eq!((hit.position, hit.distance), (IVec3::new(2, 64, 0), 1.5));
+        assert_eq!(hit.block, u32::from(test_block("Water")));
+    }
+
+    #[test]
+    fn hits_the_closest_entity() {
+        let half_width = f64::from(kind("Cow").width) / 2.0;
+        let mut unknown = Entity::new(u32::MAX);
+        unknown.pos = DVec3::new(2.0, 64.0, 0.5);
+        let entities = [
+            entity("Cow", 1, DVec3::new(8.0, 64.0, 0.5)),
+            entity("Cow", 2, DVec3::new(5.0, 64.0, 0.5)),
+            entity("Cow", 3, DVec3::new(5.0, 64.0, 3.5)),
+            unknown,
+        ];
+
+        let hit = raycast_entities(&entities, EYES, DVec3::X, 10.0).unwrap();
+        let distance = 5.0 - half_width - EYES.x;
+        assert_eq!(hit.id, 2);
+        assert!((hit.distance - distance).abs() < 1e-9);
+        assert!((hit.point - (EYES + DVec3::X * distance)).length() < 1e-9);
+
+        assert_eq!(raycast_entities(&entities, EYES, DVec3::X, 4.0), None);
+        assert_eq!(raycast_entities(&entities, EYES, DVec3::NEG_X, 10.0), None);
+    }
+
+    #[test]
+    fn starting_inside_an_entity() {
+        let entities = [entity("Cow", 1, DVec3::new(0.5, 64.0, 0.5))];
+
+        let hit = raycast_entities(&entities, EYES, DVec3::Y, 10.0).unwrap();
+        assert_eq!((hit.id, hit.distance, hit.point), (1, 0.0, EYES));
+    }
+}
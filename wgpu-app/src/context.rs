@@ -1,15 +1,18 @@
 use egui::ViewportId;
 use egui_wgpu::ScreenDescriptor;
 use egui_winit::EventResponse;
-use wgpu::{CommandEncoder, TextureFormat, TextureView};
+use wgpu::{CommandEncoder, PresentMode, TextureFormat, TextureView};
 use winit::{
     dpi::PhysicalSize,
+    error::ExternalError,
     event::{Event, WindowEvent},
     event_loop::EventLoopWindowTarget,
     keyboard::{KeyCode, PhysicalKey},
-    window::Window,
+    window::{CursorGrabMode, Window},
 };
 
+#[cfg(feature = "gamepad")]
+use crate::io::gamepad::Gamepad;
 use crate::io::{keyboard::Keyboard, mouse::Mouse};
 
 /// `Context` stores some useful things you might want to use in your app, including input from a Keyboard and Mouse,
@@ -20,16 +23,22 @@ pub struct Context<'a> {
 
     pub mouse: Mouse,
     pub keyboard: Keyboard,
+    #[cfg(feature = "gamepad")]
+    pub gamepad: Gamepad,
     /// If true, Egui will not process new window events
     pub block_gui_input: bool,
     /// If true, Egui will not receive keyboard inputs for the tab key.
     pub block_gui_tab_input: bool,
+
+    mouse_grabbed: bool,
 }
 
 /// Convenience struct to manage the required state to use Egui
 pub struct EguiManager {
     renderer: egui_wgpu::Renderer,
     state: egui_winit::State,
+    /// How much bigger to draw the gui than the window's own scale
+    ui_scale: f32,
 }
 
 /// Convenience struct holding everything you need to get rendering with Wgpu
@@ -40,10 +49,80 @@ pub struct WgpuState<'a> {
     pub config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
     pub window: &'a Window,
+
+    depth_texture: wgpu::Texture,
+    depth_view: TextureView,
+    /// Present modes the surface supports
+    present_modes: Vec<PresentMode>,
 }
 
 impl<'a> WgpuState<'a> {
-    /// Reconfigure the Wgpu surface for the given size
+    /// Format of the depth texture, pipelines that draw with depth testing need to use this
+    pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+    pub fn new(
+        surface: wgpu::Surface<'a>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        size: winit::dpi::PhysicalSize<u32>,
+        window: &'a Window,
+        present_modes: Vec<PresentMode>,
+    ) -> WgpuState<'a> {
+        let (depth_texture, depth_view) = Self::create_depth_texture(&device, &config);
+
+        WgpuState {
+            surface,
+            device,
+            queue,
+            config,
+            size,
+            window,
+
+            depth_texture,
+            depth_view,
+            present_modes,
+        }
+    }
+
+    /// Change how frames are presented and reconfigure the surface, falling back to `Fifo` (which
+    /// is always supported) if the surface doesn't support `mode`
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        let mode = if self.present_modes.contains(&mode) {
+            mode
+        } else {
+            log::warn!("Present mode {mode:?} isn't supported, falling back to Fifo");
+            PresentMode::Fifo
+        };
+        if mode == self.config.present_mode {
+            return;
+        }
+
+        log::info!("Using present mode {mode:?}");
+        self.config.present_mode = mode;
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Wait for vertical sync with `Fifo`, or present frames straight away with `Immediate` or
+    /// `Mailbox` if the surface supports either
+    pub fn set_vsync(&mut self, vsync: bool) {
+        let mode = if vsync {
+            PresentMode::Fifo
+        } else {
+            [PresentMode::Immediate, PresentMode::Mailbox]
+                .into_iter()
+                .find(|m| self.present_modes.contains(m))
+                .unwrap_or(PresentMode::Fifo)
+        };
+        self.set_present_mode(mode);
+    }
+
+    #[must_use]
+    pub fn get_present_mode(&self) -> PresentMode {
+        self.config.present_mode
+    }
+
+    /// Reconfigure the Wgpu surface and depth texture for the given size
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
         if size.width < 16 || size.height < 16 {
             return;
@@ -52,6 +131,76 @@ impl<'a> WgpuState<'a> {
         self.config.height = size.height;
         self.surface.configure(&self.device, &self.config);
         self.size = size;
+
+        let (depth_texture, depth_view) = Self::create_depth_texture(&self.device, &self.config);
+        self.depth_texture = depth_texture;
+        self.depth_view = depth_view;
+    }
+
+    /// The depth texture, which always matches the size of the surface
+    pub fn depth_texture(&self) -> &wgpu::Texture {
+        &self.depth_texture
+    }
+
+    /// A view of the depth texture to attach to render passes
+    pub fn depth_view(&self) -> &TextureView {
+        &self.depth_view
+    }
+
+    /// Begin a render pass drawing to `view` with the depth texture attached, usually the first
+    /// pass of a frame. The depth texture is always cleared, the colour only if `clear_color` is
+    /// `Some`, e.g. from `Application::clear_color`.
+    pub fn begin_cleared_pass<'p>(
+        &'p self,
+        encoder: &'p mut CommandEncoder,
+        view: &'p TextureView,
+        clear_color: Option<wgpu::Color>,
+    ) -> wgpu::RenderPass<'p> {
+        let load = clear_color.map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear);
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Cleared Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        })
+    }
+
+    fn create_depth_texture(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> (wgpu::Texture, TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Texture"),
+            size: wgpu::Extent3d {
+                width: config.width.max(1),
+                height: config.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view)
     }
 }
 
@@ -63,13 +212,17 @@ impl<'a> Context<'a> {
 
             mouse: Mouse::new(),
             keyboard: Keyboard::new(),
+            #[cfg(feature = "gamepad")]
+            gamepad: Gamepad::new(),
             block_gui_input: false,
             block_gui_tab_input: false,
+
+            mouse_grabbed: false,
         }
     }
 
     /// This function is automatically called in the application loop, you shouldn't need to call it yourself
-    pub fn handle_event(&mut self, event: &Event<()>) {
+    pub fn handle_event<T>(&mut self, event: &Event<T>) {
         self.keyboard.handle_event(event);
         self.mouse.handle_event(event);
 
@@ -78,12 +231,23 @@ impl<'a> Context<'a> {
             event,
         } = event
         {
+            // The OS lets go of the mouse when the window loses focus, so it needs grabbing again
+            if let winit::event::WindowEvent::Focused(_) = event {
+                self.mouse_grabbed = false;
+            }
+
             if let winit::event::WindowEvent::Resized(new_size) = event {
                 self.wgpu_state.resize(*new_size);
                 let _ = self.egui.on_event(self.wgpu_state.window, event);
                 return;
             }
 
+            // egui needs the new scale even while it isn't taking input
+            if let winit::event::WindowEvent::ScaleFactorChanged { .. } = event {
+                let _ = self.egui.on_event(self.wgpu_state.window, event);
+                return;
+            }
+
             if self.block_gui_input {
                 return;
             }
@@ -119,29 +283,45 @@ impl<'a> Context<'a> {
     //     ScreenDescriptor { size_in_pixels: , pixels_per_point: () }
     // }
 
-    // Attempts to restrict the mouse movement to inside the window
-    //
-    // # Errors:
-    // This function can fail for a number of reasons, a common one might be that the mouse is already grabbed by another application or the OS
-    // this does happen occasionally such as if the user grabs the title bar of the window to drag it around on many Linux machines
-    // so be a little careful on when you try to grab the mouse, such as when receiving focus.
-    // pub fn set_mouse_grabbed(&self, grabbed: bool) -> Result<(), ExternalError> {
-    //     let gl_win = self.dis.gl_window();
-    //     let win = gl_win.window();
-    //
-    //     win.set_cursor_grab(grabbed)
-    // }
+    /// Attempts to restrict the mouse movement to inside the window. The mouse is locked in place
+    /// where the platform supports it, otherwise it's confined to the window.
+    ///
+    /// The requested state is remembered even if this fails, so `is_mouse_grabbed` won't cause it
+    /// to be retried every frame. It's reset when the window gains or loses focus.
+    ///
+    /// # Errors
+    /// This function can fail for a number of reasons, a common one might be that the mouse is already grabbed by another application or the OS
+    /// this does happen occasionally such as if the user grabs the title bar of the window to drag it around on many Linux machines
+    /// so be a little careful on when you try to grab the mouse, such as when receiving focus.
+    pub fn set_mouse_grabbed(&mut self, grabbed: bool) -> Result<(), ExternalError> {
+        self.mouse_grabbed = grabbed;
 
-    // Sets the mouse visible or invisible
-    // pub fn set_mouse_visible(&self, visible: bool) {
-    //     let gl_win = self.dis.gl_window();
-    //     let win = gl_win.window();
-    //
-    //     win.set_cursor_visible(visible);
-    // }
+        let window = self.wgpu_state.window;
+        if grabbed {
+            window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+        } else {
+            window.set_cursor_grab(CursorGrabMode::None)
+        }
+    }
+
+    /// Returns if the mouse was last set to be grabbed
+    #[must_use]
+    pub const fn is_mouse_grabbed(&self) -> bool {
+        self.mouse_grabbed
+    }
+
+    /// Sets the mouse visible or invisible
+    pub fn set_mouse_visible(&self, visible: bool) {
+        self.wgpu_state.window.set_cursor_visible(visible);
+    }
 }
 
 impl EguiManager {
+    pub const MIN_UI_SCALE: f32 = 0.5;
+    pub const MAX_UI_SCALE: f32 = 3.0;
+
     /// Setup everything required to render Egui
     pub fn new<T>(
         device: &wgpu::Device,
@@ -157,9 +337,21 @@ impl EguiManager {
                 None,
                 Some(device.limits().max_texture_dimension_2d as usize),
             ),
+            ui_scale: 1.0,
         }
     }
 
+    /// Scale the gui on top of the window's scale factor, clamped to between
+    /// `MIN_UI_SCALE` and `MAX_UI_SCALE`
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.ui_scale = scale.clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE);
+    }
+
+    #[must_use]
+    pub const fn get_ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
     /// Update egui state
     pub fn on_event(
         &mut self,
@@ -204,15 +396,17 @@ impl EguiManager {
         encoder: &mut CommandEncoder,
         run_ui: impl FnOnce(&egui::Context),
     ) {
+        #[allow(clippy::cast_possible_truncation)]
+        let pixels_per_point = wgpu_state.window.scale_factor() as f32 * self.ui_scale;
         let input = self.state.take_egui_input(wgpu_state.window);
+        self.state.egui_ctx().set_pixels_per_point(pixels_per_point);
         let run_output = self.state.egui_ctx().run(input, run_ui);
         self.state
             .handle_platform_output(wgpu_state.window, run_output.platform_output);
 
         let screen_descriptor = ScreenDescriptor {
             size_in_pixels: [wgpu_state.config.width, wgpu_state.config.height],
-            #[allow(clippy::cast_possible_truncation)]
-            pixels_per_point: wgpu_state.window.scale_factor() as f32,
+            pixels_per_point,
         };
 
         let clipped_primitives = self
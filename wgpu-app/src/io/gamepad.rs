@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use gilrs::{Axis, EventType, GamepadId, Gilrs};
+
+pub use gilrs::Button;
+
+/// Stick values smaller than this are treated as 0, to stop worn sticks from drifting
+const DEADZONE: f32 = 0.15;
+
+/// Input from the most recently used gamepad
+pub struct Gamepad {
+    /// None if the platform's gamepad backend couldn't be started
+    gilrs: Option<Gilrs>,
+    active: Option<GamepadId>,
+
+    buttons: HashMap<Button, bool>,
+    this_frame: HashMap<Button, bool>,
+}
+
+impl Gamepad {
+    #[must_use]
+    pub fn new() -> Self {
+        let gilrs = Gilrs::new()
+            .map_err(|e| log::error!("Couldn't initialise gamepad support: {e}"))
+            .ok();
+
+        Self {
+            gilrs,
+            active: None,
+
+            buttons: HashMap::new(),
+            this_frame: HashMap::new(),
+        }
+    }
+
+    fn press(&mut self, button: Button) {
+        self.buttons.insert(button, true);
+        self.this_frame.insert(button, true);
+    }
+
+    fn release(&mut self, button: Button) {
+        self.buttons.insert(button, false);
+        self.this_frame.insert(button, true);
+    }
+
+    /// Process any new gamepad events, this function is called automatically in the application
+    /// loop so you shouldn't need to call it yourself.
+    pub fn poll(&mut self) {
+        while let Some(event) = self.gilrs.as_mut().and_then(Gilrs::next_event) {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    self.active = Some(event.id);
+                    self.press(button);
+                }
+                EventType::ButtonReleased(button, _) => self.release(button),
+                EventType::AxisChanged(..) => self.active = Some(event.id),
+                EventType::Connected => log::info!("Gamepad connected"),
+                EventType::Disconnected => {
+                    log::info!("Gamepad disconnected");
+                    if self.active == Some(event.id) {
+                        self.active = None;
+                        self.buttons.clear();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns if a gamepad has been used and is still connected
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Returns if this button was pressed down on this frame
+    #[must_use]
+    pub fn pressed_this_frame(&self, button: Button) -> bool {
+        self.is_pressed(button) && self.this_frame.get(&button).copied().unwrap_or(false)
+    }
+
+    /// Returns if this button was released on this frame
+    #[must_use]
+    pub fn released_this_frame(&self, button: Button) -> bool {
+        !self.is_pressed(button) && self.this_frame.get(&button).copied().unwrap_or(false)
+    }
+
+    /// Returns if the button is currently held down
+    #[must_use]
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.buttons.get(&button).copied().unwrap_or(false)
+    }
+
+    /// Position of the left stick from -1 to 1 on each axis, where up is positive y
+    #[must_use]
+    pub fn left_stick(&self) -> (f32, f32) {
+        (self.axis(Axis::LeftStickX), self.axis(Axis::LeftStickY))
+    }
+
+    /// Position of the right stick from -1 to 1 on each axis, where up is positive y
+    #[must_use]
+    pub fn right_stick(&self) -> (f32, f32) {
+        (self.axis(Axis::RightStickX), self.axis(Axis::RightStickY))
+    }
+
+    fn axis(&self, axis: Axis) -> f32 {
+        let (Some(gilrs), Some(id)) = (&self.gilrs, self.active) else {
+            return 0.0;
+        };
+
+        let value = gilrs.gamepad(id).value(axis);
+        if value.abs() < DEADZONE {
+            0.0
+        } else {
+            value
+        }
+    }
+
+    /// Resets the Gamepad for the next frame, this function is called automatically so you shouldn't need to call this function yourself.
+    pub fn next_frame(&mut self) {
+        self.this_frame.clear();
+    }
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
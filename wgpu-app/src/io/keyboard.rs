@@ -1,4 +1,4 @@
-use egui_winit::winit::event::{ElementState, Event, WindowEvent};
+use egui_winit::winit::event::{ElementState, Event, Ime, WindowEvent};
 use winit::{
     event::KeyEvent,
     keyboard::{KeyCode, PhysicalKey},
@@ -9,6 +9,8 @@ use std::collections::HashMap;
 pub struct Keyboard {
     keys: HashMap<KeyCode, bool>,
     this_frame: HashMap<KeyCode, bool>,
+    /// Characters typed this frame, after the keyboard layout and any IME have had their say
+    text: String,
 }
 
 impl Keyboard {
@@ -17,6 +19,7 @@ impl Keyboard {
         Self {
             keys: HashMap::new(),
             this_frame: HashMap::new(),
+            text: String::new(),
         }
     }
 
@@ -31,29 +34,47 @@ impl Keyboard {
     }
 
     /// This function is called automatically in the application loop, you shouldn't be calling this yourself.
-    pub fn handle_event(&mut self, event: &Event<()>) {
-        if let Event::WindowEvent {
-            window_id: _,
-            event:
-                WindowEvent::KeyboardInput {
-                    event:
-                        KeyEvent {
-                            physical_key: PhysicalKey::Code(key_code),
-                            state,
-                            ..
-                        },
-                    ..
-                },
-        } = event
-        {
-            if *state == ElementState::Pressed {
-                self.press(*key_code);
-            } else {
-                self.release(*key_code);
+    pub fn handle_event<T>(&mut self, event: &Event<T>) {
+        let Event::WindowEvent { event, .. } = event else {
+            return;
+        };
+
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key,
+                        state,
+                        text,
+                        ..
+                    },
+                ..
+            } => {
+                if let PhysicalKey::Code(key_code) = physical_key {
+                    if *state == ElementState::Pressed {
+                        self.press(*key_code);
+                    } else {
+                        self.release(*key_code);
+                    }
+                }
+
+                // Keys like backspace and enter come through as control characters, they're
+                // better handled by their key codes
+                if let (ElementState::Pressed, Some(text)) = (state, text) {
+                    self.text.extend(text.chars().filter(|c| !c.is_control()));
+                }
             }
+            WindowEvent::Ime(Ime::Commit(text)) => self.text.push_str(text),
+            _ => {}
         }
     }
 
+    /// Take the text typed since the start of the frame, so it's only used once. Anything not
+    /// taken is thrown away at the end of the frame.
+    pub fn take_text_input(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+
     /// Returns if this key was pressed down on this frame
     #[must_use]
     pub fn pressed_this_frame(&self, key: KeyCode) -> bool {
@@ -90,6 +111,7 @@ impl Keyboard {
     /// Resets the Keyboard for the next frame, this function is called automatically so you shouldn't need to call this function yourself.
     pub fn next_frame(&mut self) {
         self.this_frame.clear();
+        self.text.clear();
     }
 }
 
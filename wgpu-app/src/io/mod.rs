@@ -1,2 +1,4 @@
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
 pub mod keyboard;
 pub mod mouse;
@@ -2,6 +2,9 @@ use egui_winit::winit::event::{
     DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent,
 };
 
+/// Roughly how many pixels a touchpad scrolls for each line a mouse wheel would
+const PIXELS_PER_LINE: f64 = 20.0;
+
 pub struct Mouse {
     this_frame: [bool; 10],
     pressed: [bool; 10],
@@ -57,7 +60,7 @@ impl Mouse {
     }
 
     /// This function is called automatically in the application loop, you shouldn't be calling this yourself.
-    pub fn handle_event(&mut self, event: &Event<()>) {
+    pub fn handle_event<T>(&mut self, event: &Event<T>) {
         match event {
             Event::WindowEvent {
                 window_id: _,
@@ -81,15 +84,14 @@ impl Mouse {
                         MouseButton::Left => 0,
                         MouseButton::Middle => 1,
                         MouseButton::Right => 2,
+                        MouseButton::Back => 3,
+                        MouseButton::Forward => 4,
                         MouseButton::Other(bnum) => {
                             if bnum > &9_u16 {
                                 return;
                             }
                             *bnum
                         }
-                        MouseButton::Back | MouseButton::Forward => {
-                            return;
-                        }
                     };
 
                     if *state == ElementState::Pressed {
@@ -105,6 +107,18 @@ impl Mouse {
                 } => {
                     self.scroll((*x, *y));
                 }
+                // Touchpads scroll smoothly in pixels, count them in lines like a mouse wheel
+                WindowEvent::MouseWheel {
+                    device_id: _,
+                    delta: MouseScrollDelta::PixelDelta(pos),
+                    ..
+                } => {
+                    #[allow(clippy::cast_possible_truncation)]
+                    self.scroll((
+                        (pos.x / PIXELS_PER_LINE) as f32,
+                        (pos.y / PIXELS_PER_LINE) as f32,
+                    ));
+                }
                 WindowEvent::Focused(focused) => {
                     self.focused = *focused;
                 }
@@ -147,7 +161,8 @@ impl Mouse {
         self.wheel
     }
 
-    /// Returns if the provided mouse button is currently held down
+    /// Returns if the provided mouse button is currently held down. Buttons are numbered left,
+    /// middle, right, back then forward from 0
     #[must_use]
     pub const fn is_pressed(&self, button: usize) -> bool {
         self.pressed[button]
@@ -5,7 +5,7 @@ pub mod io;
 pub mod timer;
 pub mod utils;
 
-use egui_winit::winit::event_loop::EventLoop;
+use egui_winit::winit::event_loop::{EventLoop, EventLoopProxy};
 pub use timer::Timer;
 
 use wgpu::{Adapter, Surface};
@@ -15,7 +15,13 @@ use winit::{
 };
 
 /// Implement this trait to run it with `run` or `run_with_context`!
-pub trait Application {
+///
+/// `T` is the type of user events that can be sent to the event loop through an
+/// `EventLoopProxy`, which arrive in `Self::handle_event` as `Event::UserEvent`.
+pub trait Application<T: 'static = ()> {
+    /// Called once before `Self::init` with a proxy that can send user events to the event loop,
+    /// e.g. to wake it up from another thread
+    fn set_event_proxy(&mut self, _proxy: EventLoopProxy<T>) {}
     /// This function is called after everything is setup but before the first frame is rendered
     fn init(&mut self, ctx: &mut Context);
     /// Called every frame to give the application a chance to update, the timer provides information like the time since the last frame and the current frame rate
@@ -26,8 +32,14 @@ pub trait Application {
     fn render(&mut self, t: &Timer, ctx: &mut Context) -> Result<(), wgpu::SurfaceError>;
     /// Called when the window is requested to close
     fn close(&mut self, ctx: &Context);
+    /// The colour to clear the screen to at the start of each frame, for use with
+    /// `WgpuState::begin_cleared_pass`. Returning `None` skips the clear, leaving whatever was
+    /// drawn to the surface before.
+    fn clear_color(&self) -> Option<wgpu::Color> {
+        Some(wgpu::Color::BLACK)
+    }
     /// Called a number of times between each frame with all new incoming events for the application
-    fn handle_event(&mut self, ctx: &mut Context, event: &Event<()>);
+    fn handle_event(&mut self, ctx: &mut Context, event: &Event<T>);
 }
 
 /// Create and run a window for this application
@@ -39,8 +51,8 @@ pub trait Application {
 ///
 /// # Panics
 /// If no suitable surface or adapter could be found
-pub fn run<A: 'static + Application>(app: A, wb: WindowBuilder) {
-    let event_loop = winit::event_loop::EventLoopBuilder::new()
+pub fn run<T: 'static, A: 'static + Application<T>>(app: A, wb: WindowBuilder) {
+    let event_loop = winit::event_loop::EventLoopBuilder::<T>::with_user_event()
         .build()
         .expect("Failed to build event loop");
 
@@ -80,7 +92,12 @@ pub fn run<A: 'static + Application>(app: A, wb: WindowBuilder) {
         &wgpu::DeviceDescriptor {
             label: None,
             required_features: wgpu::Features::default(),
-            required_limits: wgpu::Limits::default(),
+            // Allow as many texture array layers as the adapter supports, the defaults are
+            // quite conservative
+            required_limits: wgpu::Limits {
+                max_texture_array_layers: adapter.limits().max_texture_array_layers,
+                ..wgpu::Limits::default()
+            },
         },
         None,
     ))
@@ -104,21 +121,23 @@ pub fn run<A: 'static + Application>(app: A, wb: WindowBuilder) {
         format: surface_format,
         width: size.width,
         height: size.height,
-        present_mode: surface_caps.present_modes[0],
+        // Fifo is the only mode every surface supports, apps can change it once they're running
+        present_mode: wgpu::PresentMode::Fifo,
         alpha_mode: surface_caps.alpha_modes[0],
         view_formats: vec![],
         desired_maximum_frame_latency: 2,
     };
     surface.configure(&device, &config);
 
-    let wgpu_state = WgpuState {
+    let wgpu_state = WgpuState::new(
         surface,
         device,
         queue,
         config,
         size,
-        window: &window,
-    };
+        &window,
+        surface_caps.present_modes,
+    );
 
     let egui = EguiManager::new(&wgpu_state.device, surface_format, &event_loop);
 
@@ -132,23 +151,33 @@ pub fn run<A: 'static + Application>(app: A, wb: WindowBuilder) {
 /// # Arguments
 /// * `mut app: Application` - the application you want to run
 /// * `mut context: Context` - A `wgpu_app` Context containing a Display, Egui object and io managers
-/// * `event_loop: EventLoop<()>` - The `EventLoop` for the window
+/// * `event_loop: EventLoop<T>` - The `EventLoop` for the window
 ///
 /// # Panics
 /// On out-of-memory
-pub fn run_with_context<A: 'static + Application>(
+pub fn run_with_context<T: 'static, A: 'static + Application<T>>(
     mut app: A,
     mut context: Context,
-    event_loop: EventLoop<()>,
+    event_loop: EventLoop<T>,
 ) {
+    app.set_event_proxy(event_loop.create_proxy());
+
     let mut t = Timer::new();
+    // Whether the window is covered up or minimized, there's nothing to draw to when it is
+    let mut occluded = false;
+    let mut minimized = false;
+    // Time only passes while the window is both focused and visible, as either can change first
+    let mut focused = true;
 
     t.reset();
     event_loop
         .run(move |ev, control_flow| {
             match &ev {
                 Event::AboutToWait => {
-                    context.wgpu_state.window.request_redraw();
+                    // Otherwise wait for the event that shows the window again
+                    if !occluded && !minimized {
+                        context.wgpu_state.window.request_redraw();
+                    }
                 }
                 Event::NewEvents(cause) => {
                     if matches!(cause, event::StartCause::Init) {
@@ -166,8 +195,14 @@ pub fn run_with_context<A: 'static + Application>(
                     window_id: _,
                     event: event::WindowEvent::RedrawRequested,
                 } => {
+                    if occluded || minimized {
+                        return;
+                    }
+
                     // Update
                     let Some(_) = t.go() else { return };
+                    #[cfg(feature = "gamepad")]
+                    context.gamepad.poll();
                     app.update(&t, &mut context);
                     match app.render(&t, &mut context) {
                         Ok(()) => {}
@@ -182,8 +217,48 @@ pub fn run_with_context<A: 'static + Application>(
 
                     context.mouse.next_frame();
                     context.keyboard.next_frame();
+                    #[cfg(feature = "gamepad")]
+                    context.gamepad.next_frame();
                 }
                 _ => {
+                    // Don't let time build up while the window is in the background. Some
+                    // platforms only report being minimized as a resize to nothing
+                    if let Event::WindowEvent {
+                        window_id: _,
+                        event,
+                    } = &ev
+                    {
+                        let was_active = focused && !occluded && !minimized;
+                        match event {
+                            event::WindowEvent::Focused(now_focused) => focused = *now_focused,
+                            event::WindowEvent::Occluded(hidden) => occluded = *hidden,
+                            event::WindowEvent::Resized(size) => {
+                                minimized = size.width == 0 || size.height == 0;
+                            }
+                            _ => {}
+                        }
+
+                        let active = focused && !occluded && !minimized;
+                        if was_active && !active {
+                            log::debug!("Window in the background, pausing");
+                            t.pause();
+                        } else if active && !was_active {
+                            log::debug!("Window in the foreground, resuming");
+                            t.resume();
+                        }
+                    }
+
+                    // Moving to a monitor with a different DPI changes the window's size in
+                    // pixels, which not every platform follows up with a resize
+                    if let Event::WindowEvent {
+                        window_id: _,
+                        event: event::WindowEvent::ScaleFactorChanged { .. },
+                    } = &ev
+                    {
+                        let size = context.wgpu_state.window.inner_size();
+                        context.wgpu_state.resize(size);
+                    }
+
                     context.handle_event(&ev);
                     app.handle_event(&mut context, &ev);
                 }
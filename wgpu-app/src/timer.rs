@@ -1,5 +1,12 @@
 use std::time::Instant;
 
+/// The most steps `Timer::go_fixed` will return at once, so a long stall doesn't leave the app
+/// trying to catch up with more and more steps
+const MAX_FIXED_STEPS: u32 = 10;
+/// The longest time `Timer::go` will say has passed in one tick, so a stall like dragging the
+/// window doesn't make everything jump forward
+const MAX_DELTA: f64 = 0.1;
+
 pub struct Timer {
     last: Instant,
     fps: u32,
@@ -10,6 +17,9 @@ pub struct Timer {
     fps_update_time: f64,
 
     abs_time: f64,
+    /// Time that hasn't made up a whole step yet, for `go_fixed`
+    accumulator: f64,
+    paused: bool,
 }
 
 /// Keeps track of timing
@@ -26,6 +36,8 @@ impl Timer {
             fps_update_time: 0.25,
 
             abs_time: 0.0,
+            accumulator: 0.0,
+            paused: false,
         }
     }
 
@@ -41,6 +53,8 @@ impl Timer {
             fps_update_time: 0.25,
 
             abs_time: 0.0,
+            accumulator: 0.0,
+            paused: false,
         }
     }
 
@@ -48,24 +62,50 @@ impl Timer {
     pub fn reset(&mut self) {
         self.last = Instant::now();
         self.abs_time = 0.0;
+        self.accumulator = 0.0;
+    }
+
+    /// Stop time from passing, e.g. while the window isn't focused. `go` keeps ticking at the same
+    /// rate but reports no time passing until `resume` is called.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Let time pass again, skipping over however long the timer was paused for
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.last = Instant::now();
+        }
+    }
+
+    #[must_use]
+    pub const fn is_paused(&self) -> bool {
+        self.paused
     }
 
     /// Returns the time since `go()` last returned a value.
     /// If less than `frame_min_duration` has elapsed since this function last returned a value then it will return None,
-    /// indicating it is not yet time for the next tick. Otherwise it will return `Some` containing how much time has elapsed in seconds
+    /// indicating it is not yet time for the next tick. Otherwise it will return `Some` containing how much time has elapsed in seconds,
+    /// which is never more than `MAX_DELTA` and always 0 while paused
     pub fn go(&mut self) -> Option<f64> {
         let now = self.last.elapsed();
         #[allow(clippy::cast_precision_loss)]
-        let delta = (now.as_micros() as f64) / 1_000_000.0;
+        let elapsed = (now.as_micros() as f64) / 1_000_000.0;
 
-        if delta < self.tick_duration {
+        if elapsed < self.tick_duration {
             return None;
         }
+        let delta = if self.paused {
+            0.0
+        } else {
+            elapsed.min(MAX_DELTA)
+        };
 
         self.abs_time += self.last_delta;
 
         self.frame_count += 1;
-        self.frame_time += delta;
+        self.frame_time += elapsed;
         if self.frame_time > self.fps_update_time {
             #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
             let fps = (f64::from(self.frame_count) * (1.0 / self.frame_time)) as u32;
@@ -79,6 +119,33 @@ impl Timer {
         Some(delta)
     }
 
+    /// Returns how many fixed steps of the tick duration have passed since this was last called,
+    /// carrying leftover time over to the next call so steps happen at a steady rate however
+    /// fast frames are. This is an alternative to `go`, a Timer should only use one of them.
+    ///
+    /// At most `MAX_FIXED_STEPS` are returned at once, any more time than that is dropped.
+    pub fn go_fixed(&mut self) -> u32 {
+        let now = Instant::now();
+        let delta = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        if self.paused {
+            return 0;
+        }
+        self.abs_time += delta;
+        self.last_delta = delta;
+
+        self.accumulator += delta;
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let steps = (self.accumulator / self.tick_duration) as u32;
+        if steps > MAX_FIXED_STEPS {
+            self.accumulator = 0.0;
+            return MAX_FIXED_STEPS;
+        }
+
+        self.accumulator -= f64::from(steps) * self.tick_duration;
+        steps
+    }
+
     /// Set how many seconds should pass before the next tick
     pub fn set_tick_duration(&mut self, dur: f64) {
         self.tick_duration = dur;